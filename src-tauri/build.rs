@@ -1,3 +1,30 @@
+use std::fs;
+use std::path::Path;
+
 fn main() {
+    export_whisper_rs_version();
     tauri_build::build()
 }
+
+/// Exposes the locked `whisper-rs` version as `WHISPER_RS_VERSION`, since the
+/// crate does not report its own version at runtime.
+fn export_whisper_rs_version() {
+    let lock_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+    let version = fs::read_to_string(&lock_path)
+        .ok()
+        .and_then(|lock| locked_version(&lock, "whisper-rs"))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=WHISPER_RS_VERSION={version}");
+}
+
+fn locked_version(lock: &str, package: &str) -> Option<String> {
+    let name_line = format!("name = \"{package}\"");
+    let mut lines = lock.lines().map(str::trim);
+    lines.find(|line| *line == name_line)?;
+    lines
+        .next()?
+        .strip_prefix("version = \"")?
+        .strip_suffix('"')
+        .map(str::to_string)
+}
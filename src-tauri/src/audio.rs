@@ -1,27 +1,125 @@
+use crate::logging;
+use crate::whisper;
 use anyhow::{anyhow, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use cpal::{SampleFormat, StreamConfig};
-use serde::Serialize;
+use cpal::{BufferSize, SampleFormat, StreamConfig, SupportedBufferSize};
+use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 #[cfg(target_os = "macos")]
 use block2::RcBlock;
 #[cfg(target_os = "macos")]
 use objc2::runtime::Bool;
 #[cfg(target_os = "macos")]
-use objc2_av_foundation::{AVAuthorizationStatus, AVCaptureDevice, AVMediaTypeAudio};
+use objc2_av_foundation::{AVAuthorizationStatus, AVCaptureDevice, AVMediaType, AVMediaTypeAudio};
 
 pub struct RecordingSession {
     stop_tx: mpsc::Sender<()>,
     worker: Option<JoinHandle<()>>,
     pub samples: Arc<Mutex<Vec<f32>>>,
     reached_capacity: Arc<AtomicBool>,
+    stream_error: Arc<Mutex<Option<String>>>,
+    /// Set while paused; the input callback drops audio but the stream stays open.
+    paused: Arc<AtomicBool>,
+    paused_since: Option<Instant>,
+    /// Paused time before the current pause, if any.
+    paused_total: Duration,
     pub sample_rate: u32,
+    /// The recording limit the capture was opened with.
+    pub max_seconds: u32,
     pub started_at: Instant,
+    warmup_ms: u32,
+    config: CaptureConfig,
+    /// Why the preferred buffer size was not used, when it was not.
+    pub buffer_fallback: Option<String>,
+    /// Why the chosen microphone was not used, when it was not.
+    pub device_fallback: Option<String>,
+    /// Second microphone in dual-mic mode; stopped and merged with this one.
+    secondary: Option<(Box<RecordingSession>, DualMicMode)>,
+    /// Helper threads that live as long as the recording, such as the
+    /// time-warning timer. Woken and joined when it stops.
+    timers: Vec<JoinHandle<()>>,
+}
+
+impl RecordingSession {
+    /// Ties `timer` to this recording so stopping it also joins the thread.
+    /// The thread must exit once the recording is no longer running, and
+    /// should wait with `thread::park_timeout` so it can be woken early.
+    pub fn add_timer(&mut self, timer: JoinHandle<()>) {
+        self.timers.push(timer);
+    }
+
+    fn join_timers(&mut self) {
+        for timer in self.timers.drain(..) {
+            timer.thread().unpark();
+            if !join_with_timeout(timer, WORKER_JOIN_TIMEOUT) {
+                logging::warn("recording timer did not stop in time; abandoning it");
+            }
+        }
+    }
+
+    /// Stops keeping audio without closing the stream, so resuming is
+    /// instant. Returns `false` if already paused.
+    pub fn pause(&mut self) -> bool {
+        if self.paused_since.is_some() {
+            return false;
+        }
+        self.paused.store(true, Ordering::Relaxed);
+        self.paused_since = Some(Instant::now());
+        if let Some((secondary, _)) = self.secondary.as_mut() {
+            secondary.pause();
+        }
+        true
+    }
+
+    /// Returns `false` if the session was not paused.
+    pub fn resume(&mut self) -> bool {
+        let Some(since) = self.paused_since.take() else {
+            return false;
+        };
+        self.paused_total += since.elapsed();
+        self.paused.store(false, Ordering::Relaxed);
+        if let Some((secondary, _)) = self.secondary.as_mut() {
+            secondary.resume();
+        }
+        true
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_since.is_some()
+    }
+
+    /// Total time spent paused so far, including a pause still in progress.
+    pub fn paused_time(&self) -> Duration {
+        self.paused_total
+            + self
+                .paused_since
+                .map_or(Duration::ZERO, |since| since.elapsed())
+    }
+}
+
+/// The input device and format a recording was captured with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CaptureConfig {
+    pub device: Option<String>,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl std::fmt::Display for CaptureConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({} Hz, {} ch)",
+            self.device.as_deref().unwrap_or("unknown device"),
+            self.sample_rate,
+            self.channels
+        )
+    }
 }
 
 pub struct CapturedAudio {
@@ -29,6 +127,100 @@ pub struct CapturedAudio {
     pub sample_rate: u32,
     pub duration_ms: i64,
     pub truncated: bool,
+    /// First error reported by the stream callback after it started, if any.
+    pub stream_error: Option<String>,
+    pub config: CaptureConfig,
+    /// Dual-mic captures only: each microphone on its own, aligned and at
+    /// `sample_rate`, for per-speaker transcription.
+    pub speakers: Option<(Vec<f32>, Vec<f32>)>,
+}
+
+/// How long `start_capture_with_device` waits for the audio thread to report that the
+/// stream is playing before giving up on a wedged driver.
+const STREAM_READY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long stopping a capture waits for the audio thread before abandoning it
+/// to a wedged driver.
+const WORKER_JOIN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Seconds of audio the capture buffer is sized for up front. It grows past
+/// this as needed, so a long recording limit does not reserve its full size
+/// for every short dictation.
+const INITIAL_CAPTURE_SECONDS: usize = 10;
+
+/// Audio dropped at the start of every capture so the transient pop many mics
+/// emit when the stream opens is not transcribed as a spurious word.
+pub const DEFAULT_CAPTURE_WARMUP_MS: u32 = 30;
+pub const MAX_CAPTURE_WARMUP_MS: u32 = 500;
+
+/// How two microphones are combined in the experimental dual-mic mode.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DualMicMode {
+    #[default]
+    Off,
+    /// Average both inputs.
+    Mix,
+    /// Keep whichever input is louder in each 10 ms frame.
+    Loudest,
+}
+
+/// Inputs for dual-mic capture. `None` means the system default input.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct DualMic {
+    pub mode: DualMicMode,
+    pub primary: Option<String>,
+    pub secondary: Option<String>,
+}
+
+/// Rate both dual-mic inputs are resampled to before they are combined.
+const DUAL_MIC_RATE: u32 = 16_000;
+const DUAL_MIC_FRAME: usize = DUAL_MIC_RATE as usize / 100;
+
+pub const MIN_CAPTURE_BUFFER_FRAMES: u32 = 64;
+pub const MAX_CAPTURE_BUFFER_FRAMES: u32 = 8_192;
+
+/// Frames per audio callback. Smaller buffers start delivering audio sooner
+/// but can drop samples (xruns) on a busy machine; larger buffers are more
+/// robust at the cost of latency. `Default` lets the driver choose.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureBufferSize {
+    #[default]
+    Default,
+    Fixed(u32),
+}
+
+impl CaptureBufferSize {
+    pub fn clamped(self) -> Self {
+        match self {
+            Self::Default => Self::Default,
+            Self::Fixed(frames) => {
+                Self::Fixed(frames.clamp(MIN_CAPTURE_BUFFER_FRAMES, MAX_CAPTURE_BUFFER_FRAMES))
+            }
+        }
+    }
+}
+
+/// Picks the buffer to request from the device. A fixed size outside the
+/// range the device reports falls back to the driver default, with a message
+/// saying why; devices that report no range get the requested size as is.
+fn resolve_buffer_size(
+    preference: CaptureBufferSize,
+    supported: &SupportedBufferSize,
+) -> (BufferSize, Option<String>) {
+    let CaptureBufferSize::Fixed(frames) = preference else {
+        return (BufferSize::Default, None);
+    };
+    match supported {
+        SupportedBufferSize::Range { min, max } if !(*min..=*max).contains(&frames) => (
+            BufferSize::Default,
+            Some(format!(
+                "This microphone does not support a {frames}-frame buffer ({min}-{max} allowed); using the default instead."
+            )),
+        ),
+        _ => (BufferSize::Fixed(frames), None),
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize)]
@@ -86,19 +278,188 @@ pub fn input_status() -> AudioInputStatus {
     }
 }
 
-pub fn start_capture(max_seconds: u32) -> Result<RecordingSession> {
+/// How often the device watcher re-enumerates inputs.
+const DEVICE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Polls the input device list in the background and calls `on_change` with a
+/// fresh `AudioInputStatus` whenever devices are plugged in or removed, or the
+/// default input changes. A change must hold for two consecutive polls before it
+/// is reported, so a device that flaps while connecting only fires once.
+pub fn spawn_device_watcher<F>(on_change: F)
+where
+    F: Fn(AudioInputStatus) + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut debouncer = ChangeDebouncer::new(device_fingerprint());
+        loop {
+            thread::sleep(DEVICE_POLL_INTERVAL);
+            if debouncer.observe(device_fingerprint()) {
+                on_change(input_status());
+            }
+        }
+    });
+}
+
+type DeviceFingerprint = (Vec<String>, Option<String>);
+
+fn device_fingerprint() -> DeviceFingerprint {
+    (input_device_names(), default_input_device_name())
+}
+
+/// Name of the system default input device, if there is one.
+pub fn default_input_device_name() -> Option<String> {
+    cpal::default_host()
+        .default_input_device()
+        .and_then(|device| device.name().ok())
+}
+
+/// An input device as offered for selection. cpal has no stable device id,
+/// so the name doubles as one.
+#[derive(Debug, Clone, Serialize)]
+pub struct InputDeviceInfo {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub default_sample_rate: Option<u32>,
+}
+
+/// Every input device, sorted by name.
+pub fn input_devices() -> Vec<InputDeviceInfo> {
+    let host = cpal::default_host();
+    let default_name = host
+        .default_input_device()
+        .and_then(|device| device.name().ok());
+    let mut devices: Vec<InputDeviceInfo> = host
+        .input_devices()
+        .map(|devices| {
+            devices
+                .filter_map(|device| {
+                    let name = device.name().ok()?;
+                    Some(InputDeviceInfo {
+                        id: name.clone(),
+                        is_default: default_name.as_deref() == Some(name.as_str()),
+                        default_sample_rate: device
+                            .default_input_config()
+                            .ok()
+                            .map(|config| config.sample_rate().0),
+                        name,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    devices
+}
+
+/// Names of every input device, sorted.
+pub fn input_device_names() -> Vec<String> {
+    let mut names: Vec<String> = cpal::default_host()
+        .input_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Reports a new value only once it has been observed twice in a row.
+struct ChangeDebouncer<T> {
+    reported: T,
+    pending: Option<T>,
+}
+
+impl<T: PartialEq> ChangeDebouncer<T> {
+    fn new(initial: T) -> Self {
+        Self {
+            reported: initial,
+            pending: None,
+        }
+    }
+
+    fn observe(&mut self, value: T) -> bool {
+        if value == self.reported {
+            self.pending = None;
+            return false;
+        }
+        if self.pending.as_ref() == Some(&value) {
+            self.reported = value;
+            self.pending = None;
+            return true;
+        }
+        self.pending = Some(value);
+        false
+    }
+}
+
+/// Opens the input named `device_id` (the default input when `None`), or both
+/// configured inputs when `dual.mode` is not `Off`; dual-mic mode picks its own
+/// devices and ignores `device_id`. Both streams share one session: stopping
+/// or abandoning it stops both.
+///
+/// A chosen device that is no longer connected falls back to the default
+/// input, with the reason in `device_fallback`.
+pub fn start_capture_with_device(
+    device_id: Option<&str>,
+    max_seconds: u32,
+    warmup_ms: u32,
+    buffer_size: CaptureBufferSize,
+    dual: &DualMic,
+) -> Result<RecordingSession> {
     #[cfg(target_os = "macos")]
     ensure_microphone_permission()?;
 
     let host = cpal::default_host();
-    let device = host
-        .default_input_device()
-        .ok_or_else(|| {
+    if dual.mode == DualMicMode::Off {
+        let (device, device_fallback) = match (find_input_device(&host, device_id), device_id) {
+            (Ok(device), _) => (device, None),
+            (Err(_), Some(id)) => (
+                find_input_device(&host, None)?,
+                Some(format!(
+                    "Microphone '{id}' is not connected. Recording from the default input instead."
+                )),
+            ),
+            (Err(err), None) => return Err(err),
+        };
+        let mut session = open_capture(device, max_seconds, warmup_ms, buffer_size)?;
+        session.device_fallback = device_fallback;
+        return Ok(session);
+    }
+
+    let primary = find_input_device(&host, dual.primary.as_deref())?;
+    let secondary = find_input_device(&host, dual.secondary.as_deref())?;
+    let mut session = open_capture(primary, max_seconds, warmup_ms, buffer_size)?;
+    match open_capture(secondary, max_seconds, warmup_ms, buffer_size) {
+        Ok(second) => {
+            session.secondary = Some((Box::new(second), dual.mode));
+            Ok(session)
+        }
+        Err(err) => {
+            abandon_capture(session);
+            Err(err.context("The second microphone failed to start"))
+        }
+    }
+}
+
+fn find_input_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device> {
+    let Some(name) = name else {
+        return host.default_input_device().ok_or_else(|| {
             anyhow!(
                 "No input microphone device found. Check System Settings > Privacy & Security > Microphone."
             )
-        })?;
+        });
+    };
+    host.input_devices()
+        .map_err(|err| anyhow!("Failed to enumerate input devices: {err}"))?
+        .find(|device| device.name().ok().as_deref() == Some(name))
+        .ok_or_else(|| anyhow!("Input device '{name}' is not connected."))
+}
 
+fn open_capture(
+    device: cpal::Device,
+    max_seconds: u32,
+    warmup_ms: u32,
+    buffer_size: CaptureBufferSize,
+) -> Result<RecordingSession> {
     let supported = device.default_input_config().map_err(|err| {
         anyhow!(
             "Failed to access microphone configuration: {err}. Verify microphone permissions and input device availability."
@@ -106,43 +467,78 @@ pub fn start_capture(max_seconds: u32) -> Result<RecordingSession> {
     })?;
     let sample_rate = supported.sample_rate().0;
     let channels = usize::from(supported.channels());
-    let config: StreamConfig = supported.clone().into();
+    let capture_config = CaptureConfig {
+        device: device.name().ok(),
+        sample_rate,
+        channels: supported.channels(),
+    };
+    let mut config: StreamConfig = supported.clone().into();
+    let (resolved_buffer, buffer_fallback) =
+        resolve_buffer_size(buffer_size, supported.buffer_size());
+    config.buffer_size = resolved_buffer;
+    if let Some(message) = &buffer_fallback {
+        logging::warn(message);
+    }
 
+    let warmup_ms = warmup_ms.min(MAX_CAPTURE_WARMUP_MS);
+    let warmup_frames = sample_rate as usize * warmup_ms as usize / 1000;
     let max_samples = sample_rate as usize * max_seconds as usize;
-    let samples = Arc::new(Mutex::new(Vec::<f32>::with_capacity(max_samples)));
+    let initial_samples = max_samples.min(sample_rate as usize * INITIAL_CAPTURE_SECONDS);
+    let samples = Arc::new(Mutex::new(Vec::<f32>::with_capacity(initial_samples)));
     let samples_for_thread = Arc::clone(&samples);
     let reached_capacity = Arc::new(AtomicBool::new(false));
     let capacity_for_thread = Arc::clone(&reached_capacity);
 
+    let stream_error = Arc::new(Mutex::new(None::<String>));
+    let stream_error_for_thread = Arc::clone(&stream_error);
+    let paused = Arc::new(AtomicBool::new(false));
+    let paused_for_thread = Arc::clone(&paused);
+
     let (stop_tx, stop_rx) = mpsc::channel::<()>();
     let (ready_tx, ready_rx) = mpsc::channel::<Result<()>>();
 
     let worker = thread::spawn(move || {
-        let err_fn = |err| eprintln!("audio stream error: {err}");
+        let err_fn = move |err: cpal::StreamError| {
+            logging::error(format!("audio stream error: {err}"));
+            if let Ok(mut slot) = stream_error_for_thread.lock() {
+                slot.get_or_insert_with(|| err.to_string());
+            }
+        };
 
         let stream = match supported.sample_format() {
-            SampleFormat::F32 => device.build_input_stream(
-                &config,
-                move |data: &[f32], _| {
-                    push_samples_f32(
-                        data,
-                        channels,
-                        max_samples,
-                        &samples_for_thread,
-                        &capacity_for_thread,
-                    )
-                },
-                err_fn,
-                None,
-            ),
+            SampleFormat::F32 => {
+                let mut warmup_left = warmup_frames;
+                device.build_input_stream(
+                    &config,
+                    move |data: &[f32], _| {
+                        if paused_for_thread.load(Ordering::Relaxed) {
+                            return;
+                        }
+                        push_samples_f32(
+                            skip_warmup(data, channels, &mut warmup_left),
+                            channels,
+                            max_samples,
+                            &samples_for_thread,
+                            &capacity_for_thread,
+                        )
+                    },
+                    err_fn,
+                    None,
+                )
+            }
             SampleFormat::I16 => {
                 let samples_for_thread = Arc::clone(&samples_for_thread);
                 let capacity_for_thread = Arc::clone(&capacity_for_thread);
+                let paused_for_thread = Arc::clone(&paused_for_thread);
+                let mut warmup_left = warmup_frames;
                 device.build_input_stream(
                     &config,
                     move |data: &[i16], _| {
+                        if paused_for_thread.load(Ordering::Relaxed) {
+                            return;
+                        }
                         push_samples_i16(
-                            data,
+                            skip_warmup(data, channels, &mut warmup_left),
                             channels,
                             max_samples,
                             &samples_for_thread,
@@ -156,11 +552,16 @@ pub fn start_capture(max_seconds: u32) -> Result<RecordingSession> {
             SampleFormat::U16 => {
                 let samples_for_thread = Arc::clone(&samples_for_thread);
                 let capacity_for_thread = Arc::clone(&capacity_for_thread);
+                let paused_for_thread = Arc::clone(&paused_for_thread);
+                let mut warmup_left = warmup_frames;
                 device.build_input_stream(
                     &config,
                     move |data: &[u16], _| {
+                        if paused_for_thread.load(Ordering::Relaxed) {
+                            return;
+                        }
                         push_samples_u16(
-                            data,
+                            skip_warmup(data, channels, &mut warmup_left),
                             channels,
                             max_samples,
                             &samples_for_thread,
@@ -171,7 +572,12 @@ pub fn start_capture(max_seconds: u32) -> Result<RecordingSession> {
                     None,
                 )
             }
-            _ => Err(cpal::BuildStreamError::StreamConfigNotSupported),
+            other => {
+                let _ = ready_tx.send(Err(anyhow!(
+                    "Unsupported microphone sample format {other:?}. Try a different input device."
+                )));
+                return;
+            }
         };
 
         match stream {
@@ -190,87 +596,314 @@ pub fn start_capture(max_seconds: u32) -> Result<RecordingSession> {
         }
     });
 
-    match ready_rx.recv() {
-        Ok(Ok(())) => Ok(RecordingSession {
-            stop_tx,
-            worker: Some(worker),
-            samples,
-            reached_capacity,
-            sample_rate,
-            started_at: Instant::now(),
-        }),
+    let worker = await_stream_ready(&ready_rx, worker, STREAM_READY_TIMEOUT)?;
+    Ok(RecordingSession {
+        stop_tx,
+        worker: Some(worker),
+        samples,
+        reached_capacity,
+        stream_error,
+        paused,
+        paused_since: None,
+        paused_total: Duration::ZERO,
+        sample_rate,
+        max_seconds,
+        started_at: Instant::now(),
+        warmup_ms,
+        config: capture_config,
+        buffer_fallback,
+        device_fallback: None,
+        secondary: None,
+        timers: Vec::new(),
+    })
+}
+
+/// Waits for the audio thread's startup report so every failure (build, play,
+/// unsupported format, panic, or a hung driver) reaches the caller with a
+/// specific message instead of producing a silent recording.
+fn await_stream_ready(
+    ready_rx: &mpsc::Receiver<Result<()>>,
+    worker: JoinHandle<()>,
+    timeout: Duration,
+) -> Result<JoinHandle<()>> {
+    match ready_rx.recv_timeout(timeout) {
+        Ok(Ok(())) => Ok(worker),
         Ok(Err(err)) => {
             let _ = worker.join();
             Err(err)
         }
-        Err(err) => {
-            let _ = worker.join();
-            Err(anyhow!("Failed to initialize audio thread: {err}"))
+        Err(mpsc::RecvTimeoutError::Disconnected) => match worker.join() {
+            Err(_) => Err(anyhow!("Audio thread crashed while starting the microphone stream.")),
+            Ok(()) => Err(anyhow!(
+                "Audio thread exited before the microphone stream started."
+            )),
+        },
+        // Leave the worker detached: once the caller drops the stop sender the
+        // thread exits on its own if the driver ever returns.
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(anyhow!(
+            "Timed out waiting for the microphone stream to start. The input device may be busy or unresponsive."
+        )),
+    }
+}
+
+/// Microphone authorization as reported by the OS.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub enum MicrophonePermission {
+    Authorized,
+    Denied,
+    Restricted,
+    NotDetermined,
+}
+
+/// Reads the current authorization without prompting. Platforms without a
+/// per-app microphone permission always report `Authorized`.
+pub fn microphone_permission_status() -> Result<MicrophonePermission> {
+    #[cfg(target_os = "macos")]
+    {
+        let media_type = microphone_media_type()?;
+        permission_from_status(unsafe {
+            AVCaptureDevice::authorizationStatusForMediaType(media_type)
+        })
+    }
+    #[cfg(not(target_os = "macos"))]
+    Ok(MicrophonePermission::Authorized)
+}
+
+/// Shows the system prompt if the user has not decided yet and returns the
+/// resulting authorization. Already-decided states are returned as-is.
+pub fn request_microphone_permission() -> Result<MicrophonePermission> {
+    #[cfg(target_os = "macos")]
+    {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let status = microphone_permission_status()?;
+        if status != MicrophonePermission::NotDetermined {
+            return Ok(status);
+        }
+
+        let media_type = microphone_media_type()?;
+        let (tx, rx) = mpsc::channel::<bool>();
+        let handler = RcBlock::new(move |granted: Bool| {
+            let _ = tx.send(granted.as_bool());
+        });
+        unsafe {
+            AVCaptureDevice::requestAccessForMediaType_completionHandler(media_type, &handler);
+        }
+        match rx.recv_timeout(Duration::from_secs(20)) {
+            Ok(true) => Ok(MicrophonePermission::Authorized),
+            Ok(false) => Ok(MicrophonePermission::Denied),
+            Err(_) => Err(anyhow!(
+                "Microphone permission prompt timed out. Open System Settings > Privacy & Security > Microphone and enable Murmur."
+            )),
         }
     }
+    #[cfg(not(target_os = "macos"))]
+    Ok(MicrophonePermission::Authorized)
 }
 
 #[cfg(target_os = "macos")]
-fn ensure_microphone_permission() -> Result<()> {
-    use std::sync::mpsc;
-    use std::time::Duration;
-
-    let media_type = unsafe { AVMediaTypeAudio }
-        .ok_or_else(|| anyhow!("Failed to resolve AVMediaTypeAudio for microphone permission"))?;
+fn microphone_media_type() -> Result<&'static AVMediaType> {
+    unsafe { AVMediaTypeAudio }
+        .ok_or_else(|| anyhow!("Failed to resolve AVMediaTypeAudio for microphone permission"))
+}
 
-    let status = unsafe { AVCaptureDevice::authorizationStatusForMediaType(media_type) };
+#[cfg(target_os = "macos")]
+fn permission_from_status(status: AVAuthorizationStatus) -> Result<MicrophonePermission> {
     match status {
-        AVAuthorizationStatus::Authorized => Ok(()),
-        AVAuthorizationStatus::Restricted => Err(anyhow!(
+        AVAuthorizationStatus::Authorized => Ok(MicrophonePermission::Authorized),
+        AVAuthorizationStatus::Denied => Ok(MicrophonePermission::Denied),
+        AVAuthorizationStatus::Restricted => Ok(MicrophonePermission::Restricted),
+        AVAuthorizationStatus::NotDetermined => Ok(MicrophonePermission::NotDetermined),
+        _ => Err(anyhow!("Unknown microphone authorization status.")),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn ensure_microphone_permission() -> Result<()> {
+    match request_microphone_permission()? {
+        MicrophonePermission::Authorized => Ok(()),
+        MicrophonePermission::Restricted => Err(anyhow!(
             "Microphone access is restricted by system policy."
         )),
-        AVAuthorizationStatus::Denied => Err(anyhow!(
+        MicrophonePermission::Denied | MicrophonePermission::NotDetermined => Err(anyhow!(
             "Microphone access denied. Enable Murmur in System Settings > Privacy & Security > Microphone."
         )),
-        AVAuthorizationStatus::NotDetermined => {
-            let (tx, rx) = mpsc::channel::<bool>();
-            let handler = RcBlock::new(move |granted: Bool| {
-                let _ = tx.send(granted.as_bool());
-            });
-            unsafe {
-                AVCaptureDevice::requestAccessForMediaType_completionHandler(media_type, &handler);
-            }
-            match rx.recv_timeout(Duration::from_secs(20)) {
-                Ok(true) => Ok(()),
-                Ok(false) => Err(anyhow!(
-                    "Microphone access denied. Enable Murmur in System Settings > Privacy & Security > Microphone."
-                )),
-                Err(_) => Err(anyhow!(
-                    "Microphone permission prompt timed out. Open System Settings > Privacy & Security > Microphone and enable Murmur."
-                )),
-            }
-        }
-        _ => Err(anyhow!("Unknown microphone authorization status.")),
     }
 }
 
 pub fn stop_capture(mut session: RecordingSession) -> CapturedAudio {
+    let Some((secondary, mode)) = session.secondary.take() else {
+        return stop_single(session);
+    };
+    // Signal both streams before joining either so they stop together.
+    let _ = secondary.stop_tx.send(());
+    let offset = secondary
+        .started_at
+        .saturating_duration_since(session.started_at);
+    let primary = stop_single(session);
+    let secondary = stop_single(*secondary);
+    merge_dual(primary, secondary, offset, mode)
+}
+
+fn stop_single(mut session: RecordingSession) -> CapturedAudio {
     let _ = session.stop_tx.send(());
-    if let Some(worker) = session.worker.take() {
-        let _ = worker.join();
+    let joined = session
+        .worker
+        .take()
+        .is_none_or(|worker| join_with_timeout(worker, WORKER_JOIN_TIMEOUT));
+    if !joined {
+        logging::warn("audio thread did not stop in time; abandoning it");
     }
+    session.join_timers();
 
-    let duration_ms = session.started_at.elapsed().as_millis() as i64;
+    // The warm-up window and paused stretches were never accumulated, so they
+    // are not part of the recording's duration either.
+    let duration_ms = (session.started_at.elapsed().as_millis() as i64)
+        .saturating_sub(i64::from(session.warmup_ms))
+        .saturating_sub(session.paused_time().as_millis() as i64)
+        .max(0);
     let samples = session
         .samples
         .lock()
         .map_or_else(|_| Vec::new(), |buf| buf.clone());
     let truncated = session.reached_capacity.load(Ordering::Relaxed);
+    let stream_error = session
+        .stream_error
+        .lock()
+        .ok()
+        .and_then(|slot| slot.clone())
+        .or_else(|| {
+            (!joined).then(|| "The audio thread stopped responding and was abandoned.".to_string())
+        });
 
     CapturedAudio {
         samples,
         sample_rate: session.sample_rate,
         duration_ms,
         truncated,
+        stream_error,
+        config: session.config.clone(),
+        speakers: None,
+    }
+}
+
+/// Stops a session whose result is no longer wanted. Returns `false` when the
+/// audio thread had to be left behind.
+pub fn abandon_capture(mut session: RecordingSession) -> bool {
+    let secondary_joined = session
+        .secondary
+        .take()
+        .is_none_or(|(secondary, _)| abandon_capture(*secondary));
+    let _ = session.stop_tx.send(());
+    let joined = session
+        .worker
+        .take()
+        .is_none_or(|worker| join_with_timeout(worker, WORKER_JOIN_TIMEOUT));
+    session.join_timers();
+    joined && secondary_joined
+}
+
+/// Brings both captures to `DUAL_MIC_RATE`, shifts the later-started one by
+/// `offset` so the two line up in time, and combines them per `mode`.
+fn merge_dual(
+    primary: CapturedAudio,
+    secondary: CapturedAudio,
+    offset: Duration,
+    mode: DualMicMode,
+) -> CapturedAudio {
+    let first = whisper::resample_to_16k(&primary.samples, primary.sample_rate);
+    let offset_samples = (offset.as_secs_f64() * f64::from(DUAL_MIC_RATE)) as usize;
+    let mut second = vec![0.0; offset_samples];
+    second.extend(whisper::resample_to_16k(
+        &secondary.samples,
+        secondary.sample_rate,
+    ));
+
+    let samples = combine_dual(&first, &second, mode);
+    let device = |config: &CaptureConfig| {
+        config
+            .device
+            .clone()
+            .unwrap_or_else(|| "unknown device".to_string())
+    };
+    CapturedAudio {
+        duration_ms: samples.len() as i64 * 1000 / i64::from(DUAL_MIC_RATE),
+        samples,
+        sample_rate: DUAL_MIC_RATE,
+        truncated: primary.truncated || secondary.truncated,
+        stream_error: primary.stream_error.or(secondary.stream_error),
+        config: CaptureConfig {
+            device: Some(format!(
+                "{} + {}",
+                device(&primary.config),
+                device(&secondary.config)
+            )),
+            sample_rate: DUAL_MIC_RATE,
+            channels: 1,
+        },
+        speakers: Some((first, second)),
+    }
+}
+
+/// Combines two aligned mono buffers at the same rate. The shorter one is
+/// treated as silent past its end.
+fn combine_dual(first: &[f32], second: &[f32], mode: DualMicMode) -> Vec<f32> {
+    let len = first.len().max(second.len());
+    let at = |buf: &[f32], idx: usize| buf.get(idx).copied().unwrap_or(0.0);
+    match mode {
+        DualMicMode::Off => first.to_vec(),
+        DualMicMode::Mix => (0..len)
+            .map(|idx| (at(first, idx) + at(second, idx)) * 0.5)
+            .collect(),
+        DualMicMode::Loudest => {
+            let mut out = Vec::with_capacity(len);
+            for start in (0..len).step_by(DUAL_MIC_FRAME) {
+                let frame = start..(start + DUAL_MIC_FRAME).min(len);
+                let energy =
+                    |buf: &[f32]| frame.clone().map(|idx| at(buf, idx).powi(2)).sum::<f32>();
+                let louder = if energy(second) > energy(first) {
+                    second
+                } else {
+                    first
+                };
+                out.extend(frame.map(|idx| at(louder, idx)));
+            }
+            out
+        }
     }
 }
 
-pub fn analyze_signal(samples: &[f32]) -> CaptureSignalStats {
+/// Joins `worker` if it finishes within `timeout`; otherwise detaches it so a
+/// driver that never returns cannot block the caller. Returns whether it joined.
+fn join_with_timeout(worker: JoinHandle<()>, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !worker.is_finished() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    let _ = worker.join();
+    true
+}
+
+/// Level above which a sample counts as active when deciding about a
+/// recording, such as rejecting one with no microphone signal. Conservative,
+/// so room noise is not taken for a working mic.
+pub const DEFAULT_DECISION_ACTIVITY_THRESHOLD: f32 = 0.01;
+/// Level above which a sample counts as active on the live level meter. Lower
+/// than the decision threshold so quiet speech still moves the meter; the
+/// meter never decides anything, so a sensitive value costs nothing.
+pub const DEFAULT_METER_ACTIVITY_THRESHOLD: f32 = 0.003;
+pub const MIN_ACTIVITY_THRESHOLD: f32 = 0.000_5;
+pub const MAX_ACTIVITY_THRESHOLD: f32 = 0.1;
+
+/// Level stats for `samples`. `activity_threshold` sets which samples count
+/// toward `active_ratio`: the meter and the decisions use different ones.
+pub fn analyze_signal(samples: &[f32], activity_threshold: f32) -> CaptureSignalStats {
     if samples.is_empty() {
         return CaptureSignalStats {
             rms: 0.0,
@@ -286,7 +919,7 @@ pub fn analyze_signal(samples: &[f32]) -> CaptureSignalStats {
     for &sample in samples {
         let abs = sample.abs();
         peak = peak.max(abs);
-        if abs > 0.01 {
+        if abs > activity_threshold {
             active += 1;
         }
         sum_sq += (sample as f64) * (sample as f64);
@@ -299,6 +932,62 @@ pub fn analyze_signal(samples: &[f32]) -> CaptureSignalStats {
     }
 }
 
+/// Root mean square level of `samples`; 0 when empty.
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_sq: f64 = samples.iter().map(|&s| f64::from(s) * f64::from(s)).sum();
+    (sum_sq / samples.len() as f64).sqrt() as f32
+}
+
+/// A 20 ms frame counts as speech when its RMS reaches this share of the
+/// whole buffer's RMS, so the threshold follows the input level.
+const PAUSE_TRIM_RELATIVE_LEVEL: f32 = 0.25;
+/// Frames quieter than this are never speech, however quiet the buffer is.
+const PAUSE_TRIM_MIN_LEVEL: f32 = 0.002;
+
+/// Shortens quiet stretches longer than `max_pause_ms` to `max_pause_ms`,
+/// keeping the halves next to the speech on either side, so word edges and
+/// short pauses survive untouched. Leading and trailing silence are pauses
+/// too. A buffer with no speech at all comes back unchanged.
+pub fn trim_long_pauses(samples: &[f32], sample_rate: u32, max_pause_ms: u32) -> Vec<f32> {
+    let frame_len = (sample_rate as usize / 50).max(1);
+    let threshold = (rms(samples) * PAUSE_TRIM_RELATIVE_LEVEL).max(PAUSE_TRIM_MIN_LEVEL);
+    let speech: Vec<bool> = samples
+        .chunks(frame_len)
+        .map(|frame| rms(frame) >= threshold)
+        .collect();
+    if !speech.contains(&true) {
+        return samples.to_vec();
+    }
+
+    let keep = (max_pause_ms / 20) as usize;
+    let span =
+        |from: usize, to: usize| &samples[from * frame_len..(to * frame_len).min(samples.len())];
+    let mut out = Vec::with_capacity(samples.len());
+    let mut index = 0;
+    while index < speech.len() {
+        let start = index;
+        let is_speech = speech[index];
+        while index < speech.len() && speech[index] == is_speech {
+            index += 1;
+        }
+        let (head, tail) = match (start == 0, index == speech.len()) {
+            (true, _) => (0, keep - keep / 2),
+            (_, true) => (keep / 2, 0),
+            _ => (keep / 2, keep - keep / 2),
+        };
+        if is_speech || index - start <= head + tail {
+            out.extend_from_slice(span(start, index));
+        } else {
+            out.extend_from_slice(span(start, start + head));
+            out.extend_from_slice(span(index - tail, index));
+        }
+    }
+    out
+}
+
 fn push_samples_f32(
     data: &[f32],
     channels: usize,
@@ -348,6 +1037,17 @@ fn push_samples_u16(
     });
 }
 
+/// Drops whole frames from the front of `data` until the warm-up budget is spent.
+fn skip_warmup<'a, T>(data: &'a [T], channels: usize, warmup_left: &mut usize) -> &'a [T] {
+    if *warmup_left == 0 || channels == 0 {
+        return data;
+    }
+
+    let frames = (data.len() / channels).min(*warmup_left);
+    *warmup_left -= frames;
+    &data[frames * channels..]
+}
+
 fn append_mono<T, F>(
     data: &[T],
     channels: usize,
@@ -378,10 +1078,185 @@ fn append_mono<T, F>(
     }
 }
 
+/// File extensions `read_audio_file` can decode.
+pub const SUPPORTED_AUDIO_EXTENSIONS: &[&str] = &["wav"];
+
+/// Decodes an audio file into mono samples and its sample rate.
+pub fn read_audio_file(path: &std::path::Path) -> Result<(Vec<f32>, u32)> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    if !SUPPORTED_AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(anyhow!(
+            "Unsupported audio file '{}'. Supported formats: {}.",
+            path.display(),
+            SUPPORTED_AUDIO_EXTENSIONS.join(", ")
+        ));
+    }
+    decode_wav(&std::fs::read(path)?)
+}
+
+/// Where the audio kept for transcription `id` lives inside `dir`.
+pub fn kept_audio_path(dir: &std::path::Path, id: i64) -> std::path::PathBuf {
+    dir.join(format!("{id}.wav"))
+}
+
+/// Saves a capture as 16 kHz mono WAV for transcription `id`, the rate
+/// whisper decodes at, so re-transcribing needs no resampling and the file
+/// stays small.
+pub fn keep_audio(dir: &std::path::Path, id: i64, samples: &[f32], sample_rate: u32) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let resampled = whisper::resample_to_16k(samples, sample_rate);
+    write_wav(&kept_audio_path(dir, id), &resampled, 16_000)
+}
+
+/// Deletes kept audio whose transcription `is_kept` rejects, e.g. after
+/// history rows were removed. Files not named after an id are left alone.
+/// Returns the number of files removed.
+pub fn prune_kept_audio(dir: &std::path::Path, is_kept: impl Fn(i64) -> bool) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let mut removed = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("wav") {
+            continue;
+        }
+        let Some(id) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<i64>().ok())
+        else {
+            continue;
+        };
+        if !is_kept(id) {
+            std::fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Writes mono samples as a 16-bit PCM WAV file.
+pub fn write_wav(path: &std::path::Path, samples: &[f32], sample_rate: u32) -> Result<()> {
+    std::fs::write(path, encode_wav(samples, sample_rate))?;
+    Ok(())
+}
+
+fn encode_wav(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut raw = Vec::with_capacity(44 + samples.len() * 2);
+    raw.extend_from_slice(b"RIFF");
+    raw.extend_from_slice(&(36 + data_len).to_le_bytes());
+    raw.extend_from_slice(b"WAVEfmt ");
+    raw.extend_from_slice(&16_u32.to_le_bytes());
+    raw.extend_from_slice(&1_u16.to_le_bytes()); // PCM
+    raw.extend_from_slice(&1_u16.to_le_bytes()); // mono
+    raw.extend_from_slice(&sample_rate.to_le_bytes());
+    raw.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    raw.extend_from_slice(&2_u16.to_le_bytes());
+    raw.extend_from_slice(&16_u16.to_le_bytes());
+    raw.extend_from_slice(b"data");
+    raw.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)).round() as i16;
+        raw.extend_from_slice(&value.to_le_bytes());
+    }
+    raw
+}
+
+/// Parses a RIFF/WAVE file holding 8/16/24/32-bit PCM or 32-bit float
+/// samples, averaging channels down to mono.
+fn decode_wav(raw: &[u8]) -> Result<(Vec<f32>, u32)> {
+    const FORMAT_PCM: u16 = 1;
+    const FORMAT_FLOAT: u16 = 3;
+    const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+    if raw.len() < 12 || &raw[0..4] != b"RIFF" || &raw[8..12] != b"WAVE" {
+        return Err(anyhow!("Not a WAV file"));
+    }
+    let u16_at = |at: usize| u16::from_le_bytes([raw[at], raw[at + 1]]);
+    let u32_at = |at: usize| u32::from_le_bytes([raw[at], raw[at + 1], raw[at + 2], raw[at + 3]]);
+
+    let mut format = None;
+    let mut data = None;
+    let mut offset = 12;
+    while offset + 8 <= raw.len() {
+        let id = &raw[offset..offset + 4];
+        let len = u32_at(offset + 4) as usize;
+        let body = offset + 8;
+        let end = body.saturating_add(len).min(raw.len());
+        match id {
+            b"fmt " if end - body >= 16 => {
+                let mut tag = u16_at(body);
+                if tag == FORMAT_EXTENSIBLE && end - body >= 26 {
+                    // The real format is the first two bytes of the sub-format GUID.
+                    tag = u16_at(body + 24);
+                }
+                format = Some((tag, u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+            }
+            b"data" => data = Some(&raw[body..end]),
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        offset = body.saturating_add(len + (len & 1));
+    }
+
+    let (tag, channels, sample_rate, bits) =
+        format.ok_or_else(|| anyhow!("WAV file has no format chunk"))?;
+    let data = data.ok_or_else(|| anyhow!("WAV file has no audio data"))?;
+    if channels == 0 || sample_rate == 0 {
+        return Err(anyhow!("WAV file has an invalid format chunk"));
+    }
+
+    let sample_bytes = usize::from(bits / 8);
+    let decode: fn(&[u8]) -> f32 = match (tag, bits) {
+        (FORMAT_PCM, 8) => |b| (f32::from(b[0]) - 128.0) / 128.0,
+        (FORMAT_PCM, 16) => |b| f32::from(i16::from_le_bytes([b[0], b[1]])) / 32_768.0,
+        (FORMAT_PCM, 24) => {
+            |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0
+        }
+        (FORMAT_PCM, 32) => {
+            |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0
+        }
+        (FORMAT_FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => {
+            return Err(anyhow!(
+                "Unsupported WAV encoding (format {tag}, {bits}-bit). Use PCM or 32-bit float."
+            ))
+        }
+    };
+
+    let frame_bytes = sample_bytes * usize::from(channels);
+    let samples = data
+        .chunks_exact(frame_bytes)
+        .map(|frame| {
+            let sum: f32 = frame.chunks_exact(sample_bytes).map(decode).sum();
+            sum / f32::from(channels)
+        })
+        .collect();
+    Ok((samples, sample_rate))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn meter_counts_quieter_samples_as_active_than_decisions() {
+        let samples = [0.005_f32, -0.005, 0.02, 0.0];
+        let meter = analyze_signal(&samples, DEFAULT_METER_ACTIVITY_THRESHOLD);
+        let decision = analyze_signal(&samples, DEFAULT_DECISION_ACTIVITY_THRESHOLD);
+
+        assert_eq!(meter.active_ratio, 0.75);
+        assert_eq!(decision.active_ratio, 0.25);
+        assert_eq!(meter.rms, decision.rms);
+        assert!((rms(&samples) - decision.rms).abs() < 1e-6);
+    }
+
     #[test]
     fn append_mono_uses_first_channel() {
         let truncated = Arc::new(AtomicBool::new(false));
@@ -393,6 +1268,172 @@ mod tests {
         assert!(!truncated.load(Ordering::Relaxed));
     }
 
+    #[test]
+    fn resolve_buffer_size_falls_back_outside_device_range() {
+        let range = SupportedBufferSize::Range {
+            min: 128,
+            max: 4096,
+        };
+
+        assert_eq!(
+            resolve_buffer_size(CaptureBufferSize::Fixed(256), &range),
+            (BufferSize::Fixed(256), None)
+        );
+        let (buffer, fallback) = resolve_buffer_size(CaptureBufferSize::Fixed(64), &range);
+        assert_eq!(buffer, BufferSize::Default);
+        assert!(fallback.is_some_and(|message| message.contains("128-4096")));
+        assert_eq!(
+            resolve_buffer_size(CaptureBufferSize::Fixed(64), &SupportedBufferSize::Unknown),
+            (BufferSize::Fixed(64), None)
+        );
+        assert_eq!(
+            resolve_buffer_size(CaptureBufferSize::Default, &range),
+            (BufferSize::Default, None)
+        );
+    }
+
+    #[test]
+    fn combine_dual_mixes_or_picks_the_louder_frame() {
+        let quiet = vec![0.1_f32; DUAL_MIC_FRAME * 2];
+        let mut loud_then_silent = vec![0.8_f32; DUAL_MIC_FRAME];
+        loud_then_silent.extend(vec![0.0_f32; DUAL_MIC_FRAME / 2]);
+
+        let loudest = combine_dual(&quiet, &loud_then_silent, DualMicMode::Loudest);
+        assert_eq!(loudest.len(), DUAL_MIC_FRAME * 2);
+        assert!(loudest[..DUAL_MIC_FRAME].iter().all(|s| *s == 0.8));
+        assert!(loudest[DUAL_MIC_FRAME..].iter().all(|s| *s == 0.1));
+
+        let mixed = combine_dual(&quiet, &loud_then_silent, DualMicMode::Mix);
+        assert!((mixed[0] - 0.45).abs() < 1e-6);
+        assert!((mixed[DUAL_MIC_FRAME * 2 - 1] - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn decode_wav_reads_stereo_pcm_after_extra_chunks() {
+        let frames: [[i16; 2]; 3] = [[16_384, -16_384], [32_767, 32_767], [0, -32_768]];
+        let mut data = Vec::new();
+        for frame in frames {
+            for sample in frame {
+                data.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+
+        let mut raw = b"RIFF\0\0\0\0WAVE".to_vec();
+        raw.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        raw.extend_from_slice(b"fmt \x10\0\0\0");
+        raw.extend_from_slice(&1_u16.to_le_bytes());
+        raw.extend_from_slice(&2_u16.to_le_bytes());
+        raw.extend_from_slice(&44_100_u32.to_le_bytes());
+        raw.extend_from_slice(&(44_100_u32 * 4).to_le_bytes());
+        raw.extend_from_slice(&4_u16.to_le_bytes());
+        raw.extend_from_slice(&16_u16.to_le_bytes());
+        raw.extend_from_slice(b"data");
+        raw.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        raw.extend_from_slice(&data);
+
+        let (samples, rate) = decode_wav(&raw).unwrap();
+        assert_eq!(rate, 44_100);
+        assert_eq!(samples.len(), 3);
+        assert!(samples[0].abs() < 1e-6);
+        assert!((samples[1] - 1.0).abs() < 1e-3);
+        assert!((samples[2] + 0.5).abs() < 1e-6);
+
+        assert!(decode_wav(b"RIFF\0\0\0\0AVI ").is_err());
+    }
+
+    #[test]
+    fn encode_wav_round_trips_through_decode() {
+        let samples = [0.0_f32, 0.5, -0.5, 1.0, -1.0];
+        let (decoded, rate) = decode_wav(&encode_wav(&samples, 16_000)).unwrap();
+
+        assert_eq!(rate, 16_000);
+        assert_eq!(decoded.len(), samples.len());
+        for (got, want) in decoded.iter().zip(samples) {
+            assert!((got - want).abs() < 1e-4, "{got} vs {want}");
+        }
+    }
+
+    #[test]
+    fn prune_kept_audio_removes_only_unkept_ids() {
+        let dir = std::env::temp_dir().join(format!("murmur-audio-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        keep_audio(&dir, 1, &[0.0; 160], 16_000).unwrap();
+        keep_audio(&dir, 2, &[0.0; 160], 16_000).unwrap();
+        std::fs::write(dir.join("notes.wav"), b"").unwrap();
+
+        assert_eq!(prune_kept_audio(&dir, |id| id == 2).unwrap(), 1);
+        assert!(!kept_audio_path(&dir, 1).exists());
+        assert!(kept_audio_path(&dir, 2).exists());
+        assert!(dir.join("notes.wav").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn change_debouncer_waits_for_a_stable_value() {
+        let mut debouncer = ChangeDebouncer::new(1);
+        assert!(!debouncer.observe(1));
+        assert!(!debouncer.observe(2));
+        assert!(!debouncer.observe(3));
+        assert!(debouncer.observe(3));
+        assert!(!debouncer.observe(3));
+        assert!(!debouncer.observe(1));
+        assert!(!debouncer.observe(3));
+    }
+
+    #[test]
+    fn skip_warmup_spans_callbacks() {
+        let mut warmup_left = 3;
+        let first = [1_i16, 1, 2, 2];
+        assert!(skip_warmup(&first, 2, &mut warmup_left).is_empty());
+        assert_eq!(warmup_left, 1);
+
+        let second = [3_i16, 3, 4, 4, 5, 5];
+        assert_eq!(skip_warmup(&second, 2, &mut warmup_left), &[4, 4, 5, 5]);
+        assert_eq!(warmup_left, 0);
+        assert_eq!(skip_warmup(&second, 2, &mut warmup_left), &second);
+    }
+
+    #[test]
+    fn await_stream_ready_reports_worker_error() {
+        let (tx, rx) = mpsc::channel::<Result<()>>();
+        let worker = thread::spawn(move || {
+            let _ = tx.send(Err(anyhow!("Failed to start audio stream: boom")));
+        });
+
+        let err = await_stream_ready(&rx, worker, Duration::from_secs(1)).unwrap_err();
+        assert!(err.to_string().contains("boom"));
+    }
+
+    #[test]
+    fn await_stream_ready_reports_silent_exit_and_panic() {
+        let (tx, rx) = mpsc::channel::<Result<()>>();
+        let worker = thread::spawn(move || drop(tx));
+        let err = await_stream_ready(&rx, worker, Duration::from_secs(1)).unwrap_err();
+        assert!(err.to_string().contains("exited before"));
+
+        let (tx, rx) = mpsc::channel::<Result<()>>();
+        let worker = thread::spawn(move || {
+            let _tx = tx;
+            panic!("driver fault");
+        });
+        let err = await_stream_ready(&rx, worker, Duration::from_secs(1)).unwrap_err();
+        assert!(err.to_string().contains("crashed"));
+    }
+
+    #[test]
+    fn await_stream_ready_times_out_on_hung_worker() {
+        let (tx, rx) = mpsc::channel::<Result<()>>();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let worker = thread::spawn(move || {
+            let _tx = tx;
+            let _ = release_rx.recv();
+        });
+
+        let err = await_stream_ready(&rx, worker, Duration::from_millis(20)).unwrap_err();
+        assert!(err.to_string().contains("Timed out"));
+        drop(release_tx);
+    }
+
     #[test]
     fn append_mono_respects_capacity() {
         let truncated = Arc::new(AtomicBool::new(false));
@@ -403,4 +1444,25 @@ mod tests {
         assert_eq!(out, vec![0.1, 0.2]);
         assert!(truncated.load(Ordering::Relaxed));
     }
+
+    #[test]
+    fn trim_long_pauses_cuts_long_gaps_and_keeps_speech_edges() {
+        let tone = |len: usize| -> Vec<f32> {
+            (0..len).map(|idx| 0.3 * (idx as f32 * 0.2).sin()).collect()
+        };
+        // 0.5 s speech, 3 s silence, 0.5 s speech, 0.3 s silence, 0.5 s speech.
+        let mut input = tone(8_000);
+        input.extend(vec![0.0; 48_000]);
+        input.extend(tone(8_000));
+        input.extend(vec![0.0; 4_800]);
+        input.extend(tone(8_000));
+
+        let trimmed = trim_long_pauses(&input, 16_000, 600);
+
+        // The long gap shrinks to 600 ms; the short one is untouched.
+        assert_eq!(trimmed.len(), 8_000 + 9_600 + 8_000 + 4_800 + 8_000);
+        assert_eq!(&trimmed[..8_000], &input[..8_000]);
+        assert!(trimmed[8_000..17_600].iter().all(|sample| *sample == 0.0));
+        assert_eq!(&trimmed[17_600..], &input[56_000..]);
+    }
 }
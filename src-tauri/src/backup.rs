@@ -0,0 +1,249 @@
+use crate::db;
+use crate::settings;
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Bumped whenever the archive layout changes in a way older builds cannot read.
+const BACKUP_FORMAT_VERSION: u32 = 1;
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DB_ENTRY: &str = "murmur.db";
+const SETTINGS_ENTRY: &str = "settings.json";
+const MODELS_PREFIX: &str = "models/";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub manifest: BackupManifest,
+    pub transcriptions: i64,
+    pub models_restored: Vec<String>,
+    /// Settings from the archive, already validated. The caller applies them
+    /// so hotkeys and cached state are updated the same way as a settings import.
+    #[serde(skip)]
+    pub settings_json: Option<String>,
+}
+
+/// Writes history, settings, and optionally every installed model into one zip
+/// at `dest`. The database is copied with SQLite's online backup API, so
+/// transcriptions saved while exporting cannot leave a torn copy.
+pub fn export(
+    dest: &Path,
+    db_path: &Path,
+    settings_path: &Path,
+    models_dir: &Path,
+    include_models: bool,
+) -> Result<BackupManifest> {
+    let snapshot = scratch_path(&std::env::temp_dir(), "murmur-backup", "db");
+    let result = write_archive(
+        dest,
+        &snapshot,
+        db_path,
+        settings_path,
+        models_dir,
+        include_models,
+    );
+    let _ = fs::remove_file(&snapshot);
+    result
+}
+
+fn write_archive(
+    dest: &Path,
+    snapshot: &Path,
+    db_path: &Path,
+    settings_path: &Path,
+    models_dir: &Path,
+    include_models: bool,
+) -> Result<BackupManifest> {
+    db::backup_to(db_path, snapshot)?;
+
+    let models = if include_models {
+        installed_models(models_dir)?
+    } else {
+        Vec::new()
+    };
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        models: models.clone(),
+    };
+
+    // Build next to the destination and rename at the end so a failed export
+    // never leaves a truncated archive under the chosen name.
+    let partial = dest.with_extension("zip.part");
+    let built = (|| -> Result<()> {
+        let mut zip = ZipWriter::new(File::create(&partial)?);
+        let deflated = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(true);
+        // Model weights barely compress; storing them keeps exports fast.
+        let stored = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(true);
+
+        zip.start_file(MANIFEST_ENTRY, deflated)?;
+        serde_json::to_writer_pretty(&mut zip, &manifest)?;
+
+        zip.start_file(DB_ENTRY, deflated)?;
+        io::copy(&mut File::open(snapshot)?, &mut zip)?;
+
+        if settings_path.exists() {
+            zip.start_file(SETTINGS_ENTRY, deflated)?;
+            io::copy(&mut File::open(settings_path)?, &mut zip)?;
+        }
+
+        for model in &models {
+            zip.start_file(format!("{MODELS_PREFIX}{model}"), stored)?;
+            io::copy(&mut File::open(models_dir.join(model))?, &mut zip)?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    })();
+
+    match built {
+        Ok(()) => {
+            fs::rename(&partial, dest)?;
+            Ok(manifest)
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&partial);
+            Err(err)
+        }
+    }
+}
+
+/// Restores an archive made by `export`: models missing here are copied into
+/// `models_dir`, and the history database replaces the live one in place.
+/// Everything is validated before anything is overwritten.
+pub fn import(src: &Path, db_path: &Path, models_dir: &Path) -> Result<RestoreReport> {
+    let mut archive = ZipArchive::new(File::open(src)?)
+        .with_context(|| format!("{} is not a Murmur backup", src.display()))?;
+
+    let manifest: BackupManifest = serde_json::from_reader(
+        archive
+            .by_name(MANIFEST_ENTRY)
+            .map_err(|_| anyhow!("{} is not a Murmur backup", src.display()))?,
+    )
+    .context("Backup manifest is invalid")?;
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        bail!(
+            "This backup was made by Murmur {} and needs a newer version to restore.",
+            manifest.app_version
+        );
+    }
+
+    let settings_json = match archive.by_name(SETTINGS_ENTRY) {
+        Ok(mut entry) => {
+            let mut raw = String::new();
+            entry.read_to_string(&mut raw)?;
+            settings::parse_import(&raw)
+                .map_err(|err| anyhow!("Backup settings are invalid: {err}"))?;
+            Some(raw)
+        }
+        Err(_) => None,
+    };
+
+    let db_parent = db_path.parent().unwrap_or_else(|| Path::new("."));
+    let candidate = scratch_path(db_parent, "murmur-restore", "db");
+    let result = (|| -> Result<RestoreReport> {
+        io::copy(
+            &mut archive
+                .by_name(DB_ENTRY)
+                .map_err(|_| anyhow!("Backup does not contain a history database"))?,
+            &mut File::create(&candidate)?,
+        )?;
+        db::validate_restore_candidate(&candidate)?;
+
+        let models_restored = extract_models(&mut archive, &manifest, models_dir)?;
+        let transcriptions = db::restore_from(db_path, &candidate)?;
+        Ok(RestoreReport {
+            manifest: manifest.clone(),
+            transcriptions,
+            models_restored,
+            settings_json,
+        })
+    })();
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{suffix}", candidate.display()));
+    }
+    result
+}
+
+/// Copies the archived models that are not installed yet. Existing files are
+/// never overwritten.
+fn extract_models(
+    archive: &mut ZipArchive<File>,
+    manifest: &BackupManifest,
+    models_dir: &Path,
+) -> Result<Vec<String>> {
+    let mut restored = Vec::new();
+    for model in &manifest.models {
+        // Names come from the archive; only plain `.bin` file names may land in
+        // the models directory.
+        let is_plain_name =
+            Path::new(model).file_name().and_then(|name| name.to_str()) == Some(model.as_str());
+        if !is_plain_name || !model.ends_with(".bin") {
+            bail!("Backup lists an invalid model name '{model}'");
+        }
+
+        let destination = models_dir.join(model);
+        if destination.exists() {
+            continue;
+        }
+
+        let partial = models_dir.join(format!("{model}.part"));
+        let mut entry = archive
+            .by_name(&format!("{MODELS_PREFIX}{model}"))
+            .map_err(|_| anyhow!("Backup manifest lists '{model}' but the file is missing"))?;
+        fs::create_dir_all(models_dir)?;
+        let copied = File::create(&partial).and_then(|mut out| io::copy(&mut entry, &mut out));
+        if let Err(err) = copied {
+            let _ = fs::remove_file(&partial);
+            return Err(err.into());
+        }
+        fs::rename(&partial, &destination)?;
+        restored.push(model.clone());
+    }
+    Ok(restored)
+}
+
+fn installed_models(models_dir: &Path) -> Result<Vec<String>> {
+    let mut models = Vec::new();
+    if !models_dir.exists() {
+        return Ok(models);
+    }
+    for entry in fs::read_dir(models_dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("bin") {
+            continue;
+        }
+        if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+            models.push(name.to_string());
+        }
+    }
+    models.sort();
+    Ok(models)
+}
+
+fn scratch_path(dir: &Path, prefix: &str, extension: &str) -> PathBuf {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or(0);
+    dir.join(format!(
+        "{prefix}-{}-{nanos}.{extension}",
+        std::process::id()
+    ))
+}
@@ -1,15 +1,25 @@
 use crate::audio;
-use crate::db;
+use crate::backup;
+use crate::db::{self, TranscriptionSource};
+use crate::export::{self, ExportFormat, SubtitleFormat};
+use crate::logging::{self, LogLevel};
 use crate::models;
-use crate::settings;
-use crate::state::{AppStatus, SharedState};
-use crate::whisper;
+use crate::paste;
+use crate::postprocess::{self, NumberFormat};
+use crate::power::PowerSource;
+use crate::settings::{self, ShortRecordingPolicy};
+use crate::state::{AppStatus, ContinuousStatus, PendingCapture, SharedState, TakenRecording};
+use crate::whisper::{self, TranscriptionMode};
 use anyhow::Result;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::sync::atomic::Ordering;
-use std::time::Instant;
-use tauri::{AppHandle, Emitter, State};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_clipboard_manager::ClipboardExt;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Modifiers, Shortcut};
 
@@ -25,6 +35,47 @@ struct TranscriptionCompletePayload {
     duration_ms: i64,
     model: String,
     auto_copied: bool,
+    /// The text was pasted into the focused app.
+    auto_pasted: bool,
+    verbatim: bool,
+    /// Mean token probability from 0 to 1, for a confidence badge.
+    avg_confidence: Option<f64>,
+}
+
+/// How often the backend proves it is alive to the frontend.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+/// How long a paste keeps the transcription on the clipboard before the
+/// previous contents are put back, when auto-copy is off.
+const CLIPBOARD_RESTORE_DELAY: Duration = Duration::from_millis(500);
+/// How long after startup the active model is preloaded, so loading it does
+/// not compete with the window and tray coming up.
+pub const STARTUP_WARM_UP_DELAY: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Heartbeat {
+    /// Increases by one every `HEARTBEAT_INTERVAL`.
+    beat: u64,
+    /// `None` when the state lock was busy at that instant.
+    status: Option<AppStatus>,
+}
+
+/// Wall-clock breakdown of one recording, from stop to clipboard-ready.
+#[derive(Debug, Clone, Serialize)]
+struct TranscriptionTimingPayload {
+    id: i64,
+    model: String,
+    audio_ms: i64,
+    capture_stop_ms: u64,
+    prepare_ms: u64,
+    model_load_ms: u64,
+    model_cached: bool,
+    decode_ms: u64,
+    /// Everything whisper-related, including a fallback model run.
+    whisper_ms: u64,
+    postprocess_ms: u64,
+    insert_ms: u64,
+    copy_ms: u64,
+    total_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -36,6 +87,62 @@ struct ModelDownloadProgressPayload {
 #[derive(Debug, Clone, Serialize)]
 struct ModelDownloadCompletePayload {
     file_name: String,
+    source_url: String,
+}
+
+/// Sent when a finished download fails its checksum, so the UI can offer a
+/// retry instead of a generic error.
+#[derive(Debug, Clone, Serialize)]
+struct ModelDownloadCorruptPayload {
+    file_name: String,
+    expected: String,
+    actual: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTranscription {
+    id: i64,
+    path: String,
+    text: String,
+    duration_ms: i64,
+    model: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FileTranscriptionStartedPayload {
+    path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FileTranscriptionProgressPayload {
+    path: String,
+    chunk_index: usize,
+    total_chunks: usize,
+    /// Share of the chunks decoded so far, like `model-download-progress`.
+    percent: u8,
+    elapsed_ms: u64,
+    /// Raw text of every chunk decoded so far, before post-processing.
+    partial_text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FileTranscriptionErrorPayload {
+    path: String,
+    message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RecordingTooShortPayload {
+    duration_ms: i64,
+    min_ms: u32,
+    policy: ShortRecordingPolicy,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ModelChangedPayload {
+    file_name: String,
+    /// Set when the switch followed a power source change.
+    power_source: Option<PowerSource>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -43,443 +150,4018 @@ struct HotkeyUpdatedPayload {
     hotkey: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct WindowToggleHotkeyUpdatedPayload {
+    hotkey: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct AutoCopyUpdatedPayload {
     auto_copy: bool,
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct TranscriptionModeUpdatedPayload {
+    mode: TranscriptionMode,
+}
+
+/// What was captured, reported as soon as recording stops and before whisper
+/// runs, so the UI can show capture feedback right away.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingSummary {
+    duration_ms: i64,
+    sample_rate: u32,
+    truncated: bool,
+    signal: audio::CaptureSignalStats,
+}
+
+/// Signal levels before and after the preprocessing whisper applies.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreprocessingPreview {
+    duration_ms: i64,
+    input: audio::CaptureSignalStats,
+    processed: audio::CaptureSignalStats,
+    /// Where the processed 16 kHz audio was written, when asked to.
+    written_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct QuickNoteStartedPayload {
+    duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct QuickNoteCountdownPayload {
+    remaining_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RecordingTimeWarningPayload {
+    remaining_ms: u64,
+    max_ms: u64,
+}
+
+/// Recordings start warning this long before they reach the length cap.
+const RECORDING_WARNING_WINDOW: Duration = Duration::from_secs(5);
+
+/// How often a quick note reports its remaining time.
+const QUICK_NOTE_TICK: Duration = Duration::from_millis(250);
+
+/// How often the input level is reported while recording.
+const CAPTURE_LEVEL_TICK: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize)]
+struct AutoStoppedPayload {
+    silence_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RetryAvailablePayload {
+    duration_ms: i64,
+}
+
+/// How often continuous dictation and auto-stop check the live level for a
+/// pause.
+const CONTINUOUS_POLL: Duration = Duration::from_millis(100);
+/// Silence after speech that ends one utterance.
+const CONTINUOUS_PAUSE: Duration = Duration::from_millis(1200);
+/// RMS above which a poll window counts as speech, for both continuous
+/// dictation and auto-stop.
+const CONTINUOUS_SPEECH_RMS: f32 = 0.01;
+/// Utterances are cut here, or two seconds short of the recording limit when
+/// that is lower.
+const CONTINUOUS_MAX_UTTERANCE: Duration = Duration::from_secs(28);
+/// A continuous session stops on its own after this long.
+const CONTINUOUS_MAX_SESSION: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+struct ContinuousStartedPayload {
+    max_session_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ContinuousUtterancePayload {
+    id: i64,
+    text: String,
+    utterances: usize,
+    elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum ContinuousStopReason {
+    Requested,
+    SessionLimit,
+    /// The recording was stopped or reset outside the session.
+    Interrupted,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ContinuousStoppedPayload {
+    reason: ContinuousStopReason,
+    #[serde(flatten)]
+    session: ContinuousStatus,
+}
+
+/// Why `wait_for_utterance_end` returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UtteranceEnd {
+    Pause,
+    BufferFull,
+    StopRequested,
+    SessionLimit,
+    Superseded,
+}
+
 #[derive(Debug, Clone, Serialize)]
 struct NoticePayload {
     message: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    app_version: String,
+    whisper_rs_version: String,
+    whisper_backend: String,
+    gpu_compiled: bool,
+    os: String,
+    arch: String,
+    cpu_cores: usize,
+    active_model: String,
+    model_header: Option<models::ModelHeader>,
+    model_header_error: Option<String>,
+    report: String,
+}
+
 #[tauri::command]
 pub fn get_app_state(state: State<'_, SharedState>) -> AppStatus {
     state.status()
 }
 
+/// Starts a recording. `mode` picks clean or verbatim output for this
+/// recording only; unset uses the default transcription mode.
 #[tauri::command]
-pub fn start_recording(app: AppHandle, state: State<'_, SharedState>) -> Result<(), String> {
-    start_recording_impl(app, state.inner().clone()).map_err(|e| e.to_string())
+pub fn start_recording(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    mode: Option<TranscriptionMode>,
+) -> Result<(), String> {
+    let mode = mode.unwrap_or_else(|| state.transcription_mode());
+    start_recording_impl(
+        app,
+        state.inner().clone(),
+        TranscriptionSource::Recording,
+        mode,
+    )
+    .map(|_| ())
+    .map_err(|e| e.to_string())
 }
 
+/// Records for the configured quick-note duration, then stops, transcribes,
+/// and copies the result without further interaction.
 #[tauri::command]
-pub async fn stop_recording(app: AppHandle, state: State<'_, SharedState>) -> Result<(), String> {
-    stop_recording_impl(app, state.inner().clone())
-        .await
-        .map_err(|e| e.to_string())
+pub fn start_quick_note(app: AppHandle, state: State<'_, SharedState>) -> Result<(), String> {
+    start_quick_note_impl(app, state.inner().clone()).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-pub async fn toggle_recording(app: AppHandle, state: State<'_, SharedState>) -> Result<(), String> {
-    toggle_recording_impl(app, state.inner().clone())
-        .await
-        .map_err(|e| e.to_string())
+pub fn get_quick_note_seconds(state: State<'_, SharedState>) -> u32 {
+    state.quick_note_seconds()
 }
 
 #[tauri::command]
-pub fn cancel_transcription(app: AppHandle, state: State<'_, SharedState>) -> Result<bool, String> {
-    let requested = state
-        .request_cancel_processing()
-        .map_err(|err| err.to_string())?;
+pub fn set_quick_note_seconds(state: State<'_, SharedState>, seconds: u32) -> Result<u32, String> {
+    state.set_quick_note_seconds(seconds)?;
+    Ok(state.quick_note_seconds())
+}
+
+/// Records utterance after utterance, transcribing each one at the next pause
+/// and copying the text gathered so far, until stopped.
+#[tauri::command]
+pub fn start_continuous_dictation(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+) -> Result<(), String> {
+    start_continuous_impl(app, state.inner().clone()).map_err(|e| e.to_string())
+}
+
+/// Ends continuous dictation once the current utterance is transcribed.
+/// Returns whether a session was running.
+#[tauri::command]
+pub fn stop_continuous_dictation(app: AppHandle, state: State<'_, SharedState>) -> bool {
+    let requested = state.request_stop_continuous();
     if requested {
-        emit_notice(&app, "Cancelling transcription...");
+        emit_notice(&app, "Stopping continuous dictation...");
     }
-    Ok(requested)
+    requested
 }
 
 #[tauri::command]
-pub fn get_history(
-    state: State<'_, SharedState>,
-    limit: Option<i64>,
-) -> Result<Vec<db::HistoryEntry>, String> {
-    let count = limit.unwrap_or(15).clamp(1, 500);
-    db::list(&state.db_path(), count).map_err(|e| e.to_string())
+pub fn get_continuous_dictation(state: State<'_, SharedState>) -> Option<ContinuousStatus> {
+    state.continuous_status()
 }
 
 #[tauri::command]
-pub fn delete_transcription(state: State<'_, SharedState>, id: i64) -> Result<(), String> {
-    db::delete(&state.db_path(), id).map_err(|e| e.to_string())
+pub fn get_max_recording_seconds(state: State<'_, SharedState>) -> u32 {
+    state.settings().max_recording_seconds
 }
 
 #[tauri::command]
-pub fn copy_text(app: AppHandle, text: String) -> Result<(), String> {
-    app.clipboard()
-        .write_text(text)
-        .map_err(|e| format!("Failed to copy text: {e}"))
+pub fn set_max_recording_seconds(
+    state: State<'_, SharedState>,
+    seconds: u32,
+) -> Result<u32, String> {
+    state.save_settings(|path| settings::save_max_recording_seconds(path, seconds))
 }
 
 #[tauri::command]
-pub fn list_models(state: State<'_, SharedState>) -> Result<Vec<models::ModelInfo>, String> {
-    models::list_models(&state.models_dir(), &state.active_model_name()).map_err(|e| e.to_string())
+pub async fn stop_recording(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+) -> Result<RecordingSummary, String> {
+    stop_recording_impl(app, state.inner().clone(), None)
+        .await
+        .map_err(|e| e.to_string())
 }
 
+/// Pauses the recording in progress without closing the microphone, so
+/// resuming picks up instantly. Paused time is left out of the recording.
 #[tauri::command]
-pub fn get_hotkey(state: State<'_, SharedState>) -> String {
-    state.hotkey()
+pub fn pause_recording(app: AppHandle, state: State<'_, SharedState>) -> Result<bool, String> {
+    let paused = state.pause_recording().map_err(|e| e.to_string())?;
+    if paused {
+        crate::set_tray_listening(&app, false);
+        let _ = app.emit("recording-paused", ());
+    }
+    Ok(paused)
 }
 
 #[tauri::command]
-pub fn get_auto_copy(state: State<'_, SharedState>) -> bool {
-    state.auto_copy()
+pub fn resume_recording(app: AppHandle, state: State<'_, SharedState>) -> Result<bool, String> {
+    let resumed = state.resume_recording().map_err(|e| e.to_string())?;
+    if resumed {
+        crate::set_tray_listening(&app, true);
+        let _ = app.emit("recording-resumed", ());
+    }
+    Ok(resumed)
 }
 
+/// Transcribes the last recording whose transcription failed again, e.g.
+/// once a missing model has finished downloading.
 #[tauri::command]
-pub fn set_hotkey(
+pub async fn retry_last_transcription(
     app: AppHandle,
     state: State<'_, SharedState>,
-    hotkey: String,
-) -> Result<String, String> {
-    set_hotkey_impl(app, state.inner().clone(), hotkey).map_err(|e| e.to_string())
+) -> Result<(), String> {
+    retry_last_transcription_impl(app, state.inner().clone())
+        .await
+        .map_err(|e| e.to_string())
 }
 
+/// Starts or stops a recording; `mode` is used as in `start_recording`.
 #[tauri::command]
-pub fn set_auto_copy(
+pub async fn toggle_recording(
     app: AppHandle,
     state: State<'_, SharedState>,
-    enabled: bool,
-) -> Result<bool, String> {
-    state
-        .set_auto_copy(enabled)
-        .map_err(|err| err.to_string())?;
+    mode: Option<TranscriptionMode>,
+) -> Result<(), String> {
+    let mode = mode.unwrap_or_else(|| state.transcription_mode());
+    toggle_recording_impl(
+        app,
+        state.inner().clone(),
+        TranscriptionSource::Recording,
+        mode,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
 
-    let _ = app.emit(
-        "auto-copy-updated",
-        AutoCopyUpdatedPayload { auto_copy: enabled },
-    );
-    Ok(enabled)
+#[tauri::command]
+pub fn cancel_transcription(app: AppHandle, state: State<'_, SharedState>) -> Result<bool, String> {
+    let requested = match state.request_cancel_processing() {
+        Ok(requested) => requested,
+        // File transcriptions run outside the recording status.
+        Err(_) if state.request_cancel_file_job() => true,
+        Err(err) => return Err(err.to_string()),
+    };
+    if requested {
+        emit_notice(&app, "Cancelling transcription...");
+    }
+    Ok(requested)
 }
 
+/// Escape hatch for a session wedged by the audio driver: abandons whatever is
+/// in flight and returns the app to idle.
 #[tauri::command]
-pub fn get_audio_input_status() -> audio::AudioInputStatus {
-    audio::input_status()
+pub async fn force_reset(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+) -> Result<AppStatus, String> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || force_reset_impl(&app, &state))
+        .await
+        .map_err(|e| e.to_string())
 }
 
+/// Last resort after a panic poisoned the app state: rebuilds it as idle and
+/// resynchronizes the tray.
 #[tauri::command]
-pub async fn set_active_model(
+pub async fn recover_state(
     app: AppHandle,
     state: State<'_, SharedState>,
-    file_name: String,
-) -> Result<(), String> {
-    let model_path = state.models_dir().join(&file_name);
+) -> Result<AppStatus, String> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || recover_state_impl(&app, &state))
+        .await
+        .map_err(|e| e.to_string())
+}
 
-    if !model_path.exists() {
-        let app_for_progress = app.clone();
-        let models_dir = state.models_dir();
-        let file_name_for_download = file_name.clone();
+/// Transcribes an audio file with the active model and saves it to history.
+#[tauri::command]
+pub async fn transcribe_file(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    path: String,
+) -> Result<FileTranscription, String> {
+    transcribe_file_impl(app, state.inner().clone(), PathBuf::from(path))
+        .await
+        .map_err(|e| e.to_string())
+}
 
-        let download_result = tauri::async_runtime::spawn_blocking(move || {
-            let mut last_emitted: Option<u8> = None;
-            models::download_model(&models_dir, &file_name_for_download, |percent| {
-                if last_emitted == Some(percent) {
-                    return;
-                }
-                last_emitted = Some(percent);
-                let payload = ModelDownloadProgressPayload {
-                    file_name: file_name_for_download.clone(),
-                    percent,
-                };
-                let _ = app_for_progress.emit("model-download-progress", payload);
-            })
-        })
+/// Transcribes the audio file currently on the clipboard, as copied from a
+/// file manager. Raw audio clips are not readable through the clipboard
+/// plugin, so only copied files are picked up.
+#[tauri::command]
+pub async fn transcribe_clipboard_audio(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+) -> Result<FileTranscription, String> {
+    let copied = app.clipboard().read_text().unwrap_or_default();
+    let path = clipboard_audio_path(&copied).ok_or_else(|| {
+        format!(
+            "The clipboard does not contain an audio file. Copy a {} file and try again.",
+            audio::SUPPORTED_AUDIO_EXTENSIONS.join(", ")
+        )
+    })?;
+    transcribe_file_impl(app, state.inner().clone(), path)
         .await
-        .map_err(|err| format!("Model download task failed: {err}"))?;
+        .map_err(|e| e.to_string())
+}
 
-        download_result.map_err(|err| err.to_string())?;
-        let _ = app.emit(
-            "model-download-complete",
-            ModelDownloadCompletePayload {
-                file_name: file_name.clone(),
-            },
-        );
-    }
+/// Runs the preprocessing chain whisper sees (pause trimming when enabled,
+/// resampling to 16 kHz, cleanup and quiet-input gain) on a WAV file, or the
+/// last recording when `path` is unset, without transcribing. `output_path` saves the result as a WAV file
+/// for A/B listening.
+#[tauri::command]
+pub async fn preview_preprocessing(
+    state: State<'_, SharedState>,
+    path: Option<String>,
+    output_path: Option<String>,
+) -> Result<PreprocessingPreview, String> {
+    let last_capture = state.last_capture();
+    let pause_trim = state.settings().pause_trim;
+    let threshold = state.settings().activity_thresholds.decision;
+    tauri::async_runtime::spawn_blocking(move || {
+        let (samples, sample_rate) = match path {
+            Some(path) => audio::read_audio_file(Path::new(&path))?,
+            None => last_capture
+                .map(|clip| (clip.samples, clip.sample_rate))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Nothing recorded yet. Record a clip or pick a WAV file.")
+                })?,
+        };
+        if let Some(output) = &output_path {
+            if !Path::new(output).is_absolute() {
+                anyhow::bail!("Preview output path must be absolute");
+            }
+        }
 
-    state
-        .set_active_model(file_name)
-        .map_err(|err| err.to_string())
+        let trimmed = apply_pause_trim(&samples, sample_rate, pause_trim);
+        let processed = whisper::prepare_audio(&trimmed, sample_rate);
+        if let Some(output) = &output_path {
+            audio::write_wav(Path::new(output), &processed, 16_000)?;
+        }
+        Ok(PreprocessingPreview {
+            duration_ms: samples.len() as i64 * 1000 / i64::from(sample_rate.max(1)),
+            input: audio::analyze_signal(&samples, threshold),
+            processed: audio::analyze_signal(&processed, threshold),
+            written_to: output_path,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
 }
 
-fn set_hotkey_impl(app: AppHandle, state: SharedState, hotkey: String) -> Result<String> {
-    let new_shortcut = parse_hotkey(&hotkey)?;
-    let old_hotkey = state.hotkey();
-    let old_shortcut = parse_hotkey(&old_hotkey)
-        .or_else(|_| parse_hotkey(settings::DEFAULT_HOTKEY))
-        .map_err(|e| anyhow::anyhow!("Invalid current hotkey: {e}"))?;
-
-    if new_shortcut.id() == old_shortcut.id() {
-        let canonical = new_shortcut.to_string();
-        state
-            .set_hotkey(canonical.clone())
-            .map_err(|e| anyhow::anyhow!(e))?;
-        return Ok(canonical);
+/// Times each pipeline stage on a WAV file with the active model, for
+/// profiling. Nothing is saved to history.
+#[tauri::command]
+pub async fn profile_pipeline(
+    state: State<'_, SharedState>,
+    wav_path: String,
+) -> Result<whisper::PipelineProfile, String> {
+    let model_path = state.active_model_path();
+    if !model_path.exists() {
+        return Err("No installed model available. Download a model or add a .bin file in the models directory.".to_string());
     }
+    let options = transcribe_options(state.inner());
+    tauri::async_runtime::spawn_blocking(move || {
+        let (samples, sample_rate) = audio::read_audio_file(Path::new(&wav_path))?;
+        whisper::profile(&model_path, &samples, sample_rate, options)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
 
-    // Unregistering may fail if the old shortcut was not registered. Continue so
-    // users can recover by selecting a new valid shortcut.
-    let _ = app.global_shortcut().unregister(old_shortcut);
-
-    if let Err(err) = app.global_shortcut().register(new_shortcut) {
-        let _ = app.global_shortcut().register(old_shortcut);
-        anyhow::bail!("Failed to register hotkey (possibly used by another app): {err}");
+/// Transcribes an audio file into timed segments with the active model.
+#[tauri::command]
+pub async fn transcribe_segments(
+    state: State<'_, SharedState>,
+    wav_path: String,
+) -> Result<Vec<whisper::Segment>, String> {
+    let model_path = state.active_model_path();
+    if !model_path.exists() {
+        return Err("No installed model available. Download a model or add a .bin file in the models directory.".to_string());
     }
+    let options = transcribe_options(state.inner());
+    tauri::async_runtime::spawn_blocking(move || {
+        let (samples, sample_rate) = audio::read_audio_file(Path::new(&wav_path))?;
+        whisper::transcribe_with_segments(&model_path, &samples, sample_rate, options, None)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
 
-    let canonical = new_shortcut.to_string();
-    if let Err(err) = state.set_hotkey(canonical.clone()) {
-        let _ = app.global_shortcut().unregister(new_shortcut);
-        let _ = app.global_shortcut().register(old_shortcut);
-        anyhow::bail!(err);
+/// Cheap liveness probe. Never waits on the state lock, so it answers even
+/// while a wedged recording or transcription holds it.
+#[tauri::command]
+pub fn ping(state: State<'_, SharedState>) -> Heartbeat {
+    Heartbeat {
+        beat: state.heartbeat(),
+        status: state.try_status(),
     }
-
-    let _ = app.emit(
-        "hotkey-updated",
-        HotkeyUpdatedPayload {
-            hotkey: canonical.clone(),
-        },
-    );
-
-    Ok(canonical)
 }
 
-fn parse_hotkey(raw: &str) -> Result<Shortcut> {
-    let shortcut =
-        Shortcut::from_str(raw.trim()).map_err(|e| anyhow::anyhow!("Invalid hotkey: {e}"))?;
+#[tauri::command]
+pub fn get_history(
+    state: State<'_, SharedState>,
+    limit: Option<i64>,
+) -> Result<Vec<db::HistoryEntry>, String> {
+    let count = limit.unwrap_or(15).clamp(1, 500);
+    db::list(&state.db_path(), count).map_err(|e| e.to_string())
+}
+
+/// History rows with their text cut to `preview_chars` characters. The detail
+/// view loads the full text with `get_transcription`.
+#[tauri::command]
+pub fn list_history_summaries(
+    state: State<'_, SharedState>,
+    limit: Option<i64>,
+    preview_chars: Option<i64>,
+) -> Result<Vec<db::HistorySummary>, String> {
+    let count = limit.unwrap_or(15).clamp(1, 500);
+    let preview_chars = preview_chars.unwrap_or(120).clamp(1, 2_000) as usize;
+    db::list_summaries(&state.db_path(), count, preview_chars).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_transcription(
+    state: State<'_, SharedState>,
+    id: i64,
+) -> Result<db::HistoryEntry, String> {
+    db::get(&state.db_path(), id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Transcription {id} not found"))
+}
+
+/// Transcriptions containing every word of `query`, best match first.
+#[tauri::command]
+pub fn search_history(
+    state: State<'_, SharedState>,
+    query: String,
+    limit: Option<i64>,
+) -> Result<Vec<db::HistoryEntry>, String> {
+    let count = limit.unwrap_or(50).clamp(1, 500);
+    db::search(&state.db_path(), &query, count).map_err(|e| e.to_string())
+}
+
+/// Writes every transcription matching the search `query` to `path` as JSON,
+/// CSV, or Markdown. Rows are streamed to a temporary file that replaces
+/// `path` only once complete. Returns the number of rows exported.
+#[tauri::command]
+pub async fn export_search_results(
+    state: State<'_, SharedState>,
+    query: String,
+    format: ExportFormat,
+    path: String,
+) -> Result<usize, String> {
+    let dest = PathBuf::from(path);
+    if !dest.is_absolute() {
+        return Err("Export path must be absolute".to_string());
+    }
+    let db_path = state.db_path();
+    tauri::async_runtime::spawn_blocking(move || {
+        export::write_file(&dest, format, |writer| {
+            db::for_each_search_result(&db_path, &query, |entry| writer.entry(&entry)).map(drop)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Writes the whole history to `path` as JSON, CSV, or Markdown, newest
+/// first. Returns the number of rows exported.
+#[tauri::command]
+pub async fn export_history(
+    state: State<'_, SharedState>,
+    format: ExportFormat,
+    path: String,
+) -> Result<usize, String> {
+    let dest = PathBuf::from(path);
+    if !dest.is_absolute() {
+        return Err("Export path must be absolute".to_string());
+    }
+    let db_path = state.db_path();
+    tauri::async_runtime::spawn_blocking(move || {
+        export::write_file(&dest, format, |writer| {
+            db::for_each_entry(&db_path, |entry| writer.entry(&entry)).map(drop)
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+/// Replaces the text of transcription `id`, e.g. after fixing a misheard word
+/// in the history list. Returns the updated row.
+#[tauri::command]
+pub fn update_transcription(
+    state: State<'_, SharedState>,
+    id: i64,
+    text: String,
+) -> Result<db::HistoryEntry, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Transcription text cannot be empty".to_string());
+    }
+    db::update_text(&state.db_path(), id, text).map_err(|e| e.to_string())
+}
+
+/// Decodes the audio kept for transcription `id` again with `model`, e.g. a
+/// larger one after a poor result, and replaces the row's text with the new
+/// transcript. The row keeps its original verbatim mode. Only recordings made
+/// with `keep_audio` on can be re-transcribed. Returns the updated row.
+#[tauri::command]
+pub async fn retranscribe(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    id: i64,
+    model: String,
+) -> Result<db::HistoryEntry, String> {
+    retranscribe_impl(&app, state.inner(), id, &model)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Shown when a file job is refused because another one is running.
+const FILE_JOB_BUSY: &str =
+    "Another file transcription is running. Wait for it to finish or cancel it first.";
+
+async fn retranscribe_impl(
+    app: &AppHandle,
+    state: &SharedState,
+    id: i64,
+    model: &str,
+) -> Result<db::HistoryEntry> {
+    // The new decode must not race a recording that is being saved.
+    if state.status() != AppStatus::Idle {
+        anyhow::bail!("Can't re-transcribe while recording or transcribing. Try again when done.");
+    }
+    let entry = db::get(&state.db_path(), id)?
+        .ok_or_else(|| anyhow::anyhow!("Transcription {id} not found"))?;
+    let audio_path = audio::kept_audio_path(&state.audio_dir(), id);
+    if !audio_path.exists() {
+        anyhow::bail!(
+            "Transcription {id} has no saved audio. Turn on Keep audio to re-transcribe future recordings."
+        );
+    }
+    let model_name = models::resolve_model_name(&state.models_dir(), model)?;
+    let model_path = state.models_dir().join(&model_name);
+    if !model_path.exists() {
+        anyhow::bail!("Model '{model_name}' is not installed. Download it first.");
+    }
+
+    let verbatim = entry.verbatim;
+    let options = whisper::TranscribeOptions {
+        mode: if verbatim {
+            TranscriptionMode::Verbatim
+        } else {
+            TranscriptionMode::Clean
+        },
+        ..transcribe_options(state)
+    };
+    let cancel = state
+        .begin_file_job()
+        .ok_or_else(|| anyhow::anyhow!("{FILE_JOB_BUSY}"))?;
+    let cancel_for_worker = cancel.clone();
+    let decoded = tauri::async_runtime::spawn_blocking(move || {
+        let (samples, sample_rate) = audio::read_audio_file(&audio_path)?;
+        whisper::transcribe(
+            &model_path,
+            &samples,
+            sample_rate,
+            options,
+            Some(cancel_for_worker),
+        )
+    })
+    .await;
+    state.end_file_job(&cancel);
+    notify_gpu_fallback(app);
+    let transcript = decoded??;
+    ensure_replacement_transcript(&transcript, cancel.load(Ordering::Relaxed))?;
+
+    let text = finalize_text(state, &transcript, verbatim);
+    let updated = db::replace_transcript(
+        &state.db_path(),
+        id,
+        &db::NewTranscription {
+            text: &text,
+            model: &model_name,
+            verbatim,
+            language: transcript.language.as_deref(),
+            segments: segments_json(state, &transcript, verbatim).as_deref(),
+            avg_confidence: transcript.avg_confidence(),
+            ..db::NewTranscription::default()
+        },
+    )?;
+    logging::info(format!("re-transcribed {id} with {model_name}"));
+    Ok(updated)
+}
+
+/// An aborted decode comes back as an empty transcript, so a cancelled or
+/// empty re-transcription must not replace the text that is already saved.
+fn ensure_replacement_transcript(transcript: &whisper::Transcript, cancelled: bool) -> Result<()> {
+    if cancelled {
+        anyhow::bail!("Transcription cancelled");
+    }
+    if transcript.text().trim().is_empty() {
+        anyhow::bail!("The new transcription came back empty, so the saved text was kept.");
+    }
+    Ok(())
+}
+
+/// Writes transcription `id` as SRT or WebVTT captions to `path`, from the
+/// segment timings saved with it. Timings are only saved while timestamps are
+/// on in the output settings, and are dropped when the text is edited.
+/// Returns the number of cues written.
+#[tauri::command]
+pub fn export_subtitles(
+    state: State<'_, SharedState>,
+    id: i64,
+    format: SubtitleFormat,
+    path: String,
+) -> Result<usize, String> {
+    let dest = PathBuf::from(path);
+    if !dest.is_absolute() {
+        return Err("Export path must be absolute".to_string());
+    }
+    let raw = db::get_segments(&state.db_path(), id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| {
+            format!(
+                "Transcription {id} has no segment timings to export. They are only saved while \
+                 timestamps are on in the output settings, and are dropped when the text is \
+                 edited."
+            )
+        })?;
+    let segments: Vec<whisper::Segment> = serde_json::from_str(&raw).map_err(|e| e.to_string())?;
+    let document = export::subtitles(&segments, format);
+    std::fs::write(&dest, &document).map_err(|e| e.to_string())?;
+    Ok(document.matches(" --> ").count())
+}
+
+#[tauri::command]
+pub fn delete_transcription(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    id: i64,
+) -> Result<(), String> {
+    guard_destructive(&app, &state, "delete transcriptions")?;
+    db::delete(&state.db_path(), id).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(audio::kept_audio_path(&state.audio_dir(), id));
+    Ok(())
+}
+
+/// Refuses a destructive history command, with a notice, unless the app is
+/// idle. Recording always takes precedence over the window: the hotkey is
+/// never blocked by UI commands, so a click that lands mid-recording is the
+/// one that gets rejected instead of racing the transcription about to be
+/// saved.
+fn guard_destructive(app: &AppHandle, state: &SharedState, action: &str) -> Result<(), String> {
+    if state.status() == AppStatus::Idle {
+        return Ok(());
+    }
+    let message = format!("Can't {action} while recording or transcribing. Try again when done.");
+    emit_notice(app, message.clone());
+    Err(message)
+}
+
+/// Emits a `heartbeat` event every `HEARTBEAT_INTERVAL` so the frontend can
+/// tell a busy backend from a stuck one.
+pub fn spawn_heartbeat(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(HEARTBEAT_INTERVAL);
+        let state = app.state::<SharedState>();
+        let heartbeat = Heartbeat {
+            beat: state.beat(),
+            status: state.try_status(),
+        };
+        let _ = app.emit("heartbeat", heartbeat);
+    });
+}
+
+/// Loads the active model in the background after `delay`, if
+/// `preload_model` is on. The model is read when the delay ends, so a switch
+/// in the meantime warms the new one; a warm-up that finishes after a switch
+/// releases the model it loaded.
+pub fn spawn_model_warm_up(app: AppHandle, delay: Duration) {
+    let state = app.state::<SharedState>().inner().clone();
+    if !state.settings().preload_model {
+        return;
+    }
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let model_path = state.active_model_path();
+        if !model_path.exists() {
+            return;
+        }
+        let use_gpu = state.settings().use_gpu;
+        let started = Instant::now();
+        match whisper::warm_up(&model_path, use_gpu) {
+            Ok(whisper::WarmUp::Cached) => {}
+            Ok(whisper::WarmUp::Loaded) => logging::info(format!(
+                "preloaded {} in {} ms",
+                model_path.display(),
+                started.elapsed().as_millis()
+            )),
+            // The model changed while this one loaded; its own warm-up
+            // takes over.
+            Ok(whisper::WarmUp::Superseded) => {}
+            Err(err) => logging::warn(format!("model preload failed: {err}")),
+        }
+        notify_gpu_fallback(&app);
+    });
+}
+
+/// Flags transcription `id` as wrong, optionally with the corrected text. The
+/// history row is left as it was; corrections stay local to this machine.
+#[tauri::command]
+pub fn submit_correction(
+    state: State<'_, SharedState>,
+    id: i64,
+    corrected_text: Option<String>,
+) -> Result<db::Correction, String> {
+    let corrected_text = corrected_text
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+    db::insert_correction(&state.db_path(), id, corrected_text.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_corrections(
+    state: State<'_, SharedState>,
+    limit: Option<i64>,
+) -> Result<Vec<db::Correction>, String> {
+    let count = limit.unwrap_or(100).clamp(1, 1000);
+    db::list_corrections(&state.db_path(), count).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_empty_transcriptions(
+    state: State<'_, SharedState>,
+    limit: Option<i64>,
+) -> Result<Vec<db::HistoryEntry>, String> {
+    db::list_empty(&state.db_path(), limit.unwrap_or(500).clamp(1, 5_000))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_empty_transcriptions(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+) -> Result<usize, String> {
+    guard_destructive(&app, &state, "delete transcriptions")?;
+    let removed = db::delete_empty(&state.db_path()).map_err(|e| e.to_string())?;
+    prune_orphaned_audio(state.inner());
+    Ok(removed)
+}
+
+/// Deletes the whole history in one go and, unless `vacuum` is false,
+/// compacts the database. Returns the number of rows deleted.
+#[tauri::command]
+pub async fn clear_history(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    vacuum: Option<bool>,
+) -> Result<usize, String> {
+    guard_destructive(&app, &state, "clear history")?;
+    let db_path = state.db_path();
+    let removed = tauri::async_runtime::spawn_blocking(move || {
+        db::clear_all(&db_path, vacuum.unwrap_or(true))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+    prune_orphaned_audio(state.inner());
+    logging::info(format!("cleared history ({removed} transcriptions)"));
+    Ok(removed)
+}
+
+#[tauri::command]
+pub fn get_max_history_rows(state: State<'_, SharedState>) -> Option<u32> {
+    state.settings().max_history_rows
+}
+
+/// Saves the cap and trims history to it right away.
+#[tauri::command]
+pub fn set_max_history_rows(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    max_rows: Option<u32>,
+) -> Result<usize, String> {
+    guard_destructive(&app, &state, "trim history")?;
+    state.save_settings(|path| settings::save_max_history_rows(path, max_rows))?;
+    enforce_history_cap_impl(state.inner()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_history_retention_days(state: State<'_, SharedState>) -> Option<u32> {
+    state.settings().history_retention_days
+}
+
+/// Saves the retention period and prunes history to it right away. `None`
+/// keeps transcriptions forever.
+#[tauri::command]
+pub fn set_history_retention_days(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    days: Option<u32>,
+) -> Result<usize, String> {
+    guard_destructive(&app, &state, "prune history")?;
+    state.save_settings(|path| settings::save_history_retention_days(path, days))?;
+    prune_history_impl(state.inner()).map_err(|e| e.to_string())
+}
+
+/// Applies the retention period and history cap now instead of waiting for
+/// the next transcription. Returns the number of rows removed.
+#[tauri::command]
+pub fn prune_history(app: AppHandle, state: State<'_, SharedState>) -> Result<usize, String> {
+    guard_destructive(&app, &state, "prune history")?;
+    prune_history_impl(state.inner()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn enforce_history_cap(app: AppHandle, state: State<'_, SharedState>) -> Result<usize, String> {
+    guard_destructive(&app, &state, "trim history")?;
+    enforce_history_cap_impl(state.inner()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn dictation_analytics(
+    state: State<'_, SharedState>,
+    bucket: db::AnalyticsBucket,
+    limit: Option<i64>,
+) -> Result<Vec<db::AnalyticsPoint>, String> {
+    let count = limit.unwrap_or(30).clamp(1, 520);
+    db::analytics(&state.db_path(), bucket, count).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn reindex_search(state: State<'_, SharedState>) -> Result<db::SearchIndexReport, String> {
+    db::reindex_search(&state.db_path()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn copy_text(app: AppHandle, text: String) -> Result<(), String> {
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to copy text: {e}"))
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CopyFormat {
+    #[default]
+    Plain,
+    Html,
+}
+
+/// Copies a saved transcription. `Html` puts a formatted version with a
+/// date/model attribution on the clipboard alongside the plain text, so rich
+/// editors get formatting and plain-text targets still get the words.
+#[tauri::command]
+pub fn copy_formatted(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    id: i64,
+    format: Option<CopyFormat>,
+) -> Result<(), String> {
+    let entry = db::get(&state.db_path(), id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Transcription {id} not found"))?;
+
+    let result = match format.unwrap_or_default() {
+        CopyFormat::Plain => app.clipboard().write_text(entry.text),
+        CopyFormat::Html => {
+            let html =
+                postprocess::transcription_html(&entry.text, &entry.created_at, &entry.model);
+            app.clipboard().write_html(html, Some(entry.text))
+        }
+    };
+    result.map_err(|e| format!("Failed to copy text: {e}"))
+}
+
+#[tauri::command]
+pub fn list_models(state: State<'_, SharedState>) -> Result<Vec<models::ModelInfo>, String> {
+    models::list_models(&state.models_dir(), &state.active_model_name()).map_err(|e| e.to_string())
+}
+
+/// Startup check so a read-only models directory is reported before the
+/// first download attempt.
+#[tauri::command]
+pub fn check_models_dir(state: State<'_, SharedState>) -> Result<(), String> {
+    models::ensure_models_dir_writable(&state.models_dir()).map_err(|e| e.to_string())
+}
+
+/// Gives a custom model a friendlier file name, following it with the active
+/// model setting. Returns the new file name.
+#[tauri::command]
+pub fn rename_model(
+    state: State<'_, SharedState>,
+    old_name: String,
+    new_name: String,
+) -> Result<String, String> {
+    let renamed = models::rename_model(&state.models_dir(), &old_name, &new_name)
+        .map_err(|e| e.to_string())?;
+    if renamed != old_name && state.active_model_name() == old_name {
+        state.set_active_model(renamed.clone())?;
+    }
+    Ok(renamed)
+}
+
+/// Deletes an installed model to reclaim disk space. Returns the bytes freed.
+#[tauri::command]
+pub fn delete_model(state: State<'_, SharedState>, file_name: String) -> Result<u64, String> {
+    let freed = models::delete_model(&state.models_dir(), &file_name, &state.active_model_name())
+        .map_err(|e| e.to_string())?;
+    // Free the memory too if the deleted model is still the cached one.
+    whisper::release_context(&state.models_dir().join(&file_name));
+    logging::info(format!("deleted model {file_name} ({freed} bytes)"));
+    Ok(freed)
+}
+
+#[tauri::command]
+pub fn get_model_mirror_prefix(state: State<'_, SharedState>) -> Option<String> {
+    state.settings().model_mirror_prefix
+}
+
+#[tauri::command]
+pub fn set_model_mirror_prefix(
+    state: State<'_, SharedState>,
+    prefix: Option<String>,
+) -> Result<Option<String>, String> {
+    state.save_settings(|path| settings::save_model_mirror_prefix(path, prefix.as_deref()))
+}
+
+#[tauri::command]
+pub fn get_locale(state: State<'_, SharedState>) -> Option<String> {
+    state.settings().locale
+}
+
+#[tauri::command]
+pub fn set_locale(
+    state: State<'_, SharedState>,
+    locale: Option<String>,
+) -> Result<Option<String>, String> {
+    state.save_settings(|path| settings::save_locale(path, locale.as_deref()))
+}
+
+#[tauri::command]
+pub fn get_timestamp_output(state: State<'_, SharedState>) -> settings::TimestampOutput {
+    state.settings().timestamp_output()
+}
+
+#[tauri::command]
+pub fn set_timestamp_output(
+    state: State<'_, SharedState>,
+    output: settings::TimestampOutput,
+) -> Result<settings::TimestampOutput, String> {
+    state.save_settings(|path| settings::save_timestamp_output(path, output))?;
+    Ok(output)
+}
+
+#[tauri::command]
+pub fn get_output_target(state: State<'_, SharedState>) -> settings::OutputTarget {
+    state.settings().output_target
+}
+
+#[tauri::command]
+pub fn set_output_target(
+    state: State<'_, SharedState>,
+    target: settings::OutputTarget,
+) -> Result<settings::OutputTarget, String> {
+    state.save_settings(|path| settings::save_output_target(path, target))
+}
+
+#[tauri::command]
+pub fn get_live_output(state: State<'_, SharedState>) -> settings::LiveOutput {
+    state.settings().live_output()
+}
+
+#[tauri::command]
+pub fn set_live_output(
+    state: State<'_, SharedState>,
+    output: settings::LiveOutput,
+) -> Result<settings::LiveOutput, String> {
+    state.save_settings(|path| settings::save_live_output(path, output))
+}
+
+#[tauri::command]
+pub fn get_hotkey(state: State<'_, SharedState>) -> String {
+    state.hotkey()
+}
+
+#[tauri::command]
+pub fn get_hotkey_mode(state: State<'_, SharedState>) -> settings::HotkeyMode {
+    state.hotkey_mode()
+}
+
+#[tauri::command]
+pub fn set_hotkey_mode(
+    state: State<'_, SharedState>,
+    mode: settings::HotkeyMode,
+) -> Result<settings::HotkeyMode, String> {
+    state.set_hotkey_mode(mode)?;
+    Ok(mode)
+}
+
+#[tauri::command]
+pub fn get_auto_copy(state: State<'_, SharedState>) -> bool {
+    state.auto_copy()
+}
+
+#[tauri::command]
+pub fn set_hotkey(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    hotkey: String,
+) -> Result<String, String> {
+    set_hotkey_impl(app, state.inner().clone(), hotkey).map_err(|e| e.to_string())
+}
+
+/// Re-registers the stored hotkey, for when another app or an OS sleep cycle
+/// silently dropped the global shortcut.
+#[tauri::command]
+pub fn resync_hotkey(app: AppHandle, state: State<'_, SharedState>) -> Result<String, String> {
+    resync_hotkey_impl(app, state.inner().clone()).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyPreview {
+    canonical: String,
+    display: String,
+}
+
+/// Shows what a typed hotkey would be stored and displayed as, without
+/// registering or saving it.
+#[tauri::command]
+pub fn canonicalize_hotkey_preview(raw: String) -> Result<HotkeyPreview, String> {
+    let shortcut = parse_hotkey(&raw).map_err(|e| e.to_string())?;
+    let canonical =
+        settings::canonicalize_hotkey(&raw).ok_or_else(|| "Invalid hotkey format".to_string())?;
+    Ok(HotkeyPreview {
+        canonical,
+        display: settings::display_hotkey(&shortcut, cfg!(target_os = "macos")),
+    })
+}
+
+#[tauri::command]
+pub fn get_window_toggle_hotkey(state: State<'_, SharedState>) -> Option<String> {
+    state.window_toggle_hotkey()
+}
+
+#[tauri::command]
+pub fn set_window_toggle_hotkey(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    hotkey: Option<String>,
+) -> Result<Option<String>, String> {
+    set_window_toggle_hotkey_impl(app, state.inner().clone(), hotkey).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_auto_copy(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    state
+        .set_auto_copy(enabled)
+        .map_err(|err| err.to_string())?;
+
+    let _ = app.emit(
+        "auto-copy-updated",
+        AutoCopyUpdatedPayload { auto_copy: enabled },
+    );
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn get_auto_paste(state: State<'_, SharedState>) -> bool {
+    state.settings().auto_paste
+}
+
+/// Turning auto-paste on asks for the Accessibility permission it needs on
+/// macOS, the same way recording asks for the microphone.
+#[tauri::command]
+pub async fn set_auto_paste(state: State<'_, SharedState>, enabled: bool) -> Result<bool, String> {
+    state.save_settings(|path| settings::save_auto_paste(path, enabled))?;
+    if enabled {
+        tauri::async_runtime::spawn_blocking(paste::request_accessibility_permission)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn get_stream_typing(state: State<'_, SharedState>) -> bool {
+    state.settings().stream_typing
+}
+
+/// Like auto-paste, turning stream typing on asks for the Accessibility
+/// permission on macOS.
+#[tauri::command]
+pub async fn set_stream_typing(
+    state: State<'_, SharedState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    state.save_settings(|path| settings::save_stream_typing(path, enabled))?;
+    if enabled {
+        tauri::async_runtime::spawn_blocking(paste::request_accessibility_permission)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn get_paste_mode(state: State<'_, SharedState>) -> settings::PasteMode {
+    state.settings().paste_mode
+}
+
+#[tauri::command]
+pub fn set_paste_mode(
+    state: State<'_, SharedState>,
+    mode: settings::PasteMode,
+) -> Result<settings::PasteMode, String> {
+    state.save_settings(|path| settings::save_paste_mode(path, mode))?;
+    Ok(mode)
+}
+
+#[tauri::command]
+pub fn get_keep_audio(state: State<'_, SharedState>) -> bool {
+    state.settings().keep_audio
+}
+
+#[tauri::command]
+pub fn set_keep_audio(state: State<'_, SharedState>, enabled: bool) -> Result<bool, String> {
+    state.save_settings(|path| settings::save_keep_audio(path, enabled))?;
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn get_low_priority(state: State<'_, SharedState>) -> bool {
+    state.low_priority()
+}
+
+#[tauri::command]
+pub fn set_low_priority(state: State<'_, SharedState>, enabled: bool) -> Result<bool, String> {
+    state.set_low_priority(enabled)?;
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn get_number_format(state: State<'_, SharedState>) -> NumberFormat {
+    state.number_format()
+}
+
+#[tauri::command]
+pub fn set_number_format(
+    state: State<'_, SharedState>,
+    format: NumberFormat,
+) -> Result<NumberFormat, String> {
+    state.set_number_format(format)?;
+    Ok(format)
+}
+
+/// The mode recordings use when they are started without one.
+#[tauri::command]
+pub fn get_transcription_mode(state: State<'_, SharedState>) -> TranscriptionMode {
+    state.transcription_mode()
+}
+
+#[tauri::command]
+pub fn set_transcription_mode(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    mode: TranscriptionMode,
+) -> Result<TranscriptionMode, String> {
+    state.set_transcription_mode(mode)?;
+    let _ = app.emit(
+        "transcription-mode-updated",
+        TranscriptionModeUpdatedPayload { mode },
+    );
+    Ok(mode)
+}
+
+#[tauri::command]
+pub fn get_short_recording(state: State<'_, SharedState>) -> settings::ShortRecording {
+    state.short_recording()
+}
+
+#[tauri::command]
+pub fn set_short_recording(
+    state: State<'_, SharedState>,
+    short_recording: settings::ShortRecording,
+) -> Result<settings::ShortRecording, String> {
+    state.set_short_recording(short_recording)?;
+    Ok(state.short_recording())
+}
+
+#[tauri::command]
+pub fn get_latency_priority(state: State<'_, SharedState>) -> whisper::LatencyPriority {
+    state.settings().latency_priority
+}
+
+#[tauri::command]
+pub fn set_latency_priority(
+    state: State<'_, SharedState>,
+    priority: whisper::LatencyPriority,
+) -> Result<whisper::LatencyPriority, String> {
+    state.save_settings(|path| settings::save_latency_priority(path, priority))?;
+    Ok(priority)
+}
+
+/// The language setting: an ISO 639-1 code, "auto", or `None` for English
+/// first.
+#[tauri::command]
+pub fn get_language(state: State<'_, SharedState>) -> Option<String> {
+    state.settings().language
+}
+
+/// Sets the language to decode as. Rejects unknown codes, and non-English
+/// languages while an English-only model is active.
+#[tauri::command]
+pub fn set_language(
+    state: State<'_, SharedState>,
+    language: Option<String>,
+) -> Result<Option<String>, String> {
+    let language = language
+        .map(|language| language.trim().to_ascii_lowercase())
+        .filter(|language| !language.is_empty());
+    let parsed = whisper::Language::from_setting(language.as_deref()).map_err(|e| e.to_string())?;
+    let active_model = state.active_model_name();
+    if !parsed.english_only_ok() && active_model.contains(".en.") {
+        return Err(format!(
+            "{active_model} only understands English. Switch to a multilingual model first."
+        ));
+    }
+    state.save_settings(|path| settings::save_language(path, language.clone()))?;
+    Ok(language)
+}
+
+/// Vocabulary whisper is primed with, or `None`.
+#[tauri::command]
+pub fn get_initial_prompt(state: State<'_, SharedState>) -> Option<String> {
+    state.settings().initial_prompt
+}
+
+/// Sets or, with `None` or blank text, clears the initial prompt. Returns the
+/// stored prompt, trimmed.
+#[tauri::command]
+pub fn set_initial_prompt(
+    state: State<'_, SharedState>,
+    prompt: Option<String>,
+) -> Result<Option<String>, String> {
+    let length = prompt
+        .as_deref()
+        .map_or(0, |prompt| prompt.trim().chars().count());
+    if length > whisper::MAX_INITIAL_PROMPT_CHARS {
+        return Err(format!(
+            "The initial prompt is {length} characters; the limit is {}. Keep it to the names and terms whisper gets wrong.",
+            whisper::MAX_INITIAL_PROMPT_CHARS
+        ));
+    }
+    if prompt
+        .as_deref()
+        .is_some_and(|prompt| prompt.contains('\0'))
+    {
+        return Err("The initial prompt cannot contain a null character.".to_string());
+    }
+    state.save_settings(|path| settings::save_initial_prompt(path, prompt))
+}
+
+/// Decode parameters that override the latency priority, or `None` when the
+/// priority's are used.
+#[tauri::command]
+pub fn get_decode_options(state: State<'_, SharedState>) -> Option<whisper::DecodeParams> {
+    state.settings().decode_override
+}
+
+/// Sets or, with `None`, clears the decode override. Returns the values
+/// stored after clamping.
+#[tauri::command]
+pub fn set_decode_options(
+    state: State<'_, SharedState>,
+    options: Option<whisper::DecodeParams>,
+) -> Result<Option<whisper::DecodeParams>, String> {
+    state.save_settings(|path| settings::save_decode_override(path, options))
+}
+
+#[tauri::command]
+pub fn get_debug_mode(state: State<'_, SharedState>) -> bool {
+    state.settings().debug_mode
+}
+
+#[tauri::command]
+pub fn set_debug_mode(state: State<'_, SharedState>, enabled: bool) -> Result<bool, String> {
+    state.save_settings(|path| settings::save_debug_mode(path, enabled))?;
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn get_use_gpu(state: State<'_, SharedState>) -> bool {
+    state.settings().use_gpu
+}
+
+/// Refuses to turn the GPU on when this build has no GPU backend, so the
+/// toggle never looks on while whisper runs on the CPU.
+#[tauri::command]
+pub fn set_use_gpu(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    if enabled && whisper::gpu_backend().is_none() {
+        return Err("This build of Murmur has no GPU support, so whisper runs on the CPU.".into());
+    }
+    state.save_settings(|path| settings::save_use_gpu(path, enabled))?;
+    spawn_model_warm_up(app, Duration::ZERO);
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn get_preload_model(state: State<'_, SharedState>) -> bool {
+    state.settings().preload_model
+}
+
+/// Turning preloading on warms the active model right away.
+#[tauri::command]
+pub fn set_preload_model(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    state.save_settings(|path| settings::save_preload_model(path, enabled))?;
+    spawn_model_warm_up(app, Duration::ZERO);
+    Ok(enabled)
+}
+
+/// The GPU backend whisper was built with ("Metal", "CUDA", "ROCm"), or
+/// `None` when it only runs on the CPU.
+#[tauri::command]
+pub fn get_gpu_backend() -> Option<&'static str> {
+    whisper::gpu_backend()
+}
+
+#[tauri::command]
+pub fn get_deterministic(state: State<'_, SharedState>) -> bool {
+    state.settings().deterministic
+}
+
+#[tauri::command]
+pub fn set_deterministic(state: State<'_, SharedState>, enabled: bool) -> Result<bool, String> {
+    state.save_settings(|path| settings::save_deterministic(path, enabled))?;
+    Ok(enabled)
+}
+
+#[tauri::command]
+pub fn get_speaker_turns(state: State<'_, SharedState>) -> settings::SpeakerTurns {
+    state.settings().speaker_turns
+}
+
+#[tauri::command]
+pub fn set_speaker_turns(
+    state: State<'_, SharedState>,
+    turns: settings::SpeakerTurns,
+) -> Result<settings::SpeakerTurns, String> {
+    state.save_settings(|path| settings::save_speaker_turns(path, turns))
+}
+
+#[tauri::command]
+pub fn get_pause_trim(state: State<'_, SharedState>) -> settings::PauseTrim {
+    state.settings().pause_trim
+}
+
+#[tauri::command]
+pub fn set_pause_trim(
+    state: State<'_, SharedState>,
+    trim: settings::PauseTrim,
+) -> Result<settings::PauseTrim, String> {
+    state.save_settings(|path| settings::save_pause_trim(path, trim))
+}
+
+#[tauri::command]
+pub fn get_activity_thresholds(state: State<'_, SharedState>) -> settings::ActivityThresholds {
+    state.settings().activity_thresholds
+}
+
+/// Sets the activity thresholds of the level meter and of the no-signal
+/// check. Returns the values stored after clamping.
+#[tauri::command]
+pub fn set_activity_thresholds(
+    state: State<'_, SharedState>,
+    thresholds: settings::ActivityThresholds,
+) -> Result<settings::ActivityThresholds, String> {
+    state.save_settings(|path| settings::save_activity_thresholds(path, thresholds))
+}
+
+#[tauri::command]
+pub fn get_auto_stop(state: State<'_, SharedState>) -> settings::AutoStop {
+    state.settings().auto_stop
+}
+
+/// Applies from the next recording. Returns the values stored after clamping.
+#[tauri::command]
+pub fn set_auto_stop(
+    state: State<'_, SharedState>,
+    auto_stop: settings::AutoStop,
+) -> Result<settings::AutoStop, String> {
+    state.save_settings(|path| settings::save_auto_stop(path, auto_stop))
+}
+
+/// Names of every input device. Kept for older callers; `list_audio_devices`
+/// has the details.
+#[tauri::command]
+pub fn list_input_devices() -> Vec<String> {
+    list_audio_devices()
+        .into_iter()
+        .map(|device| device.name)
+        .collect()
+}
+
+#[tauri::command]
+pub fn list_audio_devices() -> Vec<audio::InputDeviceInfo> {
+    audio::input_devices()
+}
+
+#[tauri::command]
+pub fn get_input_device(state: State<'_, SharedState>) -> Option<String> {
+    state.input_device()
+}
+
+/// Records from `device_id` from the next recording on; `None` follows the
+/// system default input.
+#[tauri::command]
+pub fn set_input_device(
+    state: State<'_, SharedState>,
+    device_id: Option<String>,
+) -> Result<Option<String>, String> {
+    let device_id = device_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty());
+    if let Some(id) = &device_id {
+        if !audio::input_devices().iter().any(|device| &device.id == id) {
+            return Err(format!("Input device '{id}' is not connected."));
+        }
+    }
+    state.set_input_device(device_id.clone())?;
+    Ok(device_id)
+}
+
+#[tauri::command]
+pub fn get_dual_mic(state: State<'_, SharedState>) -> audio::DualMic {
+    state.settings().dual_mic()
+}
+
+/// Experimental: records from two microphones at once and mixes them or keeps
+/// the louder one, for two-person setups.
+#[tauri::command]
+pub fn set_dual_mic(
+    state: State<'_, SharedState>,
+    dual: audio::DualMic,
+) -> Result<audio::DualMic, String> {
+    let default_device = audio::default_input_device_name();
+    state.save_settings(|path| settings::save_dual_mic(path, dual, default_device.as_deref()))
+}
+
+#[tauri::command]
+pub fn get_capture_buffer_size(state: State<'_, SharedState>) -> audio::CaptureBufferSize {
+    state.settings().capture_buffer_size
+}
+
+#[tauri::command]
+pub fn set_capture_buffer_size(
+    state: State<'_, SharedState>,
+    buffer_size: audio::CaptureBufferSize,
+) -> Result<audio::CaptureBufferSize, String> {
+    state.save_settings(|path| settings::save_capture_buffer_size(path, buffer_size))
+}
+
+#[tauri::command]
+pub fn get_capture_warmup_ms(state: State<'_, SharedState>) -> u32 {
+    state.capture_warmup_ms()
+}
+
+#[tauri::command]
+pub fn set_capture_warmup_ms(state: State<'_, SharedState>, warmup_ms: u32) -> Result<u32, String> {
+    state.set_capture_warmup_ms(warmup_ms)?;
+    Ok(state.capture_warmup_ms())
+}
+
+#[tauri::command]
+pub fn export_settings(state: State<'_, SharedState>) -> Result<String, String> {
+    settings::export(&state.settings_path())
+}
+
+#[tauri::command]
+pub fn import_settings(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    json: String,
+) -> Result<(), String> {
+    import_settings_impl(app, state.inner().clone(), &json).map_err(|e| e.to_string())
+}
+
+/// Bundles history, settings, and optionally the installed models into a zip
+/// for moving Murmur to another machine.
+#[tauri::command]
+pub async fn export_backup(
+    state: State<'_, SharedState>,
+    dest_zip: String,
+    include_models: bool,
+) -> Result<backup::BackupManifest, String> {
+    let state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        backup::export(
+            Path::new(&dest_zip),
+            &state.db_path(),
+            &state.settings_path(),
+            &state.models_dir(),
+            include_models,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn import_backup(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    src_zip: String,
+) -> Result<backup::RestoreReport, String> {
+    guard_destructive(&app, &state, "restore a backup")?;
+
+    let state = state.inner().clone();
+    let (db_path, models_dir) = (state.db_path(), state.models_dir());
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        backup::import(Path::new(&src_zip), &db_path, &models_dir)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    // Restored rows reuse ids, so audio kept for the old history would be
+    // attached to the wrong transcriptions.
+    if let Err(err) = audio::prune_kept_audio(&state.audio_dir(), |_| false) {
+        logging::warn(format!(
+            "kept audio could not be cleared after restore: {err}"
+        ));
+    }
+    // History and models are already in place; a settings failure is
+    // reported without undoing them.
+    if let Some(json) = &report.settings_json {
+        if let Err(err) = import_settings_impl(app.clone(), state.clone(), json) {
+            emit_notice(
+                &app,
+                format!("History was restored, but the backup's settings were not applied: {err}"),
+            );
+        }
+    }
+    logging::info(format!(
+        "restored backup from Murmur {}: {} transcriptions, {} model(s)",
+        report.manifest.app_version,
+        report.transcriptions,
+        report.models_restored.len()
+    ));
+    let _ = app.emit("backup-restored", report.clone());
+    Ok(report)
+}
+
+/// Forgets the saved window placement and centers the window, for when it
+/// ended up somewhere unreachable.
+#[tauri::command]
+pub fn reset_window_position(app: AppHandle, state: State<'_, SharedState>) -> Result<(), String> {
+    state.save_settings(|path| settings::save_window_geometry(path, None))?;
+    if let Some(window) = app.get_webview_window("main") {
+        window
+            .center()
+            .map_err(|e| format!("Failed to center window: {e}"))?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_log_path() -> Result<String, String> {
+    logging::path()
+        .map(|path| path.to_string_lossy().into_owned())
+        .ok_or_else(|| "File logging is not initialized".to_string())
+}
+
+#[tauri::command]
+pub fn open_log() -> Result<(), String> {
+    let path = logging::path().ok_or_else(|| "File logging is not initialized".to_string())?;
+    if !path.exists() {
+        return Err("Nothing has been logged yet".to_string());
+    }
+    open_with_system(&path, false)
+}
+
+/// Opens the audio kept for transcription `id` in the default player, or
+/// with `reveal` shows it in the file manager instead.
+#[tauri::command]
+pub fn open_transcription_audio(
+    state: State<'_, SharedState>,
+    id: i64,
+    reveal: Option<bool>,
+) -> Result<(), String> {
+    let path = audio::kept_audio_path(&state.audio_dir(), id);
+    if !path.exists() {
+        return Err(format!(
+            "Transcription {id} has no saved audio. Turn on Keep audio to save future recordings."
+        ));
+    }
+    open_with_system(&path, reveal.unwrap_or(false))
+}
+
+/// Hands `path` to the OS: opens it with its default app, or with `reveal`
+/// selects it in the file manager. Linux file managers have no common way to
+/// select a file, so there the folder is opened.
+fn open_with_system(path: &Path, reveal: bool) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let mut command = {
+        let mut command = std::process::Command::new("open");
+        if reveal {
+            command.arg("-R");
+        }
+        command.arg(path);
+        command
+    };
+    #[cfg(target_os = "windows")]
+    let mut command = if reveal {
+        use std::os::windows::process::CommandExt;
+
+        // explorer parses its own command line; quoting the path keeps
+        // commas and spaces in it from splitting the argument.
+        let mut command = std::process::Command::new("explorer");
+        command.raw_arg(format!("/select,\"{}\"", path.display()));
+        command
+    } else {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]).arg(path);
+        command
+    };
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    let mut command = {
+        let mut command = std::process::Command::new("xdg-open");
+        match path.parent().filter(|_| reveal) {
+            Some(folder) => command.arg(folder),
+            None => command.arg(path),
+        };
+        command
+    };
+
+    command
+        .spawn()
+        .map(|_| ())
+        .map_err(|err| format!("Failed to open {}: {err}", path.display()))
+}
+
+#[tauri::command]
+pub fn get_log_level(state: State<'_, SharedState>) -> LogLevel {
+    state.settings().log_level
+}
+
+#[tauri::command]
+pub fn set_log_level(state: State<'_, SharedState>, level: LogLevel) -> Result<LogLevel, String> {
+    state.set_log_level(level)?;
+    Ok(level)
+}
+
+#[tauri::command]
+pub fn get_db_tuning() -> db::DbTuning {
+    db::tuning()
+}
+
+#[tauri::command]
+pub fn set_db_tuning(
+    state: State<'_, SharedState>,
+    synchronous: db::SyncMode,
+    busy_timeout_ms: u64,
+) -> Result<db::DbTuning, String> {
+    state.set_db_tuning(db::DbTuning {
+        synchronous,
+        busy_timeout_ms,
+    })?;
+    Ok(db::tuning())
+}
+
+#[tauri::command]
+pub fn system_info(state: State<'_, SharedState>) -> SystemInfo {
+    let active_model = state.active_model_name();
+    let (model_header, model_header_error) =
+        match models::read_model_header(&state.active_model_path()) {
+            Ok(header) => (Some(header), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+
+    let mut info = SystemInfo {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        whisper_rs_version: whisper::WHISPER_RS_VERSION.to_string(),
+        whisper_backend: whisper::backend_info(),
+        gpu_compiled: whisper::GPU_COMPILED,
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        cpu_cores: std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1),
+        active_model,
+        model_header,
+        model_header_error,
+        report: String::new(),
+    };
+    info.report = format_system_report(&info);
+    info
+}
+
+fn format_system_report(info: &SystemInfo) -> String {
+    let model_line = match (&info.model_header, &info.model_header_error) {
+        (Some(header), _) => format!(
+            "{} ({}, {}, {}, ~{}M params)",
+            info.active_model,
+            header.model_type,
+            if header.multilingual {
+                "multilingual"
+            } else {
+                "English-only"
+            },
+            header.ftype,
+            header
+                .approx_params_millions
+                .map(|value| value.to_string())
+                .unwrap_or_else(|| "?".to_string())
+        ),
+        (None, Some(err)) => format!("{} (header unreadable: {err})", info.active_model),
+        (None, None) => info.active_model.clone(),
+    };
+
+    format!(
+        "Murmur {}\nwhisper-rs {} (GPU compiled: {})\nwhisper.cpp: {}\nOS: {} {}\nCPU cores: {}\nModel: {}",
+        info.app_version,
+        info.whisper_rs_version,
+        if info.gpu_compiled { "yes" } else { "no" },
+        info.whisper_backend,
+        info.os,
+        info.arch,
+        info.cpu_cores,
+        model_line
+    )
+}
+
+/// How many recent runs per model feed `estimate_transcription_time`.
+const BENCHMARK_WINDOW: i64 = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionEstimate {
+    model: String,
+    low_ms: u64,
+    high_ms: u64,
+    /// Number of past runs the estimate is based on; 0 means a size heuristic.
+    benchmark_runs: usize,
+}
+
+#[tauri::command]
+pub fn estimate_transcription_time(
+    state: State<'_, SharedState>,
+    duration_ms: u64,
+    model: Option<String>,
+) -> Result<TranscriptionEstimate, String> {
+    let model = model.unwrap_or_else(|| state.active_model_name());
+    let factors = db::realtime_factors(&state.db_path(), &model, BENCHMARK_WINDOW)
+        .map_err(|e| e.to_string())?;
+    let approx_params = models::read_model_header(&state.models_dir().join(&model))
+        .ok()
+        .and_then(|header| header.approx_params_millions);
+    let (low_ms, high_ms) =
+        whisper::estimate_transcription_ms(duration_ms, &factors, approx_params);
+
+    Ok(TranscriptionEstimate {
+        model,
+        low_ms,
+        high_ms,
+        benchmark_runs: factors.len(),
+    })
+}
+
+/// Reads microphone authorization without triggering the system prompt.
+#[tauri::command]
+pub fn microphone_permission_status() -> Result<audio::MicrophonePermission, String> {
+    audio::microphone_permission_status().map_err(|e| e.to_string())
+}
+
+/// Whether auto-paste may send keystrokes to other apps. Always `true` off
+/// macOS.
+#[tauri::command]
+pub fn accessibility_permission_status() -> bool {
+    paste::accessibility_permission_status()
+}
+
+/// Shows the Accessibility prompt if access has not been granted.
+#[tauri::command]
+pub async fn request_accessibility_permission() -> Result<bool, String> {
+    tauri::async_runtime::spawn_blocking(paste::request_accessibility_permission)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Shows the microphone prompt if the user has not been asked yet.
+#[tauri::command]
+pub async fn request_microphone_permission() -> Result<audio::MicrophonePermission, String> {
+    tauri::async_runtime::spawn_blocking(audio::request_microphone_permission)
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_audio_input_status() -> audio::AudioInputStatus {
+    audio::input_status()
+}
+
+/// Downloads a known model with progress events. Shared by model selection
+/// and resuming a paused download.
+async fn download_model_impl(
+    app: &AppHandle,
+    state: &SharedState,
+    file_name: &str,
+) -> Result<(), String> {
+    let models_dir = state.models_dir();
+    models::ensure_models_dir_writable(&models_dir).map_err(|e| e.to_string())?;
+    let cancel = state
+        .begin_download(file_name)
+        .ok_or_else(|| format!("'{file_name}' is already downloading"))?;
+    let app_for_progress = app.clone();
+    let file_name_for_download = file_name.to_string();
+    let mirror_prefix = state.settings().model_mirror_prefix;
+
+    let cancel_for_download = cancel.clone();
+    let download_result = tauri::async_runtime::spawn_blocking(move || {
+        let mut last_emitted: Option<u8> = None;
+        models::download_model(
+            &models_dir,
+            &file_name_for_download,
+            mirror_prefix.as_deref(),
+            &cancel_for_download,
+            |percent| {
+                if last_emitted == Some(percent) {
+                    return;
+                }
+                last_emitted = Some(percent);
+                let payload = ModelDownloadProgressPayload {
+                    file_name: file_name_for_download.clone(),
+                    percent,
+                };
+                let _ = app_for_progress.emit("model-download-progress", payload);
+            },
+        )
+    })
+    .await;
+    state.end_download(file_name);
+    let download_result =
+        download_result.map_err(|err| format!("Model download task failed: {err}"))?;
+
+    let source_url = download_result.map_err(|err| {
+        if cancel.load(Ordering::Relaxed) {
+            logging::info(format!("model download paused for {file_name}"));
+        } else if let Some(mismatch) = err.downcast_ref::<models::ChecksumMismatch>() {
+            logging::error(format!("model download corrupted for {file_name}: {err}"));
+            let _ = app.emit(
+                "model-download-corrupt",
+                ModelDownloadCorruptPayload {
+                    file_name: file_name.to_string(),
+                    expected: mismatch.expected.clone(),
+                    actual: mismatch.actual.clone(),
+                },
+            );
+        } else {
+            logging::error(format!("model download failed for {file_name}: {err}"));
+        }
+        err.to_string()
+    })?;
+    logging::info(format!("downloaded {file_name} from {source_url}"));
+    let _ = app.emit(
+        "model-download-complete",
+        ModelDownloadCompletePayload {
+            file_name: file_name.to_string(),
+            source_url,
+        },
+    );
+    Ok(())
+}
+
+/// Pauses a running model download. The `.part` file is kept so the download
+/// shows up in `list_resumable_downloads`.
+#[tauri::command]
+pub fn cancel_model_download(state: State<'_, SharedState>, file_name: String) -> bool {
+    state.request_cancel_download(&file_name)
+}
+
+/// Partially downloaded models that are not downloading right now.
+#[tauri::command]
+pub fn list_resumable_downloads(
+    state: State<'_, SharedState>,
+) -> Result<Vec<models::PartialDownload>, String> {
+    let partials = models::partial_downloads(&state.models_dir()).map_err(|e| e.to_string())?;
+    Ok(partials
+        .into_iter()
+        .filter(|partial| !state.is_downloading(&partial.file_name))
+        .collect())
+}
+
+/// Restarts a paused download without switching the active model.
+#[tauri::command]
+pub async fn resume_model_download(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    file_name: String,
+) -> Result<(), String> {
+    download_model_impl(&app, state.inner(), &file_name).await
+}
+
+#[tauri::command]
+pub fn discard_model_download(
+    state: State<'_, SharedState>,
+    file_name: String,
+) -> Result<bool, String> {
+    if state.is_downloading(&file_name) {
+        return Err(format!(
+            "'{file_name}' is still downloading; pause it first"
+        ));
+    }
+    models::discard_partial(&state.models_dir(), &file_name).map_err(|e| e.to_string())
+}
+
+/// Switches to a model, downloading it first if needed. `file_name` may also
+/// be a label or custom model name; see `models::resolve_model_name`. Returns
+/// the file name that was activated.
+#[tauri::command]
+pub async fn set_active_model(
+    app: AppHandle,
+    state: State<'_, SharedState>,
+    file_name: String,
+) -> Result<String, String> {
+    let file_name =
+        models::resolve_model_name(&state.models_dir(), &file_name).map_err(|e| e.to_string())?;
+    let model_path = state.models_dir().join(&file_name);
+
+    if !model_path.exists() {
+        download_model_impl(&app, state.inner(), &file_name).await?;
+    }
+
+    state.set_active_model(file_name.clone())?;
+    let _ = app.emit(
+        "model-changed",
+        ModelChangedPayload {
+            file_name: file_name.clone(),
+            power_source: None,
+        },
+    );
+    spawn_model_warm_up(app, Duration::ZERO);
+    Ok(file_name)
+}
+
+#[tauri::command]
+pub fn get_power_models(state: State<'_, SharedState>) -> settings::PowerModels {
+    state.settings().power_models
+}
+
+#[tauri::command]
+pub fn set_power_models(
+    state: State<'_, SharedState>,
+    models: settings::PowerModels,
+) -> Result<settings::PowerModels, String> {
+    state.save_settings(|path| settings::save_power_models(path, models))
+}
+
+/// Switches to the model configured for `source`, if it is installed. Returns
+/// `false` while a recording or transcription is running so the power watcher
+/// offers the change again once the app is idle.
+pub fn apply_power_model(app: &AppHandle, state: &SharedState, source: PowerSource) -> bool {
+    if state.status() != AppStatus::Idle {
+        return false;
+    }
+
+    let power_models = state.settings().power_models;
+    let Some(file_name) = power_models.for_source(source) else {
+        return true;
+    };
+    if file_name == state.active_model_name() {
+        return true;
+    }
+    if !state.models_dir().join(file_name).exists() {
+        logging::warn(format!(
+            "{file_name} is set for {source:?} power but is not installed"
+        ));
+        return true;
+    }
+
+    match state.set_active_model(file_name.to_string()) {
+        Ok(()) => {
+            logging::info(format!("switched to {file_name} on {source:?} power"));
+            let _ = app.emit(
+                "model-changed",
+                ModelChangedPayload {
+                    file_name: file_name.to_string(),
+                    power_source: Some(source),
+                },
+            );
+            spawn_model_warm_up(app.clone(), Duration::ZERO);
+        }
+        Err(err) => logging::warn(format!("power model switch to {file_name} failed: {err}")),
+    }
+    true
+}
+
+fn set_hotkey_impl(app: AppHandle, state: SharedState, hotkey: String) -> Result<String> {
+    let new_shortcut = parse_hotkey(&hotkey)?;
+    if let Some(window_toggle) = state.window_toggle_hotkey() {
+        if parse_hotkey(&window_toggle).is_ok_and(|toggle| toggle.id() == new_shortcut.id()) {
+            anyhow::bail!("That shortcut is already used to show/hide the window");
+        }
+    }
+    let old_hotkey = state.hotkey();
+    let old_shortcut = parse_hotkey(&old_hotkey)
+        .or_else(|_| parse_hotkey(settings::DEFAULT_HOTKEY))
+        .map_err(|e| anyhow::anyhow!("Invalid current hotkey: {e}"))?;
+
+    if new_shortcut.id() == old_shortcut.id() {
+        let canonical = new_shortcut.to_string();
+        state
+            .set_hotkey(canonical.clone())
+            .map_err(|e| anyhow::anyhow!(e))?;
+        return Ok(canonical);
+    }
+
+    // Unregistering may fail if the old shortcut was not registered. Continue so
+    // users can recover by selecting a new valid shortcut.
+    let _ = app.global_shortcut().unregister(old_shortcut);
+
+    if let Err(err) = app.global_shortcut().register(new_shortcut) {
+        let _ = app.global_shortcut().register(old_shortcut);
+        anyhow::bail!("Failed to register hotkey (possibly used by another app): {err}");
+    }
+
+    let canonical = new_shortcut.to_string();
+    if let Err(err) = state.set_hotkey(canonical.clone()) {
+        let _ = app.global_shortcut().unregister(new_shortcut);
+        let _ = app.global_shortcut().register(old_shortcut);
+        anyhow::bail!(err);
+    }
+
+    let _ = app.emit(
+        "hotkey-updated",
+        HotkeyUpdatedPayload {
+            hotkey: canonical.clone(),
+        },
+    );
+
+    Ok(canonical)
+}
+
+fn resync_hotkey_impl(app: AppHandle, state: SharedState) -> Result<String> {
+    let stored = state.settings().hotkey;
+    let stored_shortcut =
+        parse_hotkey(&stored).map_err(|e| anyhow::anyhow!("Invalid stored hotkey: {e}"))?;
+    let cached_shortcut = parse_hotkey(&state.hotkey()).ok();
+
+    // Either registration may be stale or missing; clear both so the register
+    // below starts from a clean slate.
+    let _ = app.global_shortcut().unregister(stored_shortcut);
+    if let Some(cached) = cached_shortcut {
+        let _ = app.global_shortcut().unregister(cached);
+    }
+
+    if let Err(err) = app.global_shortcut().register(stored_shortcut) {
+        if let Some(cached) = cached_shortcut {
+            let _ = app.global_shortcut().register(cached);
+        }
+        anyhow::bail!("Failed to register hotkey (possibly used by another app): {err}");
+    }
+
+    let canonical = stored_shortcut.to_string();
+    if let Err(err) = state.set_hotkey(canonical.clone()) {
+        let _ = app.global_shortcut().unregister(stored_shortcut);
+        if let Some(cached) = cached_shortcut {
+            let _ = app.global_shortcut().register(cached);
+        }
+        anyhow::bail!(err);
+    }
+
+    let _ = app.emit(
+        "hotkey-updated",
+        HotkeyUpdatedPayload {
+            hotkey: canonical.clone(),
+        },
+    );
+
+    Ok(canonical)
+}
+
+fn import_settings_impl(app: AppHandle, state: SharedState, json: &str) -> Result<()> {
+    let mut imported = settings::parse_import(json).map_err(|e| anyhow::anyhow!(e))?;
+
+    // Window placement depends on this machine's displays.
+    imported.window_geometry = state.settings().window_geometry;
+
+    // Models are machine-local, so keep the current one when the imported
+    // model is not installed here.
+    let current_model = state.active_model_name();
+    let imported_model_ok = imported
+        .active_model
+        .as_ref()
+        .is_some_and(|name| models::read_model_header(&state.models_dir().join(name)).is_ok());
+    if !imported_model_ok {
+        if let Some(name) = &imported.active_model {
+            emit_notice(
+                &app,
+                format!("Model '{name}' is not installed here; keeping '{current_model}'."),
+            );
+        }
+        imported.active_model = Some(current_model);
+    }
+
+    let old_shortcuts: Vec<Shortcut> = std::iter::once(state.hotkey())
+        .chain(state.window_toggle_hotkey())
+        .filter_map(|raw| parse_hotkey(&raw).ok())
+        .collect();
+    let new_shortcuts: Vec<Shortcut> = std::iter::once(imported.hotkey.clone())
+        .chain(imported.window_toggle_hotkey.clone())
+        .map(|raw| parse_hotkey(&raw))
+        .collect::<Result<_>>()?;
+
+    let restore_old = |app: &AppHandle| {
+        for shortcut in &new_shortcuts {
+            let _ = app.global_shortcut().unregister(*shortcut);
+        }
+        for shortcut in &old_shortcuts {
+            let _ = app.global_shortcut().register(*shortcut);
+        }
+    };
+
+    for shortcut in &old_shortcuts {
+        let _ = app.global_shortcut().unregister(*shortcut);
+    }
+    for shortcut in &new_shortcuts {
+        if let Err(err) = app.global_shortcut().register(*shortcut) {
+            restore_old(&app);
+            anyhow::bail!("Failed to register imported hotkey {shortcut}: {err}");
+        }
+    }
+
+    if let Err(err) = state.apply_settings(&imported) {
+        restore_old(&app);
+        anyhow::bail!(err);
+    }
+    // The imported model or GPU choice may differ from what is cached.
+    whisper::clear_context_cache();
+
+    let _ = app.emit(
+        "hotkey-updated",
+        HotkeyUpdatedPayload {
+            hotkey: imported.hotkey.clone(),
+        },
+    );
+    let _ = app.emit(
+        "window-toggle-hotkey-updated",
+        WindowToggleHotkeyUpdatedPayload {
+            hotkey: imported.window_toggle_hotkey.clone(),
+        },
+    );
+    let _ = app.emit(
+        "auto-copy-updated",
+        AutoCopyUpdatedPayload {
+            auto_copy: imported.auto_copy,
+        },
+    );
+    Ok(())
+}
+
+fn set_window_toggle_hotkey_impl(
+    app: AppHandle,
+    state: SharedState,
+    hotkey: Option<String>,
+) -> Result<Option<String>> {
+    let new_shortcut = match hotkey.as_deref().map(str::trim) {
+        Some(raw) if !raw.is_empty() => Some(parse_hotkey(raw)?),
+        _ => None,
+    };
+    if let Some(shortcut) = new_shortcut {
+        if parse_hotkey(&state.hotkey()).is_ok_and(|recording| recording.id() == shortcut.id()) {
+            anyhow::bail!("That shortcut is already used to start and stop recording");
+        }
+    }
+
+    let old_shortcut = state
+        .window_toggle_hotkey()
+        .and_then(|raw| parse_hotkey(&raw).ok());
+    if old_shortcut.map(|s| s.id()) == new_shortcut.map(|s| s.id()) {
+        return Ok(new_shortcut.map(|s| s.to_string()));
+    }
+
+    if let Some(old) = old_shortcut {
+        let _ = app.global_shortcut().unregister(old);
+    }
+
+    if let Some(new) = new_shortcut {
+        if let Err(err) = app.global_shortcut().register(new) {
+            if let Some(old) = old_shortcut {
+                let _ = app.global_shortcut().register(old);
+            }
+            anyhow::bail!("Failed to register hotkey (possibly used by another app): {err}");
+        }
+    }
+
+    let canonical = new_shortcut.map(|s| s.to_string());
+    if let Err(err) = state.set_window_toggle_hotkey(canonical.clone()) {
+        if let Some(new) = new_shortcut {
+            let _ = app.global_shortcut().unregister(new);
+        }
+        if let Some(old) = old_shortcut {
+            let _ = app.global_shortcut().register(old);
+        }
+        anyhow::bail!(err);
+    }
+
+    let _ = app.emit(
+        "window-toggle-hotkey-updated",
+        WindowToggleHotkeyUpdatedPayload {
+            hotkey: canonical.clone(),
+        },
+    );
+
+    Ok(canonical)
+}
+
+fn parse_hotkey(raw: &str) -> Result<Shortcut> {
+    let shortcut =
+        Shortcut::from_str(raw.trim()).map_err(|e| anyhow::anyhow!("Invalid hotkey: {e}"))?;
     let required_mods = Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::SUPER;
     if !shortcut.mods.intersects(required_mods) {
         anyhow::bail!("Hotkey must include at least one modifier key");
     }
-    Ok(shortcut)
+    Ok(shortcut)
+}
+
+/// Hotkey handling in push-to-talk mode: pressing starts a recording and
+/// releasing stops it. The caller updates `set_push_to_talk_held` as events
+/// arrive. Taps shorter than the minimum length end up in the usual
+/// "recording too short" handling.
+pub async fn push_to_talk_impl(app: AppHandle, state: SharedState, pressed: bool) -> Result<()> {
+    if !pressed {
+        if let Some(recording_id) = state.take_push_to_talk() {
+            if state.active_recording_id() == Some(recording_id) {
+                stop_recording_impl(app, state, Some(recording_id)).await?;
+            }
+        }
+        return Ok(());
+    }
+
+    if state.request_stop_continuous() {
+        emit_notice(&app, "Stopping continuous dictation...");
+        return Ok(());
+    }
+    match state.status() {
+        AppStatus::Idle => {
+            let recording_id = start_recording_impl(
+                app.clone(),
+                state.clone(),
+                TranscriptionSource::Hotkey,
+                state.transcription_mode(),
+            )?;
+            state.claim_push_to_talk(recording_id);
+            // Released while the microphone was still opening.
+            if !state.push_to_talk_held() && state.take_push_to_talk().is_some() {
+                stop_recording_impl(app, state, Some(recording_id)).await?;
+            }
+            Ok(())
+        }
+        AppStatus::Recording => Ok(()),
+        AppStatus::Processing | AppStatus::Cancelling => {
+            emit_notice(&app, "Transcription is still running. Please wait.");
+            Ok(())
+        }
+    }
+}
+
+/// Starts or stops a recording. This path is never blocked by UI commands:
+/// destructive history commands check `guard_destructive` instead and back
+/// off while a recording is in flight. During continuous dictation it ends
+/// the session instead.
+pub async fn toggle_recording_impl(
+    app: AppHandle,
+    state: SharedState,
+    source: TranscriptionSource,
+    mode: TranscriptionMode,
+) -> Result<()> {
+    if state.request_stop_continuous() {
+        emit_notice(&app, "Stopping continuous dictation...");
+        return Ok(());
+    }
+    match state.status() {
+        AppStatus::Idle => {
+            start_recording_impl(app, state, source, mode)?;
+            Ok(())
+        }
+        AppStatus::Recording => stop_recording_impl(app, state, None).await.map(|_| ()),
+        AppStatus::Processing | AppStatus::Cancelling => {
+            emit_notice(&app, "Transcription is still running. Please wait.");
+            Ok(())
+        }
+    }
+}
+
+pub fn emit_error(app: &AppHandle, message: impl Into<String>) {
+    let payload = ErrorPayload {
+        message: message.into(),
+    };
+    logging::error(&payload.message);
+    let _ = app.emit("transcription-error", payload);
+}
+
+/// Pastes `text` into the focused app through the clipboard. Unless it was
+/// already copied on purpose, the clipboard is put back afterwards.
+fn paste_output(app: &AppHandle, text: &str, copied: bool) -> Result<()> {
+    let previous = if copied {
+        None
+    } else {
+        let previous = app.clipboard().read_text().ok();
+        app.clipboard()
+            .write_text(text.to_string())
+            .map_err(|err| anyhow::anyhow!("{err}"))?;
+        previous
+    };
+    let pasted = paste::paste_clipboard();
+    if let Some(previous) = previous {
+        // The target app reads the clipboard when it handles the keystroke,
+        // which can be after `paste_clipboard` returns.
+        let app = app.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(CLIPBOARD_RESTORE_DELAY);
+            let _ = app.clipboard().write_text(previous);
+        });
+    }
+    pasted
+}
+
+/// Tells the user once when a model could not use the GPU and ran on the CPU.
+fn notify_gpu_fallback(app: &AppHandle) {
+    if whisper::take_gpu_fallback() {
+        emit_notice(
+            app,
+            "The model could not load on the GPU, so it is running on the CPU. Transcription will be slower.",
+        );
+    }
+}
+
+pub fn emit_notice(app: &AppHandle, message: impl Into<String>) {
+    let payload = NoticePayload {
+        message: message.into(),
+    };
+    let _ = app.emit("app-notice", payload);
+}
+
+fn transcribe_options(state: &SharedState) -> whisper::TranscribeOptions {
+    let settings = state.settings();
+    // Codes are validated when set; a bad hand-edited one falls back to the default.
+    let language =
+        whisper::Language::from_setting(settings.language.as_deref()).unwrap_or_else(|err| {
+            logging::warn(format!("ignoring language setting: {err}"));
+            whisper::Language::default()
+        });
+    whisper::TranscribeOptions {
+        mode: state.transcription_mode(),
+        language,
+        low_priority: state.low_priority(),
+        timestamps: settings.timestamps_in_text,
+        decode: settings.decode_params(),
+        initial_prompt: settings.initial_prompt,
+        use_gpu: settings.use_gpu,
+        deterministic: settings.deterministic,
+    }
+}
+
+/// Drops the oldest rows beyond `max_history_rows`, if set. Returns how many
+/// were removed.
+fn enforce_history_cap_impl(state: &SharedState) -> Result<usize> {
+    let removed = match state.settings().max_history_rows {
+        Some(max_rows) => db::enforce_row_cap(&state.db_path(), max_rows)?,
+        None => 0,
+    };
+    if removed > 0 {
+        prune_orphaned_audio(state);
+    }
+    Ok(removed)
+}
+
+/// Deletes kept audio whose history row no longer exists. Like trimming, a
+/// failure only delays cleanup until the next time rows are removed.
+fn prune_orphaned_audio(state: &SharedState) {
+    let pruned = db::ids(&state.db_path())
+        .and_then(|ids| audio::prune_kept_audio(&state.audio_dir(), |id| ids.contains(&id)));
+    if let Err(err) = pruned {
+        logging::warn(format!("kept audio could not be pruned: {err}"));
+    }
+}
+
+/// Applies both retention policies: rows older than `history_retention_days`
+/// go first, then the oldest beyond `max_history_rows`. Returns how many rows
+/// were removed.
+fn prune_history_impl(state: &SharedState) -> Result<usize> {
+    let removed = match state.settings().history_retention_days {
+        Some(days) => db::prune(&state.db_path(), days)?,
+        None => 0,
+    };
+    if removed > 0 {
+        prune_orphaned_audio(state);
+    }
+    Ok(removed + enforce_history_cap_impl(state)?)
+}
+
+/// Runs at startup and after every insert; a failed prune only delays
+/// cleanup until the next one.
+pub fn apply_history_retention(state: &SharedState) {
+    match prune_history_impl(state) {
+        Ok(0) => {}
+        Ok(removed) => logging::info(format!(
+            "history retention removed {removed} transcriptions"
+        )),
+        Err(err) => logging::warn(format!("history retention could not be applied: {err}")),
+    }
+}
+
+/// Transcribes each microphone of a dual-mic capture on its own, with segment
+/// timestamps so the turns can be interleaved. Also returns both merged into
+/// one transcript in time order.
+fn transcribe_speakers(
+    model_path: &Path,
+    speakers: [&[f32]; 2],
+    sample_rate: u32,
+    options: whisper::TranscribeOptions,
+    cancel: &Arc<AtomicBool>,
+) -> Result<(whisper::Transcript, [whisper::Transcript; 2])> {
+    let options = whisper::TranscribeOptions {
+        timestamps: true,
+        ..options
+    };
+    let [first, second] = speakers;
+    let first = whisper::transcribe(
+        model_path,
+        first,
+        sample_rate,
+        options.clone(),
+        Some(cancel.clone()),
+    )?;
+    let second = whisper::transcribe(
+        model_path,
+        second,
+        sample_rate,
+        options,
+        Some(cancel.clone()),
+    )?;
+
+    let mut merged = first.clone();
+    merged.language = first.language.clone().or_else(|| second.language.clone());
+    merged.segments.extend(second.segments.iter().cloned());
+    merged.segments.sort_by_key(|segment| segment.start_ms);
+    merged.timing.prepare_ms += second.timing.prepare_ms;
+    merged.timing.model_load_ms += second.timing.model_load_ms;
+    merged.timing.decode_ms += second.timing.decode_ms;
+    Ok((merged, [first, second]))
+}
+
+/// Normalizes one piece of whisper text the way the current settings ask for.
+fn text_cleaner(state: &SharedState, verbatim: bool) -> impl Fn(&str) -> String {
+    let locale = state.settings().number_locale();
+    let number_format = state.number_format();
+    move |text: &str| {
+        if verbatim {
+            text.trim().to_string()
+        } else {
+            postprocess::format_numbers(text.trim(), number_format, locale)
+        }
+    }
+}
+
+/// Like `finalize_text`, but writes one labeled line per speaker turn.
+fn finalize_speaker_text(
+    state: &SharedState,
+    speakers: [&whisper::Transcript; 2],
+    labels: &[String; 2],
+    verbatim: bool,
+) -> String {
+    if speakers
+        .iter()
+        .all(|transcript| transcript.text().trim().is_empty())
+    {
+        return db::NO_SPEECH_PLACEHOLDER.to_string();
+    }
+
+    let clean = text_cleaner(state, verbatim);
+    let [first, second] = speakers.map(|transcript| {
+        transcript
+            .segments
+            .iter()
+            .map(|segment| (segment.start_ms, clean(&segment.text)))
+            .collect::<Vec<_>>()
+    });
+    postprocess::speaker_turns_text(
+        first
+            .iter()
+            .map(|(start_ms, text)| (*start_ms, text.as_str())),
+        second
+            .iter()
+            .map(|(start_ms, text)| (*start_ms, text.as_str())),
+        [&labels[0], &labels[1]],
+    )
+}
+
+/// The transcript's timed segments as saved next to the text, cleaned the
+/// same way. `None` when there was no speech or whisper was not asked for
+/// timestamps.
+fn segments_json(
+    state: &SharedState,
+    transcript: &whisper::Transcript,
+    verbatim: bool,
+) -> Option<String> {
+    if !transcript.timed || transcript.text().trim().is_empty() {
+        return None;
+    }
+    let clean = text_cleaner(state, verbatim);
+    let segments: Vec<whisper::Segment> = transcript
+        .segments
+        .iter()
+        .map(|segment| whisper::Segment {
+            text: clean(&segment.text),
+            ..segment.clone()
+        })
+        .collect();
+    serde_json::to_string(&segments).ok()
+}
+
+/// Turns raw whisper output into the text that is saved and shown.
+fn finalize_text(state: &SharedState, transcript: &whisper::Transcript, verbatim: bool) -> String {
+    if transcript.text().trim().is_empty() {
+        return db::NO_SPEECH_PLACEHOLDER.to_string();
+    }
+
+    let settings = state.settings();
+    let clean = text_cleaner(state, verbatim);
+
+    let timestamps = settings.timestamp_output();
+    if timestamps.enabled {
+        let segments: Vec<(i64, String)> = transcript
+            .segments
+            .iter()
+            .map(|segment| (segment.start_ms, clean(&segment.text)))
+            .collect();
+        postprocess::timestamped_text(
+            segments
+                .iter()
+                .map(|(start_ms, text)| (*start_ms, text.as_str())),
+            timestamps.format,
+        )
+    } else {
+        clean(&transcript.text())
+    }
+}
+
+/// Transcribes dropped files one after another, skipping anything that is not
+/// a supported audio file.
+pub fn transcribe_dropped_files(app: AppHandle, paths: Vec<PathBuf>) {
+    let (audio, skipped): (Vec<PathBuf>, Vec<PathBuf>) = paths
+        .into_iter()
+        .partition(|path| is_supported_audio_file(path));
+    if !skipped.is_empty() {
+        logging::info(format!("ignored {} non-audio drop(s)", skipped.len()));
+    }
+    if audio.is_empty() {
+        if !skipped.is_empty() {
+            emit_notice(
+                &app,
+                format!(
+                    "Only {} files can be transcribed.",
+                    audio::SUPPORTED_AUDIO_EXTENSIONS.join(", ")
+                ),
+            );
+        }
+        return;
+    }
+
+    let state = app.state::<SharedState>().inner().clone();
+    tauri::async_runtime::spawn(async move {
+        for path in audio {
+            // Failures are already surfaced through file-transcription-error.
+            let _ = transcribe_file_impl(app.clone(), state.clone(), path).await;
+        }
+    });
+}
+
+fn is_supported_audio_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                audio::SUPPORTED_AUDIO_EXTENSIONS
+                    .iter()
+                    .any(|supported| ext.eq_ignore_ascii_case(supported))
+            })
+}
+
+/// The first supported audio file named on the clipboard. File managers copy
+/// either plain paths or `file://` URIs, one per line.
+fn clipboard_audio_path(copied: &str) -> Option<PathBuf> {
+    copied
+        .lines()
+        .map(|line| line.trim().trim_matches('"'))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.strip_prefix("file://") {
+            Some(uri) => file_uri_path(uri),
+            None => PathBuf::from(line),
+        })
+        .find(|path| is_supported_audio_file(path))
+}
+
+/// The path of a `file://` URI, given what follows the scheme. Windows URIs
+/// put a slash before the drive letter (`file:///C:/x.wav`), which is not
+/// part of the path.
+fn file_uri_path(uri: &str) -> PathBuf {
+    let path = percent_decode(uri.trim_start_matches("localhost"));
+    let bytes = path.as_bytes();
+    let drive =
+        bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':';
+    if drive {
+        PathBuf::from(&path[1..])
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+fn percent_decode(raw: &str) -> String {
+    let bytes = raw.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let hex = bytes
+            .get(index + 1..index + 3)
+            .and_then(|pair| std::str::from_utf8(pair).ok())
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok());
+        match (bytes[index], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+async fn transcribe_file_impl(
+    app: AppHandle,
+    state: SharedState,
+    path: PathBuf,
+) -> Result<FileTranscription> {
+    let display_path = path.display().to_string();
+    let _ = app.emit(
+        "file-transcription-started",
+        FileTranscriptionStartedPayload {
+            path: display_path.clone(),
+        },
+    );
+
+    let mut job_cancel = None;
+    let result: Result<FileTranscription> = async {
+        let model_name = state.active_model_name();
+        let model_path = state.active_model_path();
+        if !model_path.exists() {
+            anyhow::bail!(
+                "No installed model available. Download a model or add a .bin file in the models directory."
+            );
+        }
+
+        let mode = state.transcription_mode();
+        let options = transcribe_options(&state);
+        let cancel = state
+            .begin_file_job()
+            .ok_or_else(|| anyhow::anyhow!("{FILE_JOB_BUSY}"))?;
+        job_cancel = Some(cancel.clone());
+        let cancel_for_worker = cancel.clone();
+        let app_for_progress = app.clone();
+        let path_for_progress = display_path.clone();
+        let decoded = tauri::async_runtime::spawn_blocking(move || {
+            let (samples, sample_rate) = audio::read_audio_file(&path)?;
+            let duration_ms = samples.len() as i64 * 1000 / i64::from(sample_rate);
+            let started = Instant::now();
+            let transcript = whisper::transcribe_chunked(
+                &model_path,
+                &samples,
+                sample_rate,
+                options,
+                Some(cancel_for_worker),
+                |chunk_index, total_chunks, so_far| {
+                    let done = (chunk_index + 1) * 100 / total_chunks.max(1);
+                    let _ = app_for_progress.emit(
+                        "file-transcription-progress",
+                        FileTranscriptionProgressPayload {
+                            path: path_for_progress.clone(),
+                            chunk_index,
+                            total_chunks,
+                            percent: done.min(100) as u8,
+                            elapsed_ms: started.elapsed().as_millis() as u64,
+                            partial_text: so_far.text(),
+                        },
+                    );
+                },
+            )?;
+            Ok::<_, anyhow::Error>((transcript, duration_ms))
+        })
+        .await;
+        state.end_file_job(&cancel);
+        notify_gpu_fallback(&app);
+        let (transcript, duration_ms) = decoded??;
+
+        let verbatim = mode == TranscriptionMode::Verbatim;
+        let text = finalize_text(&state, &transcript, verbatim);
+        let id = db::insert(
+            &state.db_path(),
+            &db::NewTranscription {
+                text: &text,
+                duration_ms,
+                model: &model_name,
+                verbatim,
+                source: TranscriptionSource::File,
+                capture_config: None,
+                language: transcript.language.as_deref(),
+                segments: segments_json(&state, &transcript, verbatim).as_deref(),
+                avg_confidence: transcript.avg_confidence(),
+            },
+        )?;
+        apply_history_retention(&state);
+        Ok(FileTranscription {
+            id,
+            path: display_path.clone(),
+            text,
+            duration_ms,
+            model: model_name,
+        })
+    }
+    .await;
+
+    match &result {
+        Ok(done) => {
+            let _ = app.emit("file-transcription-complete", done.clone());
+        }
+        // Cancelled on request: reported the same way as a recording.
+        Err(_) if job_cancel.is_some_and(|cancel| cancel.load(Ordering::Relaxed)) => {
+            emit_notice(&app, "Transcription cancelled.");
+            let _ = app.emit("transcription-cancelled", ());
+        }
+        Err(err) => {
+            logging::error(format!(
+                "file transcription failed for {display_path}: {err}"
+            ));
+            let _ = app.emit(
+                "file-transcription-error",
+                FileTranscriptionErrorPayload {
+                    path: display_path,
+                    message: err.to_string(),
+                },
+            );
+        }
+    }
+    result
+}
+
+/// Replaces the output target file with `text`. The new contents go to a
+/// sibling temp file first and are renamed into place, so watchers never see a
+/// partial write.
+fn write_output_target(app: &AppHandle, path: &Path, text: &str) {
+    let written = write_atomically(path, text.as_bytes());
+    if let Err(err) = written {
+        logging::warn(format!(
+            "writing output file {} failed: {err}",
+            path.display()
+        ));
+        emit_notice(
+            app,
+            format!("Transcription saved, but writing the output file failed: {err}"),
+        );
+    }
+}
+
+fn write_atomically(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| std::io::Error::other("output path has no file name"))?;
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    std::fs::create_dir_all(parent)?;
+
+    let temp = parent.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    let result = std::fs::File::create(&temp)
+        .and_then(|mut file| file.write_all(data).and_then(|()| file.sync_all()))
+        .and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Appends a finished transcription to the live output file, if one is set.
+/// A file that vanished after this session wrote to it is recreated with a
+/// notice; write failures never fail the transcription itself.
+fn append_live_output(
+    app: &AppHandle,
+    state: &SharedState,
+    id: i64,
+    text: &str,
+    model: &str,
+    duration_ms: i64,
+) {
+    let output = state.settings().live_output();
+    let Some(file) = output.file else {
+        return;
+    };
+    let path = PathBuf::from(&file);
+    let existed = path.exists();
+    let mut entry = postprocess::render_live_output(&output.template, id, text, model, duration_ms);
+    entry.push_str(&output.separator);
+
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| OpenOptions::new().create(true).append(true).open(&path))
+        .and_then(|mut handle| handle.write_all(entry.as_bytes()));
+    match written {
+        Ok(()) => {
+            if state.mark_live_output_written(&path) && !existed {
+                logging::warn(format!("live output file {file} was missing; recreated it"));
+                emit_notice(
+                    app,
+                    format!("The live output file {file} was missing and has been recreated."),
+                );
+            }
+        }
+        Err(err) => {
+            logging::warn(format!("live output append to {file} failed: {err}"));
+            emit_notice(
+                app,
+                format!("Transcription saved, but writing to the live output file failed: {err}"),
+            );
+        }
+    }
+}
+
+fn force_reset_impl(app: &AppHandle, state: &SharedState) -> AppStatus {
+    state.request_stop_continuous();
+    let (previous, session) = state.force_reset();
+    crate::set_tray_listening(app, false);
+    let abandoned = session.is_some_and(|session| !audio::abandon_capture(session));
+
+    logging::warn(format!(
+        "force reset from {previous:?}{}",
+        if abandoned {
+            "; audio thread abandoned"
+        } else {
+            ""
+        }
+    ));
+    let _ = app.emit("app-reset", previous);
+    emit_notice(
+        app,
+        if abandoned {
+            "Recording was reset. The microphone did not respond and was released."
+        } else {
+            "Recording was reset."
+        },
+    );
+    previous
+}
+
+fn recover_state_impl(app: &AppHandle, state: &SharedState) -> AppStatus {
+    state.request_stop_continuous();
+    let recovery = state.recover();
+    let abandoned = recovery
+        .session
+        .is_some_and(|session| !audio::abandon_capture(session));
+    crate::set_tray_listening(app, false);
+
+    if recovery.poisoned.is_empty() {
+        logging::warn(format!(
+            "state recovered from {:?}; no locks were poisoned",
+            recovery.previous
+        ));
+    } else {
+        logging::error(format!(
+            "state recovered from {:?}; cleared poisoned locks: {}",
+            recovery.previous,
+            recovery.poisoned.join(", ")
+        ));
+    }
+    let _ = app.emit("app-reset", recovery.previous);
+    emit_notice(
+        app,
+        if abandoned {
+            "Murmur recovered from an internal error. The microphone did not respond and was released."
+        } else {
+            "Murmur recovered from an internal error and is ready again."
+        },
+    );
+    AppStatus::Idle
+}
+
+/// Starts a capture and returns its recording id.
+fn start_recording_impl(
+    app: AppHandle,
+    state: SharedState,
+    source: TranscriptionSource,
+    mode: TranscriptionMode,
+) -> Result<u64> {
+    let capture_settings = state.settings();
+    let session = audio::start_capture_with_device(
+        state.input_device().as_deref(),
+        capture_settings.max_recording_seconds,
+        state.capture_warmup_ms(),
+        capture_settings.capture_buffer_size,
+        &capture_settings.dual_mic(),
+    )?;
+    let buffer_fallback = session.buffer_fallback.clone();
+    let device_fallback = session.device_fallback.clone();
+    let recording_id = state
+        .set_recording(session, source, mode)
+        .map_err(|e| anyhow::anyhow!(e))?;
+    for message in [device_fallback, buffer_fallback].into_iter().flatten() {
+        emit_notice(&app, message);
+    }
+    crate::set_tray_listening(&app, true);
+    let _ = app.emit("recording-started", ());
+    spawn_level_meter(app.clone(), state.clone(), recording_id);
+    // Continuous utterances are cut well before the cap.
+    if source != TranscriptionSource::Continuous {
+        spawn_time_warnings(
+            app.clone(),
+            state.clone(),
+            recording_id,
+            capture_settings.max_recording_seconds,
+        );
+    }
+    // Continuous dictation already splits on pauses itself.
+    if capture_settings.auto_stop.enabled && source != TranscriptionSource::Continuous {
+        spawn_auto_stop(app, state, recording_id, capture_settings.auto_stop);
+    }
+    Ok(recording_id)
 }
 
-pub async fn toggle_recording_impl(app: AppHandle, state: SharedState) -> Result<()> {
-    match state.status() {
-        AppStatus::Idle => {
-            start_recording_impl(app, state)?;
-            Ok(())
-        }
-        AppStatus::Recording => stop_recording_impl(app, state).await,
-        AppStatus::Processing | AppStatus::Cancelling => {
-            emit_notice(&app, "Transcription is still running. Please wait.");
-            Ok(())
+/// Emits `recording-time-warning` once a second during the last
+/// `RECORDING_WARNING_WINDOW` before recording `recording_id` hits its length
+/// cap. The timer is attached to the recording, so stopping it early wakes
+/// and joins the thread; otherwise it wakes at least every `QUICK_NOTE_TICK`
+/// and exits once the recording is no longer running.
+fn spawn_time_warnings(app: AppHandle, state: SharedState, recording_id: u64, max_seconds: u32) {
+    let max = Duration::from_secs(u64::from(max_seconds));
+    // The cap counts captured audio, which starts after the warm-up and
+    // excludes paused time.
+    let deadline =
+        Instant::now() + Duration::from_millis(u64::from(state.capture_warmup_ms())) + max;
+    let timer_state = state.clone();
+    let timer = std::thread::spawn(move || {
+        let mut next_warning = RECORDING_WARNING_WINDOW;
+        loop {
+            if state.active_recording_id() != Some(recording_id) {
+                return;
+            }
+            let (paused, paused_time) = state.recording_pause().unwrap_or_default();
+            if paused {
+                std::thread::park_timeout(QUICK_NOTE_TICK);
+                continue;
+            }
+            let remaining = (deadline + paused_time).saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return;
+            }
+            if remaining <= next_warning {
+                let _ = app.emit(
+                    "recording-time-warning",
+                    RecordingTimeWarningPayload {
+                        remaining_ms: remaining.as_millis() as u64,
+                        max_ms: max.as_millis() as u64,
+                    },
+                );
+                next_warning = remaining.saturating_sub(Duration::from_secs(1));
+            }
+            let until_warning = remaining.saturating_sub(next_warning);
+            std::thread::park_timeout(
+                until_warning.clamp(Duration::from_millis(10), QUICK_NOTE_TICK),
+            );
         }
-    }
+    });
+    timer_state.attach_recording_timer(recording_id, timer);
 }
 
-pub fn emit_error(app: &AppHandle, message: impl Into<String>) {
-    let payload = ErrorPayload {
-        message: message.into(),
-    };
-    let _ = app.emit("transcription-error", payload);
+/// Stops recording `recording_id` once `auto_stop.silence_timeout_ms` of
+/// silence follows at least `auto_stop.min_speech_ms` of speech. Exits on its
+/// own when the recording is stopped another way.
+fn spawn_auto_stop(
+    app: AppHandle,
+    state: SharedState,
+    recording_id: u64,
+    auto_stop: settings::AutoStop,
+) {
+    let silence_timeout = Duration::from_millis(u64::from(auto_stop.silence_timeout_ms));
+    let min_speech = Duration::from_millis(u64::from(auto_stop.min_speech_ms));
+    std::thread::spawn(move || {
+        let mut checked = 0;
+        let mut speech = Duration::ZERO;
+        let mut last_speech = Instant::now();
+        loop {
+            std::thread::sleep(CONTINUOUS_POLL);
+            if state.active_recording_id() != Some(recording_id) {
+                return;
+            }
+            // A pause is not silence; start the timeout over once it ends.
+            if state.recording_pause().is_some_and(|(paused, _)| paused) {
+                last_speech = Instant::now();
+                continue;
+            }
+            let Some(samples) = state.live_samples() else {
+                return;
+            };
+            let level = samples.lock().ok().and_then(|buffer| {
+                let fresh = &buffer[checked.min(buffer.len())..];
+                checked = buffer.len();
+                (!fresh.is_empty()).then(|| audio::rms(fresh))
+            });
+            if level.is_some_and(|rms| rms >= CONTINUOUS_SPEECH_RMS) {
+                speech += CONTINUOUS_POLL;
+                last_speech = Instant::now();
+            }
+            if speech >= min_speech && last_speech.elapsed() >= silence_timeout {
+                break;
+            }
+        }
+
+        let _ = app.emit(
+            "recording-auto-stopped",
+            AutoStoppedPayload {
+                silence_ms: last_speech.elapsed().as_millis() as u64,
+            },
+        );
+        tauri::async_runtime::spawn(async move {
+            // Failures are already surfaced through transcription-error.
+            let _ = stop_recording_impl(app, state, Some(recording_id)).await;
+        });
+    });
 }
 
-pub fn emit_notice(app: &AppHandle, message: impl Into<String>) {
-    let payload = NoticePayload {
-        message: message.into(),
-    };
-    let _ = app.emit("app-notice", payload);
+/// Emits `capture-level` with the signal stats of the audio captured since the
+/// previous tick, for a live level meter. Exits on its own once recording
+/// `recording_id` is no longer the one running, so stopping never waits on it.
+fn spawn_level_meter(app: AppHandle, state: SharedState, recording_id: u64) {
+    let threshold = state.settings().activity_thresholds.meter;
+    std::thread::spawn(move || {
+        let mut reported = 0;
+        loop {
+            std::thread::sleep(CAPTURE_LEVEL_TICK);
+            if state.active_recording_id() != Some(recording_id) {
+                return;
+            }
+            let Some(samples) = state.live_samples() else {
+                return;
+            };
+            let level = samples.lock().ok().and_then(|buffer| {
+                let fresh = &buffer[reported.min(buffer.len())..];
+                reported = buffer.len();
+                (!fresh.is_empty()).then(|| audio::analyze_signal(fresh, threshold))
+            });
+            if let Some(level) = level {
+                let _ = app.emit("capture-level", level);
+            }
+        }
+    });
 }
 
-fn start_recording_impl(app: AppHandle, state: SharedState) -> Result<()> {
-    let session = audio::start_capture(30)?;
-    state
-        .set_recording(session)
+fn start_quick_note_impl(app: AppHandle, state: SharedState) -> Result<()> {
+    let seconds = state.quick_note_seconds();
+    // One second of buffer headroom so the timer, not the capacity cap, ends it.
+    let capture_settings = state.settings();
+    let session = audio::start_capture_with_device(
+        state.input_device().as_deref(),
+        seconds + 1,
+        state.capture_warmup_ms(),
+        capture_settings.capture_buffer_size,
+        &capture_settings.dual_mic(),
+    )?;
+    let buffer_fallback = session.buffer_fallback.clone();
+    let device_fallback = session.device_fallback.clone();
+    let recording_id = state
+        .set_recording(
+            session,
+            TranscriptionSource::QuickNote,
+            state.transcription_mode(),
+        )
         .map_err(|e| anyhow::anyhow!(e))?;
+    for message in [device_fallback, buffer_fallback].into_iter().flatten() {
+        emit_notice(&app, message);
+    }
     crate::set_tray_listening(&app, true);
     let _ = app.emit("recording-started", ());
+    spawn_level_meter(app.clone(), state.clone(), recording_id);
+    let _ = app.emit(
+        "quick-note-started",
+        QuickNoteStartedPayload {
+            duration_ms: u64::from(seconds) * 1000,
+        },
+    );
+
+    std::thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(u64::from(seconds));
+        loop {
+            // Stopped by hand (or superseded): the normal stop path owns it now.
+            if state.active_recording_id() != Some(recording_id) {
+                return;
+            }
+            // Paused time does not count against the note.
+            let (paused, paused_time) = state.recording_pause().unwrap_or_default();
+            if paused {
+                std::thread::sleep(QUICK_NOTE_TICK);
+                continue;
+            }
+            let remaining = (deadline + paused_time).saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            let _ = app.emit(
+                "quick-note-countdown",
+                QuickNoteCountdownPayload {
+                    remaining_ms: remaining.as_millis() as u64,
+                },
+            );
+            std::thread::sleep(remaining.min(QUICK_NOTE_TICK));
+        }
+
+        tauri::async_runtime::spawn(async move {
+            // Failures are already surfaced through transcription-error.
+            let _ = stop_recording_impl(app, state, Some(recording_id)).await;
+        });
+    });
     Ok(())
 }
 
-async fn stop_recording_impl(app: AppHandle, state: SharedState) -> Result<()> {
-    let (session, cancel_requested) = state.take_recording().map_err(|e| anyhow::anyhow!(e))?;
-    let _ = app.emit("recording-stopped", ());
-    crate::set_tray_listening(&app, false);
+fn start_continuous_impl(app: AppHandle, state: SharedState) -> Result<()> {
+    if state.status() != AppStatus::Idle {
+        anyhow::bail!("Finish the current recording before starting continuous dictation.");
+    }
+    if !state.begin_continuous() {
+        anyhow::bail!("Continuous dictation is already running.");
+    }
+    // Every utterance of the session is transcribed the same way.
+    let mode = state.transcription_mode();
+    let recording_id = match start_recording_impl(
+        app.clone(),
+        state.clone(),
+        TranscriptionSource::Continuous,
+        mode,
+    ) {
+        Ok(recording_id) => recording_id,
+        Err(err) => {
+            state.end_continuous();
+            return Err(err);
+        }
+    };
+    let _ = app.emit(
+        "continuous-started",
+        ContinuousStartedPayload {
+            max_session_ms: CONTINUOUS_MAX_SESSION.as_millis() as u64,
+        },
+    );
 
-    let result: Result<()> = async {
-        let captured = audio::stop_capture(session);
+    std::thread::spawn(move || {
+        let deadline = Instant::now() + CONTINUOUS_MAX_SESSION;
+        let mut recording_id = recording_id;
+        let (utterances, queue) = mpsc::channel();
+        let worker = spawn_utterance_worker(app.clone(), state.clone(), queue);
+        let reason = loop {
+            let (end, heard_speech) = wait_for_utterance_end(&state, recording_id, deadline);
+            if end == UtteranceEnd::Superseded {
+                break ContinuousStopReason::Interrupted;
+            }
+            if heard_speech {
+                // The utterance is transcribed in the background so the next
+                // one can be recorded meanwhile.
+                match end_recording(&app, &state, Some(recording_id)) {
+                    Ok(stopped) => {
+                        state.set_idle();
+                        let _ = utterances.send(stopped);
+                    }
+                    Err(err) => emit_error(&app, err.to_string()),
+                }
+            } else {
+                // Nothing was said; start over instead of transcribing silence.
+                discard_recording(&app, &state);
+            }
+            match end {
+                UtteranceEnd::StopRequested => break ContinuousStopReason::Requested,
+                UtteranceEnd::SessionLimit => break ContinuousStopReason::SessionLimit,
+                _ => {}
+            }
+            // A stop may have come in while the utterance was handed off.
+            if state
+                .continuous_status()
+                .is_none_or(|session| session.stopping)
+            {
+                break ContinuousStopReason::Requested;
+            }
+            match start_recording_impl(
+                app.clone(),
+                state.clone(),
+                TranscriptionSource::Continuous,
+                mode,
+            ) {
+                Ok(next) => recording_id = next,
+                Err(err) => {
+                    emit_error(&app, format!("Continuous dictation stopped: {err}"));
+                    break ContinuousStopReason::Failed;
+                }
+            }
+        };
+        // The session ends once everything said in it has been transcribed.
+        drop(utterances);
+        let _ = worker.join();
 
-        if captured.truncated {
+        if reason == ContinuousStopReason::SessionLimit {
             emit_notice(
                 &app,
-                "Recording exceeded 30 seconds. Only the first 30 seconds were transcribed.",
+                format!(
+                    "Continuous dictation stopped after {} minutes.",
+                    CONTINUOUS_MAX_SESSION.as_secs() / 60
+                ),
+            );
+        }
+        if let Some(session) = state.end_continuous() {
+            let _ = app.emit(
+                "continuous-stopped",
+                ContinuousStoppedPayload { reason, session },
             );
         }
+    });
+    Ok(())
+}
 
-        if captured.duration_ms < 200 {
-            emit_error(&app, "Recording too short");
-            return Ok(());
+/// Transcribes the utterances of a continuous session one at a time, in the
+/// order they were spoken, until the session stops sending them.
+fn spawn_utterance_worker(
+    app: AppHandle,
+    state: SharedState,
+    queue: mpsc::Receiver<StoppedRecording>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        for stopped in queue {
+            let result = tauri::async_runtime::block_on(transcribe_capture(
+                &app,
+                &state,
+                &stopped.capture,
+                stopped.cancel_requested,
+                stopped.stop_started,
+                stopped.capture_stop_ms,
+            ));
+            match result {
+                Ok(()) => state.clear_failed_capture(),
+                Err(err) => {
+                    // The session carries on; the status belongs to the
+                    // recording that is already under way.
+                    offer_retry(&app, &state, stopped.capture);
+                    emit_error(&app, err.to_string());
+                }
+            }
         }
+    })
+}
 
-        let signal = audio::analyze_signal(&captured.samples);
-        eprintln!(
-            "capture stats: samples={}, sample_rate={}, rms={:.6}, peak={:.6}, active_ratio={:.4}",
-            captured.samples.len(),
-            captured.sample_rate,
-            signal.rms,
-            signal.peak,
-            signal.active_ratio
+/// Watches the live level of recording `recording_id` until an utterance is
+/// over. Also returns whether any speech was heard in it.
+fn wait_for_utterance_end(
+    state: &SharedState,
+    recording_id: u64,
+    deadline: Instant,
+) -> (UtteranceEnd, bool) {
+    let max_utterance = CONTINUOUS_MAX_UTTERANCE.min(Duration::from_secs(u64::from(
+        state.settings().max_recording_seconds - 2,
+    )));
+    let started = Instant::now();
+    let mut checked = 0;
+    let mut heard_speech = false;
+    let mut last_speech = started;
+    loop {
+        std::thread::sleep(CONTINUOUS_POLL);
+        if state.active_recording_id() != Some(recording_id) {
+            return (UtteranceEnd::Superseded, heard_speech);
+        }
+        if state
+            .continuous_status()
+            .is_none_or(|session| session.stopping)
+        {
+            return (UtteranceEnd::StopRequested, heard_speech);
+        }
+        if Instant::now() >= deadline {
+            return (UtteranceEnd::SessionLimit, heard_speech);
+        }
+
+        let Some(samples) = state.live_samples() else {
+            return (UtteranceEnd::Superseded, heard_speech);
+        };
+        let level = samples.lock().ok().and_then(|buffer| {
+            let fresh = &buffer[checked.min(buffer.len())..];
+            checked = buffer.len();
+            (!fresh.is_empty()).then(|| audio::rms(fresh))
+        });
+        if level.is_some_and(|rms| rms >= CONTINUOUS_SPEECH_RMS) {
+            heard_speech = true;
+            last_speech = Instant::now();
+        }
+
+        if heard_speech && last_speech.elapsed() >= CONTINUOUS_PAUSE {
+            return (UtteranceEnd::Pause, true);
+        }
+        if started.elapsed() >= max_utterance {
+            return (UtteranceEnd::BufferFull, heard_speech);
+        }
+    }
+}
+
+/// Drops the recording in progress without transcribing it.
+fn discard_recording(app: &AppHandle, state: &SharedState) {
+    if let Ok(TakenRecording { session, .. }) = state.take_recording(None) {
+        crate::set_tray_listening(app, false);
+        audio::abandon_capture(session);
+    }
+    state.set_idle();
+}
+
+/// A recording that has been stopped and handed off, waiting to be
+/// transcribed.
+struct StoppedRecording {
+    capture: PendingCapture,
+    cancel_requested: Arc<AtomicBool>,
+    stop_started: Instant,
+    capture_stop_ms: u64,
+    summary: RecordingSummary,
+}
+
+/// Stops and transcribes the current recording, or only recording
+/// `recording_id` when one is given.
+async fn stop_recording_impl(
+    app: AppHandle,
+    state: SharedState,
+    recording_id: Option<u64>,
+) -> Result<RecordingSummary> {
+    let StoppedRecording {
+        capture,
+        cancel_requested,
+        stop_started,
+        capture_stop_ms,
+        summary,
+    } = end_recording(&app, &state, recording_id)?;
+    let result = transcribe_capture(
+        &app,
+        &state,
+        &capture,
+        cancel_requested,
+        stop_started,
+        capture_stop_ms,
+    )
+    .await;
+    finish_capture(&app, &state, capture, &result);
+    result.map(|()| summary)
+}
+
+/// Stops the current recording (or only `recording_id`) and reports what was
+/// captured. The app stays in `Processing` until the capture has been
+/// transcribed.
+fn end_recording(
+    app: &AppHandle,
+    state: &SharedState,
+    recording_id: Option<u64>,
+) -> Result<StoppedRecording> {
+    let stop_started = Instant::now();
+    let TakenRecording {
+        session,
+        cancel_requested,
+        source,
+        mode,
+    } = state
+        .take_recording(recording_id)
+        .map_err(|e| anyhow::anyhow!(e))?;
+    crate::set_tray_listening(app, false);
+
+    // The limit the recording started with; the setting may have changed since.
+    let limit = session.max_seconds;
+    let captured = audio::stop_capture(session);
+    let capture_stop_ms = stop_started.elapsed().as_millis() as u64;
+    let signal = audio::analyze_signal(
+        &captured.samples,
+        state.settings().activity_thresholds.decision,
+    );
+    let summary = RecordingSummary {
+        duration_ms: captured.duration_ms,
+        sample_rate: captured.sample_rate,
+        truncated: captured.truncated,
+        signal,
+    };
+    let _ = app.emit("recording-stopped", summary.clone());
+    state.remember_capture(captured.samples.clone(), captured.sample_rate);
+
+    if captured.truncated {
+        emit_notice(
+            app,
+            format!(
+                "Recording exceeded {limit} seconds. Only the first {limit} seconds were transcribed."
+            ),
         );
+    }
 
-        // If the captured signal is effectively flat, decoding will usually produce empty
-        // output. Surface a direct diagnostic instead of repeatedly showing no-speech.
-        if signal.rms < 0.0008 || (signal.peak < 0.008 && signal.active_ratio < 0.003) {
-            let input_status = audio::input_status();
-            let input_name = input_status
-                .default_input
-                .unwrap_or_else(|| "Unknown input".to_string());
-            emit_error(
-                &app,
-                format!(
-                    "No microphone signal detected. Check Murmur microphone permission in System Settings > Privacy & Security > Microphone, and verify the active input device (current default: {}).",
-                    input_name
-                ),
-            );
+    if let Some(previous) = state.replace_capture_config(captured.config.clone()) {
+        if previous != captured.config {
+            logging::info(format!(
+                "capture config changed: {previous} -> {}",
+                captured.config
+            ));
             emit_notice(
-                &app,
-                format!(
-                    "Capture levels were very low (rms {:.5}, peak {:.5}).",
-                    signal.rms, signal.peak
-                ),
+                app,
+                format!("Microphone input changed to {}.", captured.config),
             );
-            return Ok(());
         }
+    }
 
-        let db_path = state.db_path();
-        let models_dir = state.models_dir();
-        let mut model_name = state.active_model_name();
-        let mut model_path = state.active_model_path();
+    if let Some(stream_error) = &captured.stream_error {
+        emit_notice(
+            app,
+            format!("The microphone stream reported an error while recording: {stream_error}"),
+        );
+    }
 
-        if !model_path.exists() {
-            let fallback = models::pick_default_model(&models_dir);
-            let fallback_path = models_dir.join(&fallback);
-            if fallback_path.exists() {
-                if fallback != model_name {
-                    emit_notice(
-                        &app,
-                        format!(
-                            "Active model '{}' is missing. Switched to '{}'.",
-                            model_name, fallback
-                        ),
-                    );
-                }
-                let _ = state.set_active_model(fallback.clone());
-                model_name = fallback;
-                model_path = fallback_path;
+    Ok(StoppedRecording {
+        capture: PendingCapture {
+            captured,
+            source,
+            mode,
+        },
+        cancel_requested,
+        stop_started,
+        capture_stop_ms,
+        summary,
+    })
+}
+
+async fn retry_last_transcription_impl(app: AppHandle, state: SharedState) -> Result<()> {
+    let started = Instant::now();
+    let Some(capture) = state.take_failed_capture() else {
+        anyhow::bail!("There is no failed transcription to retry.");
+    };
+    let cancel_requested = match state.begin_retry() {
+        Ok(flag) => flag,
+        Err(err) => {
+            state.keep_failed_capture(capture);
+            anyhow::bail!(err);
+        }
+    };
+    let result = transcribe_capture(&app, &state, &capture, cancel_requested, started, 0).await;
+    finish_capture(&app, &state, capture, &result);
+    result
+}
+
+/// Ends a capture's processing: back to idle, the error reported, and the
+/// capture kept for `retry_last_transcription` if transcribing it failed.
+fn finish_capture(
+    app: &AppHandle,
+    state: &SharedState,
+    capture: PendingCapture,
+    result: &Result<()>,
+) {
+    match result {
+        Ok(()) => state.clear_failed_capture(),
+        Err(_) => offer_retry(app, state, capture),
+    }
+    state.set_idle();
+    crate::set_tray_listening(app, false);
+    if let Err(err) = result {
+        emit_error(app, err.to_string());
+    }
+}
+
+/// Keeps a capture whose transcription failed so it can be retried.
+fn offer_retry(app: &AppHandle, state: &SharedState, capture: PendingCapture) {
+    let _ = app.emit(
+        "transcription-retry-available",
+        RetryAvailablePayload {
+            duration_ms: capture.captured.duration_ms,
+        },
+    );
+    state.keep_failed_capture(capture);
+}
+
+/// Shortens long pauses in a recording when pause trimming is enabled. Shared
+/// by the recording path and the preprocessing preview so they stay in step.
+fn apply_pause_trim(samples: &[f32], sample_rate: u32, trim: settings::PauseTrim) -> Vec<f32> {
+    if trim.enabled {
+        audio::trim_long_pauses(samples, sample_rate, trim.max_pause_ms)
+    } else {
+        samples.to_vec()
+    }
+}
+
+/// Transcribes a finished capture and delivers the text: history, clipboard,
+/// paste, output files and the completion event.
+async fn transcribe_capture(
+    app: &AppHandle,
+    state: &SharedState,
+    capture: &PendingCapture,
+    cancel_requested: Arc<AtomicBool>,
+    stop_started: Instant,
+    capture_stop_ms: u64,
+) -> Result<()> {
+    let captured = &capture.captured;
+    let (source, mode) = (capture.source, capture.mode);
+    let signal = audio::analyze_signal(
+        &captured.samples,
+        state.settings().activity_thresholds.decision,
+    );
+    let short_recording = state.short_recording();
+    if captured.duration_ms < i64::from(short_recording.min_ms) {
+        let _ = app.emit(
+            "recording-too-short",
+            RecordingTooShortPayload {
+                duration_ms: captured.duration_ms,
+                min_ms: short_recording.min_ms,
+                policy: short_recording.policy,
+            },
+        );
+        // `recording-too-short` is the only event for it; the frontend shows
+        // the error itself under the `Error` policy.
+        match short_recording.policy {
+            ShortRecordingPolicy::Error | ShortRecordingPolicy::Ignore => {
+                logging::info(format!("recording too short ({} ms)", captured.duration_ms));
+                return Ok(());
             }
+            ShortRecordingPolicy::Transcribe => {}
         }
+    }
 
-        if !model_path.exists() {
-            anyhow::bail!(
-                "No installed model available. Download a model or add a .bin file in the models directory."
-            );
+    eprintln!(
+        "capture stats: samples={}, sample_rate={}, rms={:.6}, peak={:.6}, active_ratio={:.4}",
+        captured.samples.len(),
+        captured.sample_rate,
+        signal.rms,
+        signal.peak,
+        signal.active_ratio
+    );
+
+    // If the captured signal is effectively flat, decoding will usually produce empty
+    // output. Surface a direct diagnostic instead of repeatedly showing no-speech.
+    if signal.rms < 0.0008 || (signal.peak < 0.008 && signal.active_ratio < 0.003) {
+        let input_status = audio::input_status();
+        let input_name = input_status
+            .default_input
+            .unwrap_or_else(|| "Unknown input".to_string());
+        emit_error(
+            app,
+            format!(
+                "No microphone signal detected. Check Murmur microphone permission in System Settings > Privacy & Security > Microphone, and verify the active input device (current default: {}).",
+                input_name
+            ),
+        );
+        emit_notice(
+            app,
+            format!(
+                "Capture levels were very low (rms {:.5}, peak {:.5}).",
+                signal.rms, signal.peak
+            ),
+        );
+        return Ok(());
+    }
+
+    let db_path = state.db_path();
+    let models_dir = state.models_dir();
+    let mut model_name = state.active_model_name();
+    let mut model_path = state.active_model_path();
+
+    if !model_path.exists() {
+        let fallback = models::pick_default_model(&models_dir);
+        let fallback_path = models_dir.join(&fallback);
+        if fallback_path.exists() {
+            if fallback != model_name {
+                emit_notice(
+                    app,
+                    format!(
+                        "Active model '{}' is missing. Switched to '{}'.",
+                        model_name, fallback
+                    ),
+                );
+            }
+            let _ = state.set_active_model(fallback.clone());
+            model_name = fallback;
+            model_path = fallback_path;
         }
+    }
 
-        let samples = captured.samples.clone();
-        let sample_rate = captured.sample_rate;
-        let duration_ms = captured.duration_ms;
-        let primary_model_name = model_name.clone();
-        let primary_model_path = model_path.clone();
-        let fallback_model_name = "ggml-base.en.bin".to_string();
-        let fallback_model_path = models_dir.join(&fallback_model_name);
-        let fallback_available = fallback_model_path.exists() && fallback_model_path != primary_model_path;
-        let cancel_for_worker = cancel_requested.clone();
-        let transcribe_started = Instant::now();
-        let transcription = tauri::async_runtime::spawn_blocking(move || {
-            let mut text = whisper::transcribe(
-                &primary_model_path,
-                &samples,
-                sample_rate,
-                Some(cancel_for_worker.clone()),
-            )?;
-            let mut used_model = primary_model_name;
+    if !model_path.exists() {
+        anyhow::bail!(
+            "No installed model available. Download a model or add a .bin file in the models directory."
+        );
+    }
 
-            if text.trim().is_empty() && fallback_available {
-                if let Ok(fallback_text) = whisper::transcribe(
-                    &fallback_model_path,
+    let pipeline_settings = state.settings();
+    // Speaker channels are left alone: trimming them separately would
+    // break the alignment their turns are labeled by.
+    let samples = apply_pause_trim(
+        &captured.samples,
+        captured.sample_rate,
+        pipeline_settings.pause_trim,
+    );
+    let sample_rate = captured.sample_rate;
+    let duration_ms = captured.duration_ms;
+    let primary_model_name = model_name.clone();
+    let primary_model_path = model_path.clone();
+    let fallback_model_name = "ggml-base.en.bin".to_string();
+    let fallback_model_path = models_dir.join(&fallback_model_name);
+    let fallback_available =
+        fallback_model_path.exists() && fallback_model_path != primary_model_path;
+    let options = whisper::TranscribeOptions {
+        mode,
+        ..transcribe_options(state)
+    };
+    let speaker_turns = pipeline_settings.speaker_turns;
+    let speakers = captured.speakers.clone().filter(|_| speaker_turns.enabled);
+    let cancel_for_worker = cancel_requested.clone();
+    let transcribe_started = Instant::now();
+    let transcription = tauri::async_runtime::spawn_blocking(move || {
+        let primary_started = Instant::now();
+        let (mut transcript, mut by_speaker) = match speakers {
+            Some((first, second)) => {
+                let (merged, split) = transcribe_speakers(
+                    &primary_model_path,
+                    [&first, &second],
+                    sample_rate,
+                    options.clone(),
+                    &cancel_for_worker,
+                )?;
+                (merged, Some(split))
+            }
+            None => (
+                whisper::transcribe(
+                    &primary_model_path,
                     &samples,
                     sample_rate,
-                    Some(cancel_for_worker),
-                ) {
-                    if !fallback_text.trim().is_empty() {
-                        text = fallback_text;
-                        used_model = fallback_model_name;
-                    }
+                    options.clone(),
+                    Some(cancel_for_worker.clone()),
+                )?,
+                None,
+            ),
+        };
+        let primary_ms = primary_started.elapsed().as_millis() as i64;
+        let mut used_model = primary_model_name;
+
+        if transcript.is_empty() && fallback_available {
+            if let Ok(fallback) = whisper::transcribe(
+                &fallback_model_path,
+                &samples,
+                sample_rate,
+                options,
+                Some(cancel_for_worker),
+            ) {
+                if !fallback.is_empty() {
+                    transcript = fallback;
+                    by_speaker = None;
+                    used_model = fallback_model_name;
                 }
             }
+        }
 
-            Ok((text, duration_ms, used_model))
-        })
-        .await?;
-        let transcribe_ms = transcribe_started.elapsed().as_millis() as u64;
+        Ok((transcript, by_speaker, duration_ms, used_model, primary_ms))
+    })
+    .await?;
+    let transcribe_ms = transcribe_started.elapsed().as_millis() as u64;
+    notify_gpu_fallback(app);
 
-        if cancel_requested.load(Ordering::Relaxed) {
-            emit_notice(&app, "Transcription cancelled.");
-            let _ = app.emit("transcription-cancelled", ());
-            return Ok(());
-        }
+    if cancel_requested.load(Ordering::Relaxed) {
+        emit_notice(app, "Transcription cancelled.");
+        let _ = app.emit("transcription-cancelled", ());
+        return Ok(());
+    }
 
-        let (text, duration_ms, used_model_name) = match transcription {
-            Ok(value) => value,
-            Err(err) => {
-                if cancel_requested.load(Ordering::Relaxed) {
-                    emit_notice(&app, "Transcription cancelled.");
-                    let _ = app.emit("transcription-cancelled", ());
-                    return Ok(());
-                }
-                return Err(err);
+    let (transcript, by_speaker, duration_ms, used_model_name, primary_ms) = match transcription {
+        Ok(value) => value,
+        Err(err) => {
+            if cancel_requested.load(Ordering::Relaxed) {
+                emit_notice(app, "Transcription cancelled.");
+                let _ = app.emit("transcription-cancelled", ());
+                return Ok(());
             }
-        };
+            return Err(err);
+        }
+    };
 
-        if used_model_name != model_name {
-            emit_notice(
-                &app,
-                format!(
-                    "Active model '{}' returned no text; used '{}' as fallback for this transcription.",
-                    model_name, used_model_name
-                ),
-            );
-            model_name = used_model_name;
+    // Per-speaker runs decode the audio twice, which would skew the benchmark.
+    if by_speaker.is_none() {
+        if let Err(err) = db::record_benchmark(&db_path, &model_name, duration_ms, primary_ms) {
+            logging::warn(format!("failed to record transcription benchmark: {err}"));
         }
+    }
 
-        let normalized = if text.trim().is_empty() {
-            "(No speech detected)".to_string()
-        } else {
-            text.trim().to_string()
-        };
+    if used_model_name != model_name {
+        emit_notice(
+            app,
+            format!(
+                "Active model '{}' returned no text; used '{}' as fallback for this transcription.",
+                model_name, used_model_name
+            ),
+        );
+        model_name = used_model_name;
+    }
 
-        let id = db::insert(&db_path, &normalized, duration_ms, &model_name)?;
+    let verbatim = mode == TranscriptionMode::Verbatim;
+    let stage_started = Instant::now();
+    let normalized = match &by_speaker {
+        Some([first, second]) => {
+            finalize_speaker_text(state, [first, second], &speaker_turns.labels, verbatim)
+        }
+        None => finalize_text(state, &transcript, verbatim),
+    };
+    let postprocess_ms = stage_started.elapsed().as_millis() as u64;
 
-        let auto_copied = if state.auto_copy() {
-            app.clipboard().write_text(normalized.clone())?;
-            true
-        } else {
-            false
-        };
+    let stage_started = Instant::now();
+    let id = db::insert(
+        &db_path,
+        &db::NewTranscription {
+            text: &normalized,
+            duration_ms,
+            model: &model_name,
+            verbatim,
+            source,
+            capture_config: Some(&captured.config.to_string()),
+            language: transcript.language.as_deref(),
+            segments: segments_json(state, &transcript, verbatim).as_deref(),
+            avg_confidence: transcript.avg_confidence(),
+        },
+    )?;
+    apply_history_retention(state);
+    let insert_ms = stage_started.elapsed().as_millis() as u64;
 
-        if transcribe_ms > 15_000 {
+    // The transcription is already saved, so a busy clipboard should not turn
+    // a good result into an error.
+    let app_settings = state.settings();
+    if app_settings.keep_audio {
+        if let Err(err) = audio::keep_audio(
+            &state.audio_dir(),
+            id,
+            &captured.samples,
+            captured.sample_rate,
+        ) {
             emit_notice(
-                &app,
-                format!(
-                    "Transcription took {:.1}s. Consider a smaller model for faster response.",
-                    transcribe_ms as f64 / 1000.0
-                ),
+                app,
+                format!("Transcription saved, but its audio could not be kept: {err}"),
             );
         }
+    }
+    let stage_started = Instant::now();
+    // Continuous dictation copies everything said so far, not just the
+    // latest utterance.
+    let continuous = if source == TranscriptionSource::Continuous {
+        state.append_continuous(&normalized)
+    } else {
+        None
+    };
+    let output_text = continuous
+        .as_ref()
+        .map_or(normalized.as_str(), |session| session.text.as_str());
+    let wants_copy = state.auto_copy()
+        || matches!(
+            source,
+            TranscriptionSource::QuickNote | TranscriptionSource::Continuous
+        );
+    let auto_copied = if wants_copy && app_settings.output_target.uses_clipboard() {
+        match app.clipboard().write_text(output_text.to_string()) {
+            Ok(()) => true,
+            Err(err) => {
+                emit_notice(
+                    app,
+                    format!("Transcription saved, but copying to the clipboard failed: {err}"),
+                );
+                false
+            }
+        }
+    } else {
+        false
+    };
+    if let Some(file) = app_settings.output_target.file() {
+        write_output_target(app, file, output_text);
+    }
+    // Only the latest utterance is pasted; the rest of a continuous session
+    // was pasted as it came in. The clipboard holds the session text then, so
+    // the utterance goes on it just for the paste. A silent one has nothing
+    // to paste.
+    let silent_utterance = continuous.is_some() && normalized == db::NO_SPEECH_PLACEHOLDER;
+    let auto_pasted = if app_settings.stream_typing {
+        // Continuous dictation types only what the session text adds to what
+        // was typed before.
+        let (typed, target) = match &continuous {
+            Some(session) => (
+                state
+                    .swap_continuous_typed(&session.text)
+                    .unwrap_or_default(),
+                session.text.as_str(),
+            ),
+            None => (String::new(), normalized.as_str()),
+        };
+        let (backspaces, text) = paste::typing_edit(&typed, target);
+        // The rest of a session follows what was typed, already spaced.
+        let text = if typed.is_empty() {
+            app_settings
+                .paste_mode
+                .fit(state.last_inserted_char(), text)
+        } else {
+            text.to_string()
+        };
+        match paste::type_text(backspaces, &text) {
+            Ok(()) => {
+                state.record_inserted(&text);
+                true
+            }
+            Err(err) => {
+                emit_notice(
+                    app,
+                    format!("Transcription saved, but typing it failed: {err}"),
+                );
+                false
+            }
+        }
+    } else {
+        let pasted = app_settings
+            .paste_mode
+            .fit(state.last_inserted_char(), &normalized);
+        // The clipboard holds the text as transcribed, not as fitted.
+        let copied = auto_copied && continuous.is_none() && pasted == normalized;
+        app_settings.auto_paste
+            && !silent_utterance
+            && match paste_output(app, &pasted, copied) {
+                Ok(()) => {
+                    state.record_inserted(&pasted);
+                    true
+                }
+                Err(err) => {
+                    emit_notice(
+                        app,
+                        format!("Transcription saved, but pasting it failed: {err}"),
+                    );
+                    false
+                }
+            }
+    };
+    let copy_ms = stage_started.elapsed().as_millis() as u64;
 
-        let payload = TranscriptionCompletePayload {
+    if app_settings.debug_mode {
+        let timing = TranscriptionTimingPayload {
             id,
-            text: normalized,
-            duration_ms,
-            model: model_name,
-            auto_copied,
+            model: model_name.clone(),
+            audio_ms: duration_ms,
+            capture_stop_ms,
+            prepare_ms: transcript.timing.prepare_ms,
+            model_load_ms: transcript.timing.model_load_ms,
+            model_cached: transcript.timing.model_cached,
+            decode_ms: transcript.timing.decode_ms,
+            whisper_ms: transcribe_ms,
+            postprocess_ms,
+            insert_ms,
+            copy_ms,
+            total_ms: stop_started.elapsed().as_millis() as u64,
         };
-        let _ = app.emit("transcription-complete", payload);
-        Ok(())
+        logging::info(format!("transcription timing: {timing:?}"));
+        let _ = app.emit("transcription-timing", timing);
     }
-    .await;
 
-    state.set_idle();
-    crate::set_tray_listening(&app, false);
-    if let Err(err) = &result {
-        emit_error(&app, err.to_string());
+    if transcribe_ms > 15_000 {
+        emit_notice(
+            app,
+            format!(
+                "Transcription took {:.1}s. Consider a smaller model for faster response.",
+                transcribe_ms as f64 / 1000.0
+            ),
+        );
+    }
+
+    append_live_output(app, state, id, &normalized, &model_name, duration_ms);
+    if let Some(session) = continuous {
+        let _ = app.emit(
+            "continuous-utterance",
+            ContinuousUtterancePayload {
+                id,
+                text: normalized.clone(),
+                utterances: session.utterances,
+                elapsed_ms: session.elapsed_ms,
+            },
+        );
+    }
+
+    let payload = TranscriptionCompletePayload {
+        id,
+        text: normalized,
+        duration_ms,
+        model: model_name,
+        auto_copied,
+        auto_pasted,
+        verbatim,
+        avg_confidence: transcript.avg_confidence(),
+    };
+    let _ = app.emit("transcription-complete", payload);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_uri_path_handles_windows_encoded_and_posix_uris() {
+        assert_eq!(file_uri_path("/C:/x.wav"), PathBuf::from("C:/x.wav"));
+        assert_eq!(
+            file_uri_path("localhost/d:/Voice%20Memos/a.wav"),
+            PathBuf::from("d:/Voice Memos/a.wav")
+        );
+        assert_eq!(
+            file_uri_path("/home/me/My%20Notes/%C3%A9t%C3%A9.wav"),
+            PathBuf::from("/home/me/My Notes/été.wav")
+        );
+        assert_eq!(file_uri_path("/tmp/a.wav"), PathBuf::from("/tmp/a.wav"));
+    }
+
+    #[test]
+    fn cancelled_or_empty_retranscription_is_not_saved() {
+        let spoken = whisper::Transcript {
+            segments: vec![whisper::Segment {
+                start_ms: 0,
+                end_ms: 1_000,
+                text: "Hello there.".to_string(),
+                avg_prob: 0.9,
+            }],
+            ..whisper::Transcript::default()
+        };
+
+        assert!(ensure_replacement_transcript(&spoken, false).is_ok());
+        assert!(ensure_replacement_transcript(&spoken, true).is_err());
+        assert!(ensure_replacement_transcript(&whisper::Transcript::default(), false).is_err());
     }
-    result
 }
@@ -1,7 +1,9 @@
-use anyhow::Result;
-use rusqlite::{params, Connection, ErrorCode};
-use serde::Serialize;
-use std::{fs, path::Path, thread, time::Duration};
+use crate::postprocess;
+use anyhow::{anyhow, bail, Result};
+use rusqlite::backup::Progress;
+use rusqlite::{params, Connection, DatabaseName, ErrorCode, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, fs, path::Path, sync::RwLock, thread, time::Duration};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct HistoryEntry {
@@ -10,17 +12,182 @@ pub struct HistoryEntry {
     pub created_at: String,
     pub duration_ms: Option<i64>,
     pub model: String,
+    pub verbatim: bool,
+    pub source: TranscriptionSource,
+    /// `None` for rows saved before the language was recorded.
+    pub language: Option<String>,
+    /// Mean token probability from 0 to 1. `None` for rows saved before it
+    /// was recorded.
+    pub avg_confidence: Option<f64>,
 }
 
-const BUSY_TIMEOUT_MS: u64 = 2_500;
+/// A history row with its text cut to a preview, for list views.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistorySummary {
+    pub id: i64,
+    /// The text shortened to `preview_chars` characters, ending in an
+    /// ellipsis when it was cut.
+    pub preview: String,
+    /// Length of the full text in characters.
+    pub text_chars: i64,
+    pub created_at: String,
+    pub duration_ms: Option<i64>,
+    pub model: String,
+    pub verbatim: bool,
+    pub source: TranscriptionSource,
+    pub language: Option<String>,
+    pub avg_confidence: Option<f64>,
+}
+
+/// How a transcription was started, for history and support diagnostics.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionSource {
+    /// Started from the app window, or by an entry point that predates this column.
+    #[default]
+    Recording,
+    Hotkey,
+    QuickNote,
+    File,
+    Continuous,
+}
+
+impl TranscriptionSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TranscriptionSource::Recording => "recording",
+            TranscriptionSource::Hotkey => "hotkey",
+            TranscriptionSource::QuickNote => "quick_note",
+            TranscriptionSource::File => "file",
+            TranscriptionSource::Continuous => "continuous",
+        }
+    }
+
+    fn from_column(raw: &str) -> Self {
+        match raw {
+            "hotkey" => TranscriptionSource::Hotkey,
+            "quick_note" => TranscriptionSource::QuickNote,
+            "file" => TranscriptionSource::File,
+            "continuous" => TranscriptionSource::Continuous,
+            _ => TranscriptionSource::Recording,
+        }
+    }
+}
+
+/// A transcription the user marked as wrong. The original text, model, and
+/// capture config are copied at submit time, so the record outlives edits to
+/// or deletion of the history row.
+#[derive(Debug, Clone, Serialize)]
+pub struct Correction {
+    pub id: i64,
+    /// `None` once the history row has been deleted.
+    pub transcription_id: Option<i64>,
+    pub original_text: String,
+    /// `None` when the user only flagged the result without correcting it.
+    pub corrected_text: Option<String>,
+    pub model: String,
+    pub capture_config: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalyticsBucket {
+    Day,
+    Week,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnalyticsPoint {
+    pub bucket_start: String,
+    pub transcriptions: i64,
+    pub words: i64,
+    pub duration_ms: i64,
+    pub words_per_minute: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchIndexReport {
+    pub transcription_rows: i64,
+    pub indexed_rows: i64,
+    pub integrity_ok: bool,
+    pub issues: Vec<String>,
+}
+
+/// Text stored when whisper returns nothing for a capture.
+pub const NO_SPEECH_PLACEHOLDER: &str = "(No speech detected)";
+
+/// Rows that hold the placeholder or only whitespace. A short answer such as
+/// "I" or "No" is real text and is kept.
+const EMPTY_TRANSCRIPTION_FILTER: &str = "text = ?1 OR trim(text, ' ' || char(9, 10, 13)) = ''";
+
+/// SQLite `synchronous` pragma. `Normal` skips the fsync on every commit; under
+/// WAL the database stays consistent after a crash, but the most recent commits
+/// can be lost on power failure.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+    Full,
+    Normal,
+}
+
+impl SyncMode {
+    fn as_pragma(self) -> &'static str {
+        match self {
+            SyncMode::Full => "FULL",
+            SyncMode::Normal => "NORMAL",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct DbTuning {
+    pub synchronous: SyncMode,
+    pub busy_timeout_ms: u64,
+}
+
+pub const DEFAULT_BUSY_TIMEOUT_MS: u64 = 2_500;
+pub const MIN_BUSY_TIMEOUT_MS: u64 = 100;
+pub const MAX_BUSY_TIMEOUT_MS: u64 = 60_000;
 const MAX_RETRIES: usize = 5;
 const RETRY_BACKOFF_MS: [u64; MAX_RETRIES] = [25, 50, 100, 200, 400];
 
+impl Default for DbTuning {
+    fn default() -> Self {
+        Self {
+            synchronous: SyncMode::Full,
+            busy_timeout_ms: DEFAULT_BUSY_TIMEOUT_MS,
+        }
+    }
+}
+
+static TUNING: RwLock<DbTuning> = RwLock::new(DbTuning {
+    synchronous: SyncMode::Full,
+    busy_timeout_ms: DEFAULT_BUSY_TIMEOUT_MS,
+});
+
+/// Sets the pragmas applied to every connection opened afterwards.
+pub fn configure(tuning: DbTuning) {
+    if let Ok(mut guard) = TUNING.write() {
+        *guard = DbTuning {
+            synchronous: tuning.synchronous,
+            busy_timeout_ms: tuning
+                .busy_timeout_ms
+                .clamp(MIN_BUSY_TIMEOUT_MS, MAX_BUSY_TIMEOUT_MS),
+        };
+    }
+}
+
+pub fn tuning() -> DbTuning {
+    TUNING.read().map(|guard| *guard).unwrap_or_default()
+}
+
 fn open_connection(path: &Path) -> rusqlite::Result<Connection> {
+    let tuning = tuning();
     let conn = Connection::open(path)?;
-    conn.busy_timeout(Duration::from_millis(BUSY_TIMEOUT_MS))?;
+    conn.busy_timeout(Duration::from_millis(tuning.busy_timeout_ms))?;
     conn.pragma_update(None, "journal_mode", "WAL")?;
-    conn.pragma_update(None, "synchronous", "FULL")?;
+    conn.pragma_update(None, "synchronous", tuning.synchronous.as_pragma())?;
     conn.pragma_update(None, "foreign_keys", "ON")?;
     Ok(conn)
 }
@@ -50,6 +217,28 @@ where
     unreachable!("retry loop should always return");
 }
 
+/// Adds a column to an existing table. `CREATE TABLE IF NOT EXISTS` leaves
+/// databases from older releases untouched, so new columns go through here.
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .iter()
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))?;
+    }
+    Ok(())
+}
+
 pub fn init(path: &Path) -> Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -86,19 +275,84 @@ pub fn init(path: &Path) -> Result<()> {
           VALUES ('delete', old.id, old.text);
           INSERT INTO transcriptions_fts(rowid, text) VALUES (new.id, new.text);
         END;
+
+        CREATE TABLE IF NOT EXISTS model_benchmarks (
+            id            INTEGER PRIMARY KEY AUTOINCREMENT,
+            model         TEXT NOT NULL,
+            audio_ms      INTEGER NOT NULL,
+            transcribe_ms INTEGER NOT NULL,
+            created_at    TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_model_benchmarks_model
+          ON model_benchmarks(model, id DESC);
+
+        CREATE TABLE IF NOT EXISTS corrections (
+            id               INTEGER PRIMARY KEY AUTOINCREMENT,
+            transcription_id INTEGER REFERENCES transcriptions(id) ON DELETE SET NULL,
+            original_text    TEXT NOT NULL,
+            corrected_text   TEXT,
+            model            TEXT NOT NULL,
+            capture_config   TEXT,
+            created_at       TEXT NOT NULL DEFAULT (datetime('now'))
+        );
         "#,
     )?;
+    add_column_if_missing(
+        &conn,
+        "transcriptions",
+        "verbatim",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(
+        &conn,
+        "transcriptions",
+        "source",
+        "TEXT NOT NULL DEFAULT 'recording'",
+    )?;
+    add_column_if_missing(&conn, "transcriptions", "capture_config", "TEXT")?;
+    add_column_if_missing(&conn, "transcriptions", "language", "TEXT")?;
+    add_column_if_missing(&conn, "transcriptions", "segments", "TEXT")?;
+    add_column_if_missing(&conn, "transcriptions", "avg_confidence", "REAL")?;
 
     Ok(())
 }
 
-pub fn insert(path: &Path, text: &str, duration_ms: i64, model: &str) -> Result<i64> {
+/// A transcription about to be saved.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NewTranscription<'a> {
+    pub text: &'a str,
+    pub duration_ms: i64,
+    pub model: &'a str,
+    pub verbatim: bool,
+    pub source: TranscriptionSource,
+    pub capture_config: Option<&'a str>,
+    /// Language code whisper decoded the audio as.
+    pub language: Option<&'a str>,
+    /// Timed segments as JSON, for subtitle export.
+    pub segments: Option<&'a str>,
+    /// Mean token probability, from 0 to 1.
+    pub avg_confidence: Option<f64>,
+}
+
+pub fn insert(path: &Path, entry: &NewTranscription<'_>) -> Result<i64> {
     with_retry(|| {
         let mut conn = open_connection(path)?;
         let tx = conn.transaction()?;
         tx.execute(
-            "INSERT INTO transcriptions (text, duration_ms, model) VALUES (?1, ?2, ?3)",
-            params![text, duration_ms, model],
+            "INSERT INTO transcriptions (text, duration_ms, model, verbatim, source, capture_config, language, segments, avg_confidence)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                entry.text,
+                entry.duration_ms,
+                entry.model,
+                entry.verbatim,
+                entry.source.as_str(),
+                entry.capture_config,
+                entry.language,
+                entry.segments,
+                entry.avg_confidence
+            ],
         )?;
         let id = tx.last_insert_rowid();
         tx.commit()?;
@@ -106,23 +360,78 @@ pub fn insert(path: &Path, text: &str, duration_ms: i64, model: &str) -> Result<
     })
 }
 
+/// Records how long `model` took to transcribe `audio_ms` of audio on this machine.
+pub fn record_benchmark(path: &Path, model: &str, audio_ms: i64, transcribe_ms: i64) -> Result<()> {
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        conn.execute(
+            "INSERT INTO model_benchmarks (model, audio_ms, transcribe_ms) VALUES (?1, ?2, ?3)",
+            params![model, audio_ms, transcribe_ms],
+        )?;
+        Ok(())
+    })
+}
+
+/// Most recent realtime factors (transcription time / audio time) for `model`.
+pub fn realtime_factors(path: &Path, model: &str, limit: i64) -> Result<Vec<f64>> {
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        let mut stmt = conn.prepare(
+            "SELECT CAST(transcribe_ms AS REAL) / audio_ms
+             FROM model_benchmarks
+             WHERE model = ?1 AND audio_ms > 0
+             ORDER BY id DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![model, limit], |row| row.get(0))?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+    })
+}
+
 pub fn list(path: &Path, limit: i64) -> Result<Vec<HistoryEntry>> {
     with_retry(|| {
         let conn = open_connection(path)?;
         let mut stmt = conn.prepare(
-            "SELECT id, text, created_at, duration_ms, model
+            "SELECT id, text, created_at, duration_ms, model, verbatim, source, language, avg_confidence
+             FROM transcriptions
+             ORDER BY id DESC
+             LIMIT ?1",
+        )?;
+
+        let rows = stmt.query_map([limit], history_entry_from_row)?;
+
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+    })
+}
+
+/// Like `list`, but each row carries only a preview of its text, cut by
+/// `postprocess::truncate_preview`.
+pub fn list_summaries(
+    path: &Path,
+    limit: i64,
+    preview_chars: usize,
+) -> Result<Vec<HistorySummary>> {
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, text, length(text), created_at, duration_ms, model, verbatim, source, language, avg_confidence
              FROM transcriptions
              ORDER BY id DESC
              LIMIT ?1",
         )?;
 
         let rows = stmt.query_map([limit], |row| {
-            Ok(HistoryEntry {
+            Ok(HistorySummary {
                 id: row.get(0)?,
-                text: row.get(1)?,
-                created_at: row.get(2)?,
-                duration_ms: row.get(3)?,
-                model: row.get(4)?,
+                preview: postprocess::truncate_preview(&row.get::<_, String>(1)?, preview_chars),
+                text_chars: row.get(2)?,
+                created_at: row.get(3)?,
+                duration_ms: row.get(4)?,
+                model: row.get(5)?,
+                verbatim: row.get(6)?,
+                source: TranscriptionSource::from_column(&row.get::<_, String>(7)?),
+                language: row.get(8)?,
+                avg_confidence: row.get(9)?,
             })
         })?;
 
@@ -130,6 +439,327 @@ pub fn list(path: &Path, limit: i64) -> Result<Vec<HistoryEntry>> {
     })
 }
 
+/// Rows that hold the no-speech placeholder or only whitespace, newest first.
+pub fn list_empty(path: &Path, limit: i64) -> Result<Vec<HistoryEntry>> {
+    let sql = format!(
+        "SELECT id, text, created_at, duration_ms, model, verbatim, source, language, avg_confidence
+         FROM transcriptions
+         WHERE {EMPTY_TRANSCRIPTION_FILTER}
+         ORDER BY id DESC
+         LIMIT ?2"
+    );
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(
+            params![NO_SPEECH_PLACEHOLDER, limit],
+            history_entry_from_row,
+        )?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+    })
+}
+
+/// Deletes every row `list_empty` would return. The delete trigger keeps the
+/// search index in step. Returns the number of rows removed.
+pub fn delete_empty(path: &Path) -> Result<usize> {
+    let sql = format!("DELETE FROM transcriptions WHERE {EMPTY_TRANSCRIPTION_FILTER}");
+    with_retry(|| {
+        let mut conn = open_connection(path)?;
+        let tx = conn.transaction()?;
+        let removed = tx.execute(&sql, params![NO_SPEECH_PLACEHOLDER])?;
+        tx.commit()?;
+        Ok(removed)
+    })
+}
+
+/// Deletes every transcription in one transaction. The delete trigger keeps
+/// the search index in step; corrections are kept. With `vacuum`, the file
+/// is compacted afterwards to give the space back. Returns the number of
+/// rows removed.
+pub fn clear_all(path: &Path, vacuum: bool) -> Result<usize> {
+    let removed = with_retry(|| {
+        let mut conn = open_connection(path)?;
+        let tx = conn.transaction()?;
+        let removed = tx.execute("DELETE FROM transcriptions", [])?;
+        tx.commit()?;
+        Ok(removed)
+    })?;
+    if vacuum {
+        with_retry(|| open_connection(path)?.execute_batch("VACUUM"))?;
+    }
+    Ok(removed)
+}
+
+/// Deletes everything but the newest `max_rows` transcriptions, oldest id
+/// first. The delete trigger keeps the search index in step. Returns the
+/// number of rows removed.
+pub fn enforce_row_cap(path: &Path, max_rows: u32) -> Result<usize> {
+    with_retry(|| {
+        let mut conn = open_connection(path)?;
+        let tx = conn.transaction()?;
+        let removed = tx.execute(
+            "DELETE FROM transcriptions
+             WHERE id NOT IN (SELECT id FROM transcriptions ORDER BY id DESC LIMIT ?1)",
+            [max_rows],
+        )?;
+        tx.commit()?;
+        Ok(removed)
+    })
+}
+
+/// Deletes transcriptions created more than `older_than_days` days ago. The
+/// delete trigger keeps the search index in step. Returns the number of rows
+/// removed.
+pub fn prune(path: &Path, older_than_days: u32) -> Result<usize> {
+    let cutoff = format!("-{older_than_days} days");
+    with_retry(|| {
+        let mut conn = open_connection(path)?;
+        let tx = conn.transaction()?;
+        let removed = tx.execute(
+            "DELETE FROM transcriptions WHERE created_at < datetime('now', ?1)",
+            [&cutoff],
+        )?;
+        tx.commit()?;
+        Ok(removed)
+    })
+}
+
+/// Turns free text into an FTS5 query that matches rows containing every
+/// word. Each word is quoted, so operators and punctuation in user input are
+/// searched for literally instead of being parsed. `None` when nothing is
+/// left to search for.
+pub fn fts_match_query(raw: &str) -> Option<String> {
+    let terms: Vec<String> = raw
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+/// Transcriptions matching every word of `query`, best match first. User
+/// input goes through `fts_match_query`, so stray quotes or operators never
+/// surface as SQL errors. An empty query matches nothing.
+pub fn search(path: &Path, query: &str, limit: i64) -> Result<Vec<HistoryEntry>> {
+    let Some(match_query) = fts_match_query(query) else {
+        return Ok(Vec::new());
+    };
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.text, t.created_at, t.duration_ms, t.model, t.verbatim, t.source, t.language, t.avg_confidence
+             FROM transcriptions_fts
+             JOIN transcriptions t ON t.id = transcriptions_fts.rowid
+             WHERE transcriptions_fts MATCH ?1
+             ORDER BY transcriptions_fts.rank, t.id DESC
+             LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![match_query, limit], history_entry_from_row)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+    })
+}
+
+/// Calls `on_row` for every transcription matching `query`, newest first,
+/// without loading the whole result set. Not retried: `on_row` may already
+/// have written rows when a busy error arrives. Returns the number of rows.
+pub fn for_each_search_result<F>(path: &Path, query: &str, mut on_row: F) -> Result<usize>
+where
+    F: FnMut(HistoryEntry) -> Result<()>,
+{
+    let match_query = fts_match_query(query).ok_or_else(|| anyhow!("Search query is empty"))?;
+    let conn = open_connection(path)?;
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.text, t.created_at, t.duration_ms, t.model, t.verbatim, t.source, t.language, t.avg_confidence
+         FROM transcriptions_fts
+         JOIN transcriptions t ON t.id = transcriptions_fts.rowid
+         WHERE transcriptions_fts MATCH ?1
+         ORDER BY t.id DESC",
+    )?;
+    let mut rows = stmt.query([match_query])?;
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        on_row(history_entry_from_row(row)?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Calls `on_row` for every transcription, newest first, without loading
+/// the whole table. Not retried, for the same reason as
+/// `for_each_search_result`. Returns the number of rows.
+pub fn for_each_entry<F>(path: &Path, mut on_row: F) -> Result<usize>
+where
+    F: FnMut(HistoryEntry) -> Result<()>,
+{
+    let conn = open_connection(path)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, text, created_at, duration_ms, model, verbatim, source, language, avg_confidence
+         FROM transcriptions
+         ORDER BY id DESC",
+    )?;
+    let mut rows = stmt.query([])?;
+    let mut count = 0;
+    while let Some(row) = rows.next()? {
+        on_row(history_entry_from_row(row)?)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+pub fn get(path: &Path, id: i64) -> Result<Option<HistoryEntry>> {
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        conn.query_row(
+            "SELECT id, text, created_at, duration_ms, model, verbatim, source, language, avg_confidence
+             FROM transcriptions
+             WHERE id = ?1",
+            [id],
+            history_entry_from_row,
+        )
+        .optional()
+    })
+}
+
+/// The timed segments saved with transcription `id` as JSON. `None` when the
+/// row predates segment storage, holds no speech, or was edited.
+pub fn get_segments(path: &Path, id: i64) -> Result<Option<String>> {
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        conn.query_row(
+            "SELECT segments FROM transcriptions WHERE id = ?1",
+            [id],
+            |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()
+    })?
+    .ok_or_else(|| anyhow!("Transcription {id} not found"))
+}
+
+fn history_entry_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        text: row.get(1)?,
+        created_at: row.get(2)?,
+        duration_ms: row.get(3)?,
+        model: row.get(4)?,
+        verbatim: row.get(5)?,
+        source: TranscriptionSource::from_column(&row.get::<_, String>(6)?),
+        language: row.get(7)?,
+        avg_confidence: row.get(8)?,
+    })
+}
+
+/// Stores a correction for transcription `id` and returns the new record.
+pub fn insert_correction(path: &Path, id: i64, corrected_text: Option<&str>) -> Result<Correction> {
+    let correction_id = with_retry(|| {
+        let conn = open_connection(path)?;
+        let inserted = conn.execute(
+            "INSERT INTO corrections (transcription_id, original_text, corrected_text, model, capture_config)
+             SELECT id, text, ?2, model, capture_config FROM transcriptions WHERE id = ?1",
+            params![id, corrected_text],
+        )?;
+        Ok((inserted > 0).then(|| conn.last_insert_rowid()))
+    })?
+    .ok_or_else(|| anyhow!("Transcription {id} not found"))?;
+
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        conn.query_row(
+            &format!("{CORRECTION_COLUMNS} WHERE id = ?1"),
+            [correction_id],
+            correction_from_row,
+        )
+    })
+}
+
+/// Stored corrections, newest first.
+pub fn list_corrections(path: &Path, limit: i64) -> Result<Vec<Correction>> {
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        let mut stmt = conn.prepare(&format!("{CORRECTION_COLUMNS} ORDER BY id DESC LIMIT ?1"))?;
+        let rows = stmt.query_map([limit], correction_from_row)?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+    })
+}
+
+const CORRECTION_COLUMNS: &str =
+    "SELECT id, transcription_id, original_text, corrected_text, model, capture_config, created_at
+     FROM corrections";
+
+fn correction_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Correction> {
+    Ok(Correction {
+        id: row.get(0)?,
+        transcription_id: row.get(1)?,
+        original_text: row.get(2)?,
+        corrected_text: row.get(3)?,
+        model: row.get(4)?,
+        capture_config: row.get(5)?,
+        created_at: row.get(6)?,
+    })
+}
+
+/// Replaces the text of transcription `id` and returns the updated row. The
+/// update trigger keeps the search index in step. Segment timings no longer
+/// match edited text, so they are dropped.
+pub fn update_text(path: &Path, id: i64, new_text: &str) -> Result<HistoryEntry> {
+    let updated = with_retry(|| {
+        let conn = open_connection(path)?;
+        conn.execute(
+            "UPDATE transcriptions
+             SET segments = CASE WHEN text = ?2 THEN segments END, text = ?2
+             WHERE id = ?1",
+            params![id, new_text],
+        )
+    })?;
+    if updated == 0 {
+        return Err(anyhow!("Transcription {id} not found"));
+    }
+    get(path, id)?.ok_or_else(|| anyhow!("Transcription {id} not found"))
+}
+
+/// Replaces the transcript of row `id` with a new decode of the same audio:
+/// text, model, verbatim, language, segments, and confidence. Duration,
+/// source, and capture config describe the recording and stay as they were.
+pub fn replace_transcript(
+    path: &Path,
+    id: i64,
+    entry: &NewTranscription<'_>,
+) -> Result<HistoryEntry> {
+    if entry.text.trim().is_empty() || entry.text == NO_SPEECH_PLACEHOLDER {
+        bail!("Transcription {id} was not replaced with an empty transcript");
+    }
+    let updated = with_retry(|| {
+        let conn = open_connection(path)?;
+        conn.execute(
+            "UPDATE transcriptions
+             SET text = ?2, model = ?3, verbatim = ?4, language = ?5, segments = ?6, avg_confidence = ?7
+             WHERE id = ?1",
+            params![
+                id,
+                entry.text,
+                entry.model,
+                entry.verbatim,
+                entry.language,
+                entry.segments,
+                entry.avg_confidence
+            ],
+        )
+    })?;
+    if updated == 0 {
+        return Err(anyhow!("Transcription {id} not found"));
+    }
+    get(path, id)?.ok_or_else(|| anyhow!("Transcription {id} not found"))
+}
+
+/// Ids of every stored transcription.
+pub fn ids(path: &Path) -> Result<HashSet<i64>> {
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        let mut stmt = conn.prepare("SELECT id FROM transcriptions")?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<std::result::Result<HashSet<_>, _>>()
+    })
+}
+
 pub fn delete(path: &Path, id: i64) -> Result<()> {
     with_retry(|| {
         let conn = open_connection(path)?;
@@ -137,3 +767,650 @@ pub fn delete(path: &Path, id: i64) -> Result<()> {
         Ok(())
     })
 }
+
+/// Aggregates words, recorded time, and words per minute per day or week,
+/// newest bucket first. Weeks start on Monday.
+pub fn analytics(path: &Path, bucket: AnalyticsBucket, limit: i64) -> Result<Vec<AnalyticsPoint>> {
+    let bucket_expr = match bucket {
+        AnalyticsBucket::Day => "date(created_at)",
+        AnalyticsBucket::Week => "date(created_at, '-6 days', 'weekday 1')",
+    };
+    // Word counts assume whisper's single-spaced output: spaces + 1.
+    let sql = format!(
+        "SELECT {bucket_expr} AS bucket,
+                COUNT(*),
+                SUM(CASE WHEN trim(text) = '' THEN 0
+                         ELSE length(trim(text)) - length(replace(trim(text), ' ', '')) + 1 END),
+                SUM(COALESCE(duration_ms, 0))
+         FROM transcriptions
+         WHERE text != ?1
+         GROUP BY bucket
+         ORDER BY bucket DESC
+         LIMIT ?2"
+    );
+
+    with_retry(|| {
+        let conn = open_connection(path)?;
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params![NO_SPEECH_PLACEHOLDER, limit], |row| {
+            let words: i64 = row.get(2)?;
+            let duration_ms: i64 = row.get(3)?;
+            Ok(AnalyticsPoint {
+                bucket_start: row.get(0)?,
+                transcriptions: row.get(1)?,
+                words,
+                duration_ms,
+                words_per_minute: (duration_ms > 0)
+                    .then(|| words as f64 / (duration_ms as f64 / 60_000.0)),
+            })
+        })?;
+
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+    })
+}
+
+/// Rebuilds the FTS index from `transcriptions` and verifies it afterwards.
+pub fn reindex_search(path: &Path) -> Result<SearchIndexReport> {
+    with_retry(|| {
+        let mut conn = open_connection(path)?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO transcriptions_fts(transcriptions_fts) VALUES ('rebuild')",
+            [],
+        )?;
+
+        let transcription_rows: i64 =
+            tx.query_row("SELECT COUNT(*) FROM transcriptions", [], |row| row.get(0))?;
+        // The FTS table reads through to `transcriptions` for content, so count the
+        // docsize shadow table to see how many rows are actually indexed.
+        let indexed_rows: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM transcriptions_fts_docsize",
+            [],
+            |row| row.get(0),
+        )?;
+
+        let mut issues = Vec::new();
+        if let Err(err) = tx.execute(
+            "INSERT INTO transcriptions_fts(transcriptions_fts) VALUES ('integrity-check')",
+            [],
+        ) {
+            if is_retryable(&err) {
+                return Err(err);
+            }
+            issues.push(format!("FTS integrity check failed: {err}"));
+        }
+        if transcription_rows != indexed_rows {
+            issues.push(format!(
+                "Search index has {indexed_rows} rows but history has {transcription_rows}."
+            ));
+        }
+
+        tx.commit()?;
+        Ok(SearchIndexReport {
+            transcription_rows,
+            indexed_rows,
+            integrity_ok: issues.is_empty(),
+            issues,
+        })
+    })
+}
+
+/// Copies the live database to `dest` with SQLite's online backup API, which
+/// yields a consistent snapshot even while other connections keep writing.
+pub fn backup_to(path: &Path, dest: &Path) -> Result<()> {
+    let conn = open_connection(path)?;
+    conn.backup(DatabaseName::Main, dest, None)?;
+    Ok(())
+}
+
+/// Checks that `candidate` is an intact Murmur history database and migrates
+/// it to the current schema so it can be restored over the live one.
+pub fn validate_restore_candidate(candidate: &Path) -> Result<()> {
+    {
+        let conn = Connection::open(candidate)?;
+        let integrity: String = conn
+            .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+            .map_err(|_| anyhow!("Backup database is not a valid SQLite file"))?;
+        if integrity != "ok" {
+            bail!("Backup database is corrupt: {integrity}");
+        }
+        let has_history: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'transcriptions')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_history {
+            bail!("Backup database does not contain Murmur history");
+        }
+    }
+    init(candidate)
+}
+
+/// Replaces the live database's contents with `candidate` page by page through
+/// the backup API, so open connections see the restored data instead of a
+/// swapped-out file. Returns the number of restored transcriptions.
+pub fn restore_from(path: &Path, candidate: &Path) -> Result<i64> {
+    let mut conn = open_connection(path)?;
+    conn.restore(DatabaseName::Main, candidate, None::<fn(Progress)>)?;
+    let count = conn.query_row("SELECT COUNT(*) FROM transcriptions", [], |row| row.get(0))?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_db() -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "murmur-db-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("murmur.db");
+        init(&path).expect("init db");
+        path
+    }
+
+    #[test]
+    fn init_migrates_databases_without_verbatim_or_source_columns() {
+        let path = temp_db();
+        let conn = open_connection(&path).unwrap();
+        conn.execute_batch(
+            "DROP TABLE transcriptions;
+             CREATE TABLE transcriptions (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 text TEXT NOT NULL,
+                 created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                 duration_ms INTEGER,
+                 model TEXT NOT NULL
+             );
+             INSERT INTO transcriptions (text, duration_ms, model)
+             VALUES ('legacy', 1000, 'ggml-base.en.bin');",
+        )
+        .unwrap();
+        drop(conn);
+
+        init(&path).unwrap();
+        insert(
+            &path,
+            &NewTranscription {
+                text: "um, so",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                verbatim: true,
+                source: TranscriptionSource::File,
+                language: Some("de"),
+                avg_confidence: Some(0.82),
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        let entries = list(&path, 10).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].verbatim);
+        assert!(!entries[1].verbatim);
+        assert_eq!(entries[0].source, TranscriptionSource::File);
+        assert_eq!(entries[1].source, TranscriptionSource::Recording);
+        assert_eq!(entries[0].language.as_deref(), Some("de"));
+        assert_eq!(entries[1].language, None);
+        assert_eq!(entries[0].avg_confidence, Some(0.82));
+        assert_eq!(entries[1].avg_confidence, None);
+    }
+
+    #[test]
+    fn delete_empty_removes_placeholders_and_keeps_search_in_sync() {
+        let path = temp_db();
+        insert(
+            &path,
+            &NewTranscription {
+                text: NO_SPEECH_PLACEHOLDER,
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        insert(
+            &path,
+            &NewTranscription {
+                text: " \n\t ",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        for text in ["I", "keep me"] {
+            insert(
+                &path,
+                &NewTranscription {
+                    text,
+                    duration_ms: 1_000,
+                    model: "ggml-base.en.bin",
+                    ..NewTranscription::default()
+                },
+            )
+            .unwrap();
+        }
+
+        assert_eq!(list_empty(&path, 10).unwrap().len(), 2);
+        assert_eq!(delete_empty(&path).unwrap(), 2);
+
+        let remaining = list(&path, 10).unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].text, "keep me");
+        assert_eq!(remaining[1].text, "I");
+        // The delete trigger alone must have kept the index in step.
+        assert!(search(&path, "speech", 10).unwrap().is_empty());
+        assert_eq!(search(&path, "keep", 10).unwrap().len(), 1);
+        let report = reindex_search(&path).unwrap();
+        assert!(report.integrity_ok, "{:?}", report.issues);
+    }
+
+    #[test]
+    fn enforce_row_cap_keeps_the_newest_rows() {
+        let path = temp_db();
+        for text in ["first", "second", "third"] {
+            insert(
+                &path,
+                &NewTranscription {
+                    text,
+                    duration_ms: 1_000,
+                    model: "ggml-base.en.bin",
+                    ..NewTranscription::default()
+                },
+            )
+            .unwrap();
+        }
+
+        assert_eq!(enforce_row_cap(&path, 2).unwrap(), 1);
+        assert_eq!(enforce_row_cap(&path, 2).unwrap(), 0);
+        let remaining: Vec<String> = list(&path, 10)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.text)
+            .collect();
+        assert_eq!(remaining, ["third", "second"]);
+        assert!(reindex_search(&path).unwrap().integrity_ok);
+    }
+
+    #[test]
+    fn prune_deletes_only_rows_past_the_cutoff() {
+        let path = temp_db();
+        for text in ["old", "recent"] {
+            insert(
+                &path,
+                &NewTranscription {
+                    text,
+                    duration_ms: 1_000,
+                    model: "ggml-base.en.bin",
+                    ..NewTranscription::default()
+                },
+            )
+            .unwrap();
+        }
+        open_connection(&path)
+            .unwrap()
+            .execute(
+                "UPDATE transcriptions SET created_at = datetime('now', '-31 days') WHERE text = 'old'",
+                [],
+            )
+            .unwrap();
+
+        assert_eq!(prune(&path, 30).unwrap(), 1);
+        assert_eq!(prune(&path, 30).unwrap(), 0);
+        let remaining: Vec<String> = list(&path, 10)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.text)
+            .collect();
+        assert_eq!(remaining, ["recent"]);
+        assert!(search(&path, "old", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn replace_transcript_keeps_the_row_when_the_new_text_is_empty() {
+        let path = temp_db();
+        let id = insert(
+            &path,
+            &NewTranscription {
+                text: "original words",
+                duration_ms: 1_000,
+                model: "ggml-tiny.en.bin",
+                avg_confidence: Some(0.4),
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+
+        for text in [NO_SPEECH_PLACEHOLDER, "  "] {
+            let replaced = replace_transcript(
+                &path,
+                id,
+                &NewTranscription {
+                    text,
+                    model: "ggml-large-v3.bin",
+                    ..NewTranscription::default()
+                },
+            );
+            assert!(replaced.is_err());
+        }
+        let entry = get(&path, id).unwrap().unwrap();
+        assert_eq!(entry.text, "original words");
+        assert_eq!(entry.model, "ggml-tiny.en.bin");
+        assert_eq!(entry.avg_confidence, Some(0.4));
+
+        let replaced = replace_transcript(
+            &path,
+            id,
+            &NewTranscription {
+                text: "better words",
+                model: "ggml-large-v3.bin",
+                avg_confidence: Some(0.9),
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(replaced.text, "better words");
+        assert_eq!(replaced.duration_ms, Some(1_000));
+    }
+
+    #[test]
+    fn search_results_match_every_word_literally() {
+        let path = temp_db();
+        for text in [
+            "Pay the invoice on Friday",
+            "Invoice OR receipt?",
+            "Lunch on Friday",
+        ] {
+            insert(
+                &path,
+                &NewTranscription {
+                    text,
+                    duration_ms: 1_000,
+                    model: "ggml-base.en.bin",
+                    ..NewTranscription::default()
+                },
+            )
+            .unwrap();
+        }
+
+        let search = |query: &str| {
+            let mut texts = Vec::new();
+            for_each_search_result(&path, query, |entry| {
+                texts.push(entry.text);
+                Ok(())
+            })
+            .map(|_| texts)
+        };
+        assert_eq!(
+            search("invoice").unwrap(),
+            ["Invoice OR receipt?", "Pay the invoice on Friday"]
+        );
+        assert_eq!(
+            search("friday invoice").unwrap(),
+            ["Pay the invoice on Friday"]
+        );
+        assert_eq!(search("OR \"receipt").unwrap(), ["Invoice OR receipt?"]);
+        assert!(search("   ").is_err());
+    }
+
+    #[test]
+    fn search_ranks_best_match_first_and_tolerates_fts_syntax() {
+        let path = temp_db();
+        for text in [
+            "Invoice for the garden work, and the invoice for the roof",
+            "Call the bank about the new card and the overdue invoice",
+        ] {
+            insert(
+                &path,
+                &NewTranscription {
+                    text,
+                    duration_ms: 1_000,
+                    model: "ggml-base.en.bin",
+                    ..NewTranscription::default()
+                },
+            )
+            .unwrap();
+        }
+
+        let results = search(&path, "invoice", 10).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].text.starts_with("Invoice for the garden"));
+        assert_eq!(search(&path, "invoice", 1).unwrap().len(), 1);
+        assert!(search(&path, "NEAR( \"invoice* AND", 10)
+            .unwrap()
+            .is_empty());
+        assert!(search(&path, "  ", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn update_text_replaces_the_row_and_its_search_entry() {
+        let path = temp_db();
+        let id = insert(
+            &path,
+            &NewTranscription {
+                text: "Meet at the peer",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                segments: Some(r#"[{"start_ms":0,"end_ms":1000,"text":"Meet at the peer"}]"#),
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+
+        update_text(&path, id, "Meet at the peer").unwrap();
+        assert!(get_segments(&path, id).unwrap().is_some());
+        let entry = update_text(&path, id, "Meet at the pier").unwrap();
+        assert_eq!(entry.text, "Meet at the pier");
+        assert_eq!(get_segments(&path, id).unwrap(), None);
+        assert!(search(&path, "peer", 10).unwrap().is_empty());
+        assert_eq!(search(&path, "pier", 10).unwrap().len(), 1);
+        assert!(update_text(&path, id + 1, "missing").is_err());
+    }
+
+    #[test]
+    fn clear_all_empties_history_and_search() {
+        let path = temp_db();
+        for text in ["First note", "Second note"] {
+            insert(
+                &path,
+                &NewTranscription {
+                    text,
+                    duration_ms: 1_000,
+                    model: "ggml-base.en.bin",
+                    ..NewTranscription::default()
+                },
+            )
+            .unwrap();
+        }
+
+        assert_eq!(clear_all(&path, true).unwrap(), 2);
+        assert!(list(&path, 10).unwrap().is_empty());
+        assert!(search(&path, "note", 10).unwrap().is_empty());
+        assert_eq!(clear_all(&path, false).unwrap(), 0);
+    }
+
+    #[test]
+    fn corrections_keep_the_original_after_the_row_is_deleted() {
+        let path = temp_db();
+        let id = insert(
+            &path,
+            &NewTranscription {
+                text: "their going to the meeting",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                source: TranscriptionSource::Hotkey,
+                capture_config: Some("Built-in Microphone (48000 Hz, 1 ch)"),
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+
+        let correction =
+            insert_correction(&path, id, Some("they're going to the meeting")).unwrap();
+        assert_eq!(correction.transcription_id, Some(id));
+        assert_eq!(correction.original_text, "their going to the meeting");
+        assert_eq!(
+            correction.capture_config.as_deref(),
+            Some("Built-in Microphone (48000 Hz, 1 ch)")
+        );
+        assert!(insert_correction(&path, id + 1, None).is_err());
+
+        delete(&path, id).unwrap();
+        let corrections = list_corrections(&path, 10).unwrap();
+        assert_eq!(corrections.len(), 1);
+        assert_eq!(corrections[0].transcription_id, None);
+        assert_eq!(
+            corrections[0].corrected_text.as_deref(),
+            Some("they're going to the meeting")
+        );
+    }
+
+    #[test]
+    fn list_summaries_cuts_previews_by_character() {
+        let path = temp_db();
+        insert(
+            &path,
+            &NewTranscription {
+                text: "naïve café ünïcode",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+
+        let summaries = list_summaries(&path, 10, 7).unwrap();
+        assert_eq!(summaries[0].preview, "naïve…");
+        assert_eq!(summaries[0].text_chars, 18);
+    }
+
+    #[test]
+    fn backup_round_trips_into_live_database() {
+        let source = temp_db();
+        insert(
+            &source,
+            &NewTranscription {
+                text: "from the old machine",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                verbatim: true,
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        let snapshot = source.with_file_name("snapshot.db");
+        backup_to(&source, &snapshot).unwrap();
+
+        let live = temp_db();
+        insert(
+            &live,
+            &NewTranscription {
+                text: "replaced",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        validate_restore_candidate(&snapshot).unwrap();
+        assert_eq!(restore_from(&live, &snapshot).unwrap(), 1);
+
+        let restored = list(&live, 10).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].text, "from the old machine");
+        assert!(restored[0].verbatim);
+        assert!(reindex_search(&live).unwrap().integrity_ok);
+    }
+
+    #[test]
+    fn validate_restore_candidate_rejects_foreign_files() {
+        let path = temp_db().with_file_name("not-a-db.db");
+        fs::write(&path, b"definitely not sqlite").unwrap();
+        assert!(validate_restore_candidate(&path).is_err());
+    }
+
+    #[test]
+    fn reindex_search_restores_missing_index_rows() {
+        let path = temp_db();
+        insert(
+            &path,
+            &NewTranscription {
+                text: "hello world",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        insert(
+            &path,
+            &NewTranscription {
+                text: "second note",
+                duration_ms: 1_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+
+        let conn = open_connection(&path).unwrap();
+        conn.execute(
+            "INSERT INTO transcriptions_fts(transcriptions_fts) VALUES ('delete-all')",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let report = reindex_search(&path).unwrap();
+        assert_eq!(report.transcription_rows, 2);
+        assert_eq!(report.indexed_rows, 2);
+        assert!(report.integrity_ok, "{:?}", report.issues);
+    }
+
+    #[test]
+    fn analytics_groups_words_by_day() {
+        let path = temp_db();
+        insert(
+            &path,
+            &NewTranscription {
+                text: "one two three four",
+                duration_ms: 30_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        insert(
+            &path,
+            &NewTranscription {
+                text: "five six",
+                duration_ms: 30_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+        insert(
+            &path,
+            &NewTranscription {
+                text: NO_SPEECH_PLACEHOLDER,
+                duration_ms: 5_000,
+                model: "ggml-base.en.bin",
+                ..NewTranscription::default()
+            },
+        )
+        .unwrap();
+
+        let points = analytics(&path, AnalyticsBucket::Day, 30).unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].transcriptions, 2);
+        assert_eq!(points[0].words, 6);
+        assert_eq!(points[0].words_per_minute, Some(6.0));
+    }
+}
@@ -0,0 +1,340 @@
+use crate::db::HistoryEntry;
+use crate::whisper::Segment;
+use anyhow::Result;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+const CSV_HEADER: &str = "id,created_at,duration_ms,model,source,verbatim,text";
+const MARKDOWN_HEADER: &str = "| Time | Model | Duration | Text |\n| --- | --- | --- | --- |";
+
+/// Writes history entries one at a time, so exports never hold the whole
+/// result set in memory. Call `entry` for each row, then `finish`.
+pub struct ExportWriter<W: Write> {
+    out: W,
+    format: ExportFormat,
+    written: usize,
+}
+
+impl<W: Write> ExportWriter<W> {
+    pub fn new(mut out: W, format: ExportFormat) -> Result<Self> {
+        match format {
+            ExportFormat::Json => out.write_all(b"[")?,
+            ExportFormat::Csv => writeln!(out, "{CSV_HEADER}")?,
+            ExportFormat::Markdown => {
+                writeln!(out, "# Murmur transcriptions\n\n{MARKDOWN_HEADER}")?
+            }
+        }
+        Ok(Self {
+            out,
+            format,
+            written: 0,
+        })
+    }
+
+    pub fn entry(&mut self, entry: &HistoryEntry) -> Result<()> {
+        match self.format {
+            ExportFormat::Json => {
+                let separator = if self.written == 0 { "\n  " } else { ",\n  " };
+                self.out.write_all(separator.as_bytes())?;
+                serde_json::to_writer(&mut self.out, entry)?;
+            }
+            ExportFormat::Csv => writeln!(
+                self.out,
+                "{},{},{},{},{},{},{}",
+                entry.id,
+                csv_field(&entry.created_at),
+                entry
+                    .duration_ms
+                    .map(|ms| ms.to_string())
+                    .unwrap_or_default(),
+                csv_field(&entry.model),
+                entry.source.as_str(),
+                entry.verbatim,
+                csv_field(&entry.text)
+            )?,
+            ExportFormat::Markdown => writeln!(
+                self.out,
+                "| {} | {} | {} | {} |",
+                entry.created_at,
+                markdown_cell(&entry.model),
+                entry
+                    .duration_ms
+                    .map(|ms| format!("{:.1} s", ms as f64 / 1000.0))
+                    .unwrap_or_default(),
+                markdown_cell(entry.text.trim())
+            )?,
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Closes the document and returns the number of entries written.
+    pub fn finish(mut self) -> Result<usize> {
+        if self.format == ExportFormat::Json {
+            let close = if self.written == 0 { "]\n" } else { "\n]\n" };
+            self.out.write_all(close.as_bytes())?;
+        }
+        self.out.flush()?;
+        Ok(self.written)
+    }
+}
+
+/// Streams rows into `dest` through a `.part` file that replaces `dest` only
+/// once complete, so a failed export never leaves a truncated file behind.
+/// `rows` feeds entries to the writer. Returns the number of rows written.
+pub fn write_file<F>(dest: &Path, format: ExportFormat, rows: F) -> Result<usize>
+where
+    F: FnOnce(&mut ExportWriter<BufWriter<File>>) -> Result<()>,
+{
+    let partial = dest.with_extension(format!("{}.part", format.extension()));
+    let exported = (|| -> Result<usize> {
+        let mut writer = ExportWriter::new(BufWriter::new(File::create(&partial)?), format)?;
+        rows(&mut writer)?;
+        writer.finish()
+    })();
+    match exported {
+        Ok(count) => {
+            fs::rename(&partial, dest)?;
+            Ok(count)
+        }
+        Err(err) => {
+            let _ = fs::remove_file(&partial);
+            Err(err)
+        }
+    }
+}
+
+/// Escapes pipes and folds line breaks so text stays inside one table cell.
+fn markdown_cell(raw: &str) -> String {
+    raw.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+impl SubtitleFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SubtitleFormat::Srt => "srt",
+            SubtitleFormat::Vtt => "vtt",
+        }
+    }
+}
+
+/// Caption lines are wrapped at this many characters, the usual broadcast
+/// limit that stays readable on phones.
+const SUBTITLE_LINE_CHARS: usize = 42;
+/// A cue holds at most two lines; longer segments become several cues.
+const SUBTITLE_CUE_LINES: usize = 2;
+
+/// Renders timed segments as an SRT or WebVTT document.
+///
+/// Segments longer than two 42-character lines are split into several cues
+/// at word boundaries, and the segment's time is shared between them in
+/// proportion to their length. Every cue starts where the previous one
+/// ended or later, so overlapping whisper bounds never produce cues that
+/// run backwards.
+pub fn subtitles(segments: &[Segment], format: SubtitleFormat) -> String {
+    let mut out = String::new();
+    if format == SubtitleFormat::Vtt {
+        out.push_str("WEBVTT\n\n");
+    }
+
+    let mut index = 0;
+    let mut last_end = 0_i64;
+    for segment in segments {
+        let cues = wrap_cues(segment.text.trim());
+        let total_chars: usize = cues.iter().map(|lines| cue_chars(lines)).sum();
+        let start = segment.start_ms.max(last_end);
+        let span = (segment.end_ms - start).max(0);
+
+        let mut chars_before = 0;
+        for lines in &cues {
+            let cue_start = start + span * chars_before as i64 / total_chars.max(1) as i64;
+            chars_before += cue_chars(lines);
+            let cue_end = start + span * chars_before as i64 / total_chars.max(1) as i64;
+            // A zero-length cue is dropped by most players; give it 1 ms.
+            let cue_end = cue_end.max(cue_start + 1);
+            last_end = cue_end;
+
+            index += 1;
+            if format == SubtitleFormat::Srt {
+                out.push_str(&format!("{index}\n"));
+            }
+            out.push_str(&format!(
+                "{} --> {}\n{}\n\n",
+                subtitle_timecode(cue_start, format),
+                subtitle_timecode(cue_end, format),
+                lines.join("\n")
+            ));
+        }
+    }
+    out
+}
+
+/// `HH:MM:SS,mmm` for SRT and `HH:MM:SS.mmm` for WebVTT. Whisper times are
+/// whole milliseconds already, so nothing is rounded here.
+fn subtitle_timecode(ms: i64, format: SubtitleFormat) -> String {
+    let ms = ms.max(0);
+    let separator = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::Vtt => '.',
+    };
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1_000 % 60,
+        ms % 1_000
+    )
+}
+
+/// Greedy word wrap into lines of at most `SUBTITLE_LINE_CHARS`, grouped
+/// into cues of `SUBTITLE_CUE_LINES`. A single longer word keeps its own line.
+fn wrap_cues(text: &str) -> Vec<Vec<String>> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line)
+                if line.chars().count() + 1 + word.chars().count() <= SUBTITLE_LINE_CHARS =>
+            {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+        .chunks(SUBTITLE_CUE_LINES)
+        .map(<[String]>::to_vec)
+        .collect()
+}
+
+fn cue_chars(lines: &[String]) -> usize {
+    lines.iter().map(|line| line.chars().count()).sum()
+}
+
+/// Quotes a CSV field when it holds a delimiter, quote, or line break.
+fn csv_field(raw: &str) -> String {
+    if raw.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", raw.replace('"', "\"\""))
+    } else {
+        raw.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::TranscriptionSource;
+
+    #[test]
+    fn subtitles_split_long_segments_into_ordered_cues() {
+        let segment = |start_ms, end_ms, text: &str| Segment {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+            avg_prob: 0.9,
+        };
+        let segments = [
+            segment(0, 1_500, " Hello there."),
+            segment(
+                1_400,
+                9_400,
+                "This sentence is long enough that it will not fit on two caption lines of forty two characters each.",
+            ),
+        ];
+
+        assert_eq!(
+            subtitles(&segments, SubtitleFormat::Srt),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello there.\n\n\
+             2\n00:00:01,500 --> 00:00:08,110\nThis sentence is long enough that it will\nnot fit on two caption lines of forty two\n\n\
+             3\n00:00:08,110 --> 00:00:09,400\ncharacters each.\n\n"
+        );
+        assert!(subtitles(&segments[..1], SubtitleFormat::Vtt)
+            .starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:01.500\nHello there.\n"));
+        assert_eq!(
+            subtitle_timecode(3_723_004, SubtitleFormat::Srt),
+            "01:02:03,004"
+        );
+    }
+
+    #[test]
+    fn csv_export_quotes_fields_that_need_it() {
+        let entry = HistoryEntry {
+            id: 7,
+            text: "Send the \"final\" invoice,\nthen call.".to_string(),
+            created_at: "2024-05-01 09:30:00".to_string(),
+            duration_ms: Some(4_200),
+            model: "ggml-base.en.bin".to_string(),
+            verbatim: false,
+            source: TranscriptionSource::Hotkey,
+            language: Some("en".to_string()),
+            avg_confidence: None,
+        };
+        let mut out = Vec::new();
+        let mut writer = ExportWriter::new(&mut out, ExportFormat::Csv).unwrap();
+        writer.entry(&entry).unwrap();
+        assert_eq!(writer.finish().unwrap(), 1);
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{CSV_HEADER}\n7,2024-05-01 09:30:00,4200,ggml-base.en.bin,hotkey,false,\"Send the \"\"final\"\" invoice,\nthen call.\"\n"
+            )
+        );
+    }
+
+    #[test]
+    fn markdown_export_keeps_each_entry_in_one_table_row() {
+        let entry = HistoryEntry {
+            id: 3,
+            text: "Options: a|b\nor c".to_string(),
+            created_at: "2024-05-01 09:30:00".to_string(),
+            duration_ms: Some(2_500),
+            model: "ggml-base.en.bin".to_string(),
+            verbatim: false,
+            source: TranscriptionSource::Recording,
+            language: None,
+            avg_confidence: None,
+        };
+        let mut out = Vec::new();
+        let mut writer = ExportWriter::new(&mut out, ExportFormat::Markdown).unwrap();
+        writer.entry(&entry).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "# Murmur transcriptions\n\n{MARKDOWN_HEADER}\n| 2024-05-01 09:30:00 | ggml-base.en.bin | 2.5 s | Options: a\\|b<br>or c |\n"
+            )
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The active log file is rotated once it grows past this size.
+pub const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// Rotated files kept next to the active log (`murmur.log.1`, `murmur.log.2`).
+const KEPT_ROTATIONS: u32 = 2;
+const LOG_FILE_NAME: &str = "murmur.log";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+        }
+    }
+}
+
+struct Logger {
+    path: PathBuf,
+    level: LogLevel,
+}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+/// Starts writing to `<dir>/murmur.log` and routes panics there as well.
+/// Before this runs, messages only go to stderr.
+pub fn init(dir: &Path, level: LogLevel) -> Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let path = dir.join(LOG_FILE_NAME);
+    if let Ok(mut guard) = LOGGER.lock() {
+        *guard = Some(Logger {
+            path: path.clone(),
+            level,
+        });
+    }
+
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        error(format!("panic: {info}"));
+        previous_hook(info);
+    }));
+
+    Ok(path)
+}
+
+pub fn set_level(level: LogLevel) {
+    if let Ok(mut guard) = LOGGER.lock() {
+        if let Some(logger) = guard.as_mut() {
+            logger.level = level;
+        }
+    }
+}
+
+pub fn path() -> Option<PathBuf> {
+    LOGGER
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|logger| logger.path.clone()))
+}
+
+pub fn error(message: impl AsRef<str>) {
+    write(LogLevel::Error, message.as_ref());
+}
+
+pub fn warn(message: impl AsRef<str>) {
+    write(LogLevel::Warn, message.as_ref());
+}
+
+pub fn info(message: impl AsRef<str>) {
+    write(LogLevel::Info, message.as_ref());
+}
+
+fn write(level: LogLevel, message: &str) {
+    eprintln!("{}: {message}", level.label().to_lowercase());
+
+    // A poisoned lock here would mean a panic while logging; dropping the line
+    // beats panicking again inside the panic hook.
+    let Ok(guard) = LOGGER.lock() else {
+        return;
+    };
+    let Some(logger) = guard.as_ref() else {
+        return;
+    };
+    if level > logger.level {
+        return;
+    }
+
+    let line = format!("{} {:<5} {message}\n", utc_timestamp(), level.label());
+    let _ = append_line(&logger.path, &line, MAX_LOG_BYTES);
+}
+
+fn append_line(path: &Path, line: &str, max_bytes: u64) -> std::io::Result<()> {
+    let current_len = fs::metadata(path).map(|meta| meta.len()).unwrap_or(0);
+    if current_len > 0 && current_len + line.len() as u64 > max_bytes {
+        rotate(path);
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(line.as_bytes())
+}
+
+fn rotate(path: &Path) {
+    let rotated = |index: u32| PathBuf::from(format!("{}.{index}", path.display()));
+    for index in (1..KEPT_ROTATIONS).rev() {
+        let _ = fs::rename(rotated(index), rotated(index + 1));
+    }
+    let _ = fs::rename(path, rotated(1));
+}
+
+/// `YYYY-MM-DD HH:MM:SS` in UTC, without pulling in a date crate.
+fn utc_timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // Civil-from-days (Howard Hinnant), valid for any date after 1970.
+    let z = days as i64 + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn append_line_rotates_past_max_size() {
+        let dir = std::env::temp_dir().join(format!("murmur-log-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOG_FILE_NAME);
+
+        for index in 0..4 {
+            append_line(&path, &format!("line {index}\n"), 12).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 3\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("murmur.log.1")).unwrap(),
+            "line 2\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("murmur.log.2")).unwrap(),
+            "line 1\n"
+        );
+        assert!(!dir.join("murmur.log.3").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,19 +1,33 @@
 mod audio;
+mod backup;
 mod commands;
 mod db;
+mod export;
+mod logging;
 mod models;
+mod paste;
+mod postprocess;
+mod power;
 mod settings;
 mod state;
 mod whisper;
 
 use std::fs;
+use std::sync::mpsc;
+use std::time::Duration;
 
+use tauri::dpi::{PhysicalPosition, PhysicalSize};
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
-use tauri::{ActivationPolicy, AppHandle, Manager, RunEvent, WindowEvent};
+use tauri::{
+    ActivationPolicy, AppHandle, DragDropEvent, Emitter, Manager, RunEvent, WebviewWindow,
+    WindowEvent,
+};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
 pub(crate) const TRAY_ID: &str = "murmur-tray";
+/// Quiet period after the last move/resize before the window geometry is saved.
+const WINDOW_GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(500);
 
 fn main() {
     let app = tauri::Builder::default()
@@ -23,17 +37,49 @@ fn main() {
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(|app, _shortcut, event| {
-                    if event.state != ShortcutState::Pressed {
+                .with_handler(|app, shortcut, event| {
+                    let pressed = event.state == ShortcutState::Pressed;
+                    let state = app.state::<state::SharedState>().inner().clone();
+                    let is_window_toggle = state
+                        .window_toggle_hotkey()
+                        .and_then(|raw| raw.parse::<Shortcut>().ok())
+                        .is_some_and(|toggle| toggle.id() == shortcut.id());
+                    if is_window_toggle {
+                        if pressed {
+                            toggle_window(app);
+                        }
+                        return;
+                    }
+
+                    let push_to_talk = state.hotkey_mode() == settings::HotkeyMode::PushToTalk;
+                    if !pressed && !push_to_talk {
                         return;
                     }
+                    if push_to_talk {
+                        // Key repeat sends more presses while held. The held flag
+                        // is set here, in event order, because the spawned tasks
+                        // below can run out of order.
+                        if pressed && state.push_to_talk_held() {
+                            return;
+                        }
+                        state.set_push_to_talk_held(pressed);
+                    }
 
                     let app_handle = app.clone();
-                    let state = app.state::<state::SharedState>().inner().clone();
                     tauri::async_runtime::spawn(async move {
-                        if let Err(err) =
-                            commands::toggle_recording_impl(app_handle.clone(), state).await
-                        {
+                        let result = if push_to_talk {
+                            commands::push_to_talk_impl(app_handle.clone(), state, pressed).await
+                        } else {
+                            let mode = state.transcription_mode();
+                            commands::toggle_recording_impl(
+                                app_handle.clone(),
+                                state,
+                                db::TranscriptionSource::Hotkey,
+                                mode,
+                            )
+                            .await
+                        };
+                        if let Err(err) = result {
                             commands::emit_error(&app_handle, err.to_string());
                         }
                     });
@@ -50,39 +96,77 @@ fn main() {
             let models_dir = app_data.join("models");
             fs::create_dir_all(&models_dir)?;
 
-            let db_path = app_data.join("murmur.db");
-            db::init(&db_path)?;
-
             let settings_path = app_data.join("settings.json");
             let settings = settings::load(&settings_path);
+
+            if let Err(err) = logging::init(&app_data.join("logs"), settings.log_level) {
+                eprintln!("failed to initialize file logging: {err}");
+            }
+            logging::info(format!("Murmur {} starting", env!("CARGO_PKG_VERSION")));
+
+            db::configure(settings.db_tuning());
+            let db_path = app_data.join("murmur.db");
+            db::init(&db_path)?;
+            if let Err(err) = models::ensure_models_dir_writable(&models_dir) {
+                logging::warn(err.to_string());
+            }
+            scan_partial_downloads(&models_dir);
             let active_model = settings
                 .active_model
                 .clone()
                 .filter(|file_name| models_dir.join(file_name).exists())
                 .unwrap_or_else(|| models::pick_default_model(&models_dir));
-            let hotkey = settings.hotkey.clone();
-            let auto_copy = settings.auto_copy;
+            let input_device = restore_input_device(&settings_path, &settings);
             app.manage(state::SharedState::new(
                 db_path,
                 models_dir,
                 settings_path,
                 active_model,
-                hotkey.clone(),
-                auto_copy,
+                input_device,
+                &settings,
             ));
 
-            register_hotkey(app, &hotkey)?;
+            register_hotkey(
+                app,
+                &settings.hotkey,
+                settings.window_toggle_hotkey.as_deref(),
+            )?;
             setup_tray(app)?;
 
+            let app_for_devices = app.handle().clone();
+            audio::spawn_device_watcher(move |status| {
+                let _ = app_for_devices.emit("audio-devices-changed", status);
+            });
+
+            commands::apply_history_retention(app.state::<state::SharedState>().inner());
+            commands::spawn_heartbeat(app.handle().clone());
+            commands::spawn_model_warm_up(app.handle().clone(), commands::STARTUP_WARM_UP_DELAY);
+
+            let app_for_power = app.handle().clone();
+            power::spawn_watcher(move |source| {
+                let state = app_for_power.state::<state::SharedState>().inner().clone();
+                commands::apply_power_model(&app_for_power, &state, source)
+            });
+
             if let Some(main_window) = app.get_webview_window("main") {
+                restore_window_geometry(&main_window);
                 let window_for_close = main_window.clone();
-                main_window.on_window_event(move |event| {
-                    if let WindowEvent::CloseRequested { api, .. } = event {
+                let geometry_changed = spawn_window_geometry_saver(main_window.clone());
+                let app_for_drop = app.handle().clone();
+                main_window.on_window_event(move |event| match event {
+                    WindowEvent::CloseRequested { api, .. } => {
                         api.prevent_close();
                         #[cfg(target_os = "macos")]
                         let _ = window_for_close.set_visible_on_all_workspaces(false);
                         let _ = window_for_close.hide();
                     }
+                    WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+                        let _ = geometry_changed.send(());
+                    }
+                    WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => {
+                        commands::transcribe_dropped_files(app_for_drop.clone(), paths.clone());
+                    }
+                    _ => {}
                 });
                 let _ = main_window.hide();
             }
@@ -93,18 +177,153 @@ fn main() {
             commands::get_app_state,
             commands::start_recording,
             commands::stop_recording,
+            commands::pause_recording,
+            commands::resume_recording,
+            commands::retry_last_transcription,
+            commands::start_quick_note,
+            commands::start_continuous_dictation,
+            commands::stop_continuous_dictation,
+            commands::get_continuous_dictation,
+            commands::get_quick_note_seconds,
+            commands::set_quick_note_seconds,
+            commands::get_max_recording_seconds,
+            commands::set_max_recording_seconds,
             commands::toggle_recording,
             commands::cancel_transcription,
+            commands::force_reset,
+            commands::ping,
+            commands::recover_state,
+            commands::transcribe_file,
+            commands::transcribe_clipboard_audio,
+            commands::preview_preprocessing,
+            commands::profile_pipeline,
+            commands::transcribe_segments,
             commands::get_history,
+            commands::list_history_summaries,
+            commands::get_transcription,
+            commands::search_history,
+            commands::update_transcription,
+            commands::retranscribe,
             commands::delete_transcription,
+            commands::export_search_results,
+            commands::export_history,
+            commands::export_subtitles,
+            commands::submit_correction,
+            commands::list_corrections,
+            commands::list_empty_transcriptions,
+            commands::delete_empty_transcriptions,
+            commands::clear_history,
+            commands::get_max_history_rows,
+            commands::set_max_history_rows,
+            commands::get_history_retention_days,
+            commands::set_history_retention_days,
+            commands::prune_history,
+            commands::enforce_history_cap,
+            commands::reindex_search,
+            commands::dictation_analytics,
             commands::copy_text,
+            commands::copy_formatted,
             commands::list_models,
+            commands::check_models_dir,
+            commands::rename_model,
+            commands::delete_model,
             commands::set_active_model,
+            commands::cancel_model_download,
+            commands::list_resumable_downloads,
+            commands::resume_model_download,
+            commands::discard_model_download,
+            commands::get_model_mirror_prefix,
+            commands::set_model_mirror_prefix,
+            commands::get_power_models,
+            commands::set_power_models,
+            commands::get_live_output,
+            commands::set_live_output,
+            commands::get_output_target,
+            commands::set_output_target,
             commands::get_hotkey,
             commands::set_hotkey,
+            commands::get_hotkey_mode,
+            commands::set_hotkey_mode,
+            commands::resync_hotkey,
+            commands::canonicalize_hotkey_preview,
+            commands::get_window_toggle_hotkey,
+            commands::set_window_toggle_hotkey,
             commands::get_auto_copy,
             commands::set_auto_copy,
+            commands::get_auto_paste,
+            commands::set_auto_paste,
+            commands::get_stream_typing,
+            commands::set_stream_typing,
+            commands::get_paste_mode,
+            commands::set_paste_mode,
+            commands::get_keep_audio,
+            commands::set_keep_audio,
             commands::get_audio_input_status,
+            commands::microphone_permission_status,
+            commands::request_microphone_permission,
+            commands::accessibility_permission_status,
+            commands::request_accessibility_permission,
+            commands::estimate_transcription_time,
+            commands::system_info,
+            commands::get_low_priority,
+            commands::set_low_priority,
+            commands::get_number_format,
+            commands::set_number_format,
+            commands::get_locale,
+            commands::set_locale,
+            commands::get_timestamp_output,
+            commands::set_timestamp_output,
+            commands::get_transcription_mode,
+            commands::set_transcription_mode,
+            commands::get_short_recording,
+            commands::set_short_recording,
+            commands::get_capture_warmup_ms,
+            commands::set_capture_warmup_ms,
+            commands::list_input_devices,
+            commands::list_audio_devices,
+            commands::get_input_device,
+            commands::set_input_device,
+            commands::get_dual_mic,
+            commands::set_dual_mic,
+            commands::get_speaker_turns,
+            commands::set_speaker_turns,
+            commands::get_pause_trim,
+            commands::set_pause_trim,
+            commands::get_auto_stop,
+            commands::set_auto_stop,
+            commands::get_activity_thresholds,
+            commands::set_activity_thresholds,
+            commands::get_capture_buffer_size,
+            commands::set_capture_buffer_size,
+            commands::get_latency_priority,
+            commands::set_latency_priority,
+            commands::get_language,
+            commands::set_language,
+            commands::get_initial_prompt,
+            commands::set_initial_prompt,
+            commands::get_decode_options,
+            commands::set_decode_options,
+            commands::get_use_gpu,
+            commands::set_use_gpu,
+            commands::get_gpu_backend,
+            commands::get_preload_model,
+            commands::set_preload_model,
+            commands::get_deterministic,
+            commands::set_deterministic,
+            commands::get_debug_mode,
+            commands::set_debug_mode,
+            commands::export_settings,
+            commands::import_settings,
+            commands::export_backup,
+            commands::import_backup,
+            commands::reset_window_position,
+            commands::get_log_path,
+            commands::open_log,
+            commands::open_transcription_audio,
+            commands::get_log_level,
+            commands::set_log_level,
+            commands::get_db_tuning,
+            commands::set_db_tuning,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
@@ -114,12 +333,77 @@ fn main() {
         if let RunEvent::Reopen { .. } = event {
             show_window(app_handle);
         }
+        if let RunEvent::Exit = event {
+            whisper::clear_context_cache();
+        }
     });
 }
 
-fn register_hotkey(app: &tauri::App, hotkey: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// Cleans up `.part` files that can never be resumed and logs the ones that
+/// can; the models view lists those via `list_resumable_downloads`.
+fn scan_partial_downloads(models_dir: &std::path::Path) {
+    match models::remove_stale_partials(models_dir) {
+        Ok(removed) if !removed.is_empty() => {
+            logging::info(format!(
+                "removed stale partial downloads: {}",
+                removed.join(", ")
+            ));
+        }
+        Ok(_) => {}
+        Err(err) => logging::warn(format!("could not clean partial downloads: {err}")),
+    }
+    if let Ok(partials) = models::partial_downloads(models_dir) {
+        for partial in partials {
+            logging::info(format!(
+                "resumable download: {} ({} bytes)",
+                partial.file_name, partial.bytes
+            ));
+        }
+    }
+}
+
+/// The saved microphone, if it is still connected. A device the OS renamed no
+/// longer matches and is treated as gone: the setting is cleared so
+/// recordings use the default input.
+fn restore_input_device(
+    settings_path: &std::path::Path,
+    settings: &settings::AppSettings,
+) -> Option<String> {
+    let saved = settings.input_device.clone()?;
+    if audio::input_device_names().contains(&saved) {
+        return Some(saved);
+    }
+    logging::warn(format!(
+        "saved input device '{saved}' is not connected; using the default input"
+    ));
+    if let Err(err) = settings::save_input_device(settings_path, None) {
+        logging::warn(format!("failed to clear saved input device: {err}"));
+    }
+    None
+}
+
+fn register_hotkey(
+    app: &tauri::App,
+    hotkey: &str,
+    window_toggle_hotkey: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
     let shortcut: Shortcut = hotkey.parse()?;
     app.global_shortcut().register(shortcut)?;
+
+    // The window shortcut is a convenience; failing to claim it should not
+    // block startup the way a missing recording hotkey would.
+    if let Some(raw) = window_toggle_hotkey {
+        match raw.parse::<Shortcut>() {
+            Ok(toggle) => {
+                if let Err(err) = app.global_shortcut().register(toggle) {
+                    logging::warn(format!(
+                        "failed to register window toggle hotkey {raw}: {err}"
+                    ));
+                }
+            }
+            Err(err) => logging::warn(format!("invalid window toggle hotkey {raw}: {err}")),
+        }
+    }
     Ok(())
 }
 
@@ -177,11 +461,88 @@ fn show_window(app: &AppHandle) {
         #[cfg(target_os = "macos")]
         let _ = window.set_visible_on_all_workspaces(true);
         let _ = window.unminimize();
+        restore_window_geometry(&window);
         let _ = window.show();
         let _ = window.set_focus();
     }
 }
 
+/// Puts the window back where it was last left, pulled onto a connected
+/// monitor, or centers it when the saved spot is on a display that is gone.
+fn restore_window_geometry(window: &WebviewWindow) {
+    let Some(state) = window.try_state::<state::SharedState>() else {
+        return;
+    };
+    let Some(saved) = state.settings().window_geometry else {
+        return;
+    };
+    let monitors: Vec<settings::MonitorBounds> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| settings::MonitorBounds {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        })
+        .collect();
+    if monitors.is_empty() {
+        return;
+    }
+
+    match saved.clamped_to(&monitors) {
+        Some(geometry) => {
+            let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+            let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+        }
+        None => {
+            let _ = window.center();
+        }
+    }
+}
+
+/// Saves the window geometry once moves and resizes have been quiet for
+/// `WINDOW_GEOMETRY_SAVE_DELAY`, so a drag writes settings once, not per frame.
+fn spawn_window_geometry_saver(window: WebviewWindow) -> mpsc::Sender<()> {
+    let (changed_tx, changed_rx) = mpsc::channel::<()>();
+    std::thread::spawn(move || {
+        while changed_rx.recv().is_ok() {
+            loop {
+                match changed_rx.recv_timeout(WINDOW_GEOMETRY_SAVE_DELAY) {
+                    Ok(()) => continue,
+                    Err(mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+
+            // A minimized or hidden window reports an off-screen position
+            // that would be restored next launch.
+            if window.is_minimized().unwrap_or(true) || !window.is_visible().unwrap_or(false) {
+                continue;
+            }
+            let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+                continue;
+            };
+            let Some(state) = window.try_state::<state::SharedState>() else {
+                continue;
+            };
+            let geometry = settings::WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            };
+            if let Err(err) =
+                state.save_settings(|path| settings::save_window_geometry(path, Some(geometry)))
+            {
+                logging::warn(format!("failed to save window position: {err}"));
+            }
+        }
+    });
+    changed_tx
+}
+
 pub(crate) fn set_tray_listening(app: &AppHandle, listening: bool) {
     if let Some(tray) = app.tray_by_id(TRAY_ID) {
         let icon = if listening {
@@ -1,10 +1,14 @@
 use anyhow::{anyhow, Result};
 use reqwest::blocking::Client;
-use serde::Serialize;
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, RANGE};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::HashSet;
-use std::fs::{self, File};
+use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize)]
@@ -17,11 +21,46 @@ pub struct ModelInfo {
     pub download_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelHeader {
+    pub format: String,
+    pub model_type: String,
+    pub multilingual: bool,
+    pub approx_params_millions: Option<u32>,
+    pub n_vocab: i32,
+    pub n_audio_layer: i32,
+    pub n_text_layer: i32,
+    pub n_mels: i32,
+    pub ftype: String,
+}
+
+/// A `.part` file left by a paused or interrupted download of a known model.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialDownload {
+    pub file_name: String,
+    pub label: String,
+    pub bytes: u64,
+}
+
 struct KnownModel {
     file_name: &'static str,
     label: &'static str,
     quality: &'static str,
-    download_url: &'static str,
+    /// Tried in order; later entries are mirrors used when earlier ones fail.
+    download_urls: &'static [&'static str],
+    /// Lowercase hex SHA-256 of the published file.
+    sha256: &'static str,
+}
+
+/// A finished download whose contents do not match the published checksum.
+/// Kept as its own type so callers can tell corruption apart from network
+/// errors and suggest downloading again.
+#[derive(Debug, thiserror::Error)]
+#[error("Downloaded model '{file_name}' is corrupted (SHA-256 {actual}, expected {expected}). Download it again.")]
+pub struct ChecksumMismatch {
+    pub file_name: String,
+    pub expected: String,
+    pub actual: String,
 }
 
 const PREFERRED_ORDER: &[&str] = &[
@@ -39,52 +78,80 @@ const KNOWN_MODELS: &[KnownModel] = &[
         file_name: "ggml-large-v3-turbo-q5_0.bin",
         label: "large-v3-turbo-q5_0",
         quality: "best balance",
-        download_url:
+        download_urls: &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q5_0.bin",
+            "https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo-q5_0.bin",
+        ],
+        sha256: "394221709cd5ad1f40c46e6031ca61bce88931e6e088c188294c6d5a55ffa7e2",
     },
     KnownModel {
         file_name: "ggml-large-v3-turbo.bin",
         label: "large-v3-turbo",
         quality: "highest quality (fast)",
-        download_url:
+        download_urls: &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo.bin",
+            "https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main/ggml-large-v3-turbo.bin",
+        ],
+        sha256: "1fc70f774d38eb169993ac391eea357ef47c88757ef72ee5943879b7e8e2bc69",
     },
     KnownModel {
         file_name: "ggml-large-v3.bin",
         label: "large-v3",
         quality: "highest quality",
-        download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3.bin",
+        download_urls: &[
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-large-v3.bin",
+            "https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main/ggml-large-v3.bin",
+        ],
+        sha256: "64d182b440b98d5203c4f9bd541544d84c605196c4f7b845dfa11fb23594d1e2",
     },
     KnownModel {
         file_name: "ggml-medium.en.bin",
         label: "medium.en",
         quality: "high quality",
-        download_url:
+        download_urls: &[
             "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-medium.en.bin",
+            "https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main/ggml-medium.en.bin",
+        ],
+        sha256: "cc37e93478338ec7700281a7ac30a10128929eb8f427dda2e865faa8f6da4356",
     },
     KnownModel {
         file_name: "ggml-small.en.bin",
         label: "small.en",
         quality: "better than base",
-        download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en.bin",
+        download_urls: &[
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-small.en.bin",
+            "https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main/ggml-small.en.bin",
+        ],
+        sha256: "c6138d6d58ecc8322097e0f987c32f1be8bb0a18532a3f88f734d1bbf9c41e5d",
     },
     KnownModel {
         file_name: "ggml-base.en.bin",
         label: "base.en",
         quality: "balanced",
-        download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin",
+        download_urls: &[
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin",
+            "https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main/ggml-base.en.bin",
+        ],
+        sha256: "a03779c86df3323075f5e796cb2ce5029f00ec8869eee3fdfb897afe36c6d002",
     },
     KnownModel {
         file_name: "ggml-tiny.en.bin",
         label: "tiny.en",
         quality: "fastest",
-        download_url: "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en.bin",
+        download_urls: &[
+            "https://huggingface.co/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en.bin",
+            "https://hf-mirror.com/ggerganov/whisper.cpp/resolve/main/ggml-tiny.en.bin",
+        ],
+        sha256: "921e4cf8686fdd993dcd081a5da5b6c365bfde1162e72b08d75ac75289920b1f",
     },
 ];
 
 const MAX_DOWNLOAD_ATTEMPTS: usize = 3;
 const RETRY_BACKOFF_SECS: [u64; MAX_DOWNLOAD_ATTEMPTS - 1] = [2, 5];
 const MIN_EXPECTED_MODEL_BYTES: u64 = 10 * 1024 * 1024;
+const GGML_MAGIC: u32 = 0x6767_6d6c;
+const GGUF_MAGIC: u32 = 0x4655_4747;
+const MULTILINGUAL_MIN_VOCAB: i32 = 51_865;
 
 pub fn pick_default_model(models_dir: &Path) -> String {
     if let Some(best) = PREFERRED_ORDER
@@ -116,7 +183,7 @@ pub fn list_models(models_dir: &Path, active_model: &str) -> Result<Vec<ModelInf
             quality: known.quality.to_string(),
             installed,
             active: active_model == known.file_name,
-            download_url: Some(known.download_url.to_string()),
+            download_url: known.download_urls.first().map(|url| url.to_string()),
         });
         seen.insert(known.file_name.to_string());
     }
@@ -139,7 +206,23 @@ pub fn list_models(models_dir: &Path, active_model: &str) -> Result<Vec<ModelInf
     Ok(models)
 }
 
-pub fn download_model<F>(models_dir: &Path, file_name: &str, mut on_progress: F) -> Result<()>
+/// Downloads a known model, trying `mirror_prefix` (a user-supplied proxy or
+/// cache serving `<prefix>/<file_name>`) first and then each built-in URL in
+/// order. Returns the URL the file finally came from.
+///
+/// Each source gets `MAX_DOWNLOAD_ATTEMPTS` tries with backoff, resuming the
+/// `.part` file with a Range request, before the next source is tried. A file
+/// that is too small or fails its checksum also moves on to the next source.
+/// Bytes from an earlier source are only resumed elsewhere when their ETag or
+/// length can be checked against the new source's response. Setting `cancel`
+/// pauses the download and keeps the `.part` file so it can be resumed later.
+pub fn download_model<F>(
+    models_dir: &Path,
+    file_name: &str,
+    mirror_prefix: Option<&str>,
+    cancel: &AtomicBool,
+    mut on_progress: F,
+) -> Result<String>
 where
     F: FnMut(u8),
 {
@@ -150,55 +233,193 @@ where
         )
     })?;
 
-    fs::create_dir_all(models_dir)?;
-
+    let candidates = candidate_urls(known, mirror_prefix);
     let destination = models_dir.join(file_name);
     if destination.exists() {
         on_progress(100);
-        return Ok(());
+        return Ok(candidates[0].clone());
     }
+    ensure_models_dir_writable(models_dir)?;
 
     let partial = models_dir.join(format!("{file_name}.part"));
-    let mut last_err: Option<anyhow::Error> = None;
-
-    for attempt in 1..=MAX_DOWNLOAD_ATTEMPTS {
-        let _ = fs::remove_file(&partial);
+    let mut errors: Vec<String> = Vec::new();
 
-        match download_model_once(known.download_url, &partial, &mut on_progress) {
-            Ok(bytes) => {
-                if bytes < MIN_EXPECTED_MODEL_BYTES {
-                    let _ = fs::remove_file(&partial);
+    for url in &candidates {
+        prepare_partial_for(&partial, url);
+        for attempt in 1..=MAX_DOWNLOAD_ATTEMPTS {
+            match download_model_once(url, &partial, cancel, &mut on_progress) {
+                Ok(bytes) => match verify_download(known, &partial, bytes) {
+                    Ok(()) => {
+                        fs::rename(&partial, &destination)?;
+                        let _ = fs::remove_file(partial_source_path(&partial));
+                        on_progress(100);
+                        return Ok(url.clone());
+                    }
+                    Err(err) => {
+                        // Downloading the same bytes again would fail the same
+                        // way; another source may serve a good copy.
+                        remove_partial(&partial);
+                        errors.push(format!("{url}: {err}"));
+                        break;
+                    }
+                },
+                Err(_) if cancel.load(Ordering::Relaxed) => {
                     return Err(anyhow!(
-                        "Downloaded model is unexpectedly small ({bytes} bytes)."
+                        "Download of '{file_name}' paused. Resume it from the models list."
                     ));
                 }
-
-                fs::rename(&partial, &destination)?;
-                on_progress(100);
-                return Ok(());
-            }
-            Err(err) => {
-                last_err = Some(err);
-                let _ = fs::remove_file(&partial);
-
-                if attempt < MAX_DOWNLOAD_ATTEMPTS {
-                    std::thread::sleep(Duration::from_secs(RETRY_BACKOFF_SECS[attempt - 1]));
+                Err(err) => {
+                    // The partial file is kept so the next attempt against
+                    // this source picks up where this one stopped.
+                    errors.push(format!("{url} (attempt {attempt}): {err}"));
+                    if attempt < MAX_DOWNLOAD_ATTEMPTS {
+                        std::thread::sleep(Duration::from_secs(RETRY_BACKOFF_SECS[attempt - 1]));
+                    }
                 }
             }
         }
     }
 
     Err(anyhow!(
-        "Failed to download model '{}' after {} attempts: {}",
+        "Failed to download model '{}' from {} source(s):\n{}",
         file_name,
-        MAX_DOWNLOAD_ATTEMPTS,
-        last_err
-            .map(|err| err.to_string())
-            .unwrap_or_else(|| "unknown error".to_string())
+        candidates.len(),
+        errors.join("\n")
     ))
 }
 
-fn download_model_once<F>(download_url: &str, partial: &Path, on_progress: &mut F) -> Result<u64>
+/// Rejects a finished download that is too small to be a model or does not
+/// match the published checksum.
+fn verify_download(known: &KnownModel, partial: &Path, bytes: u64) -> Result<()> {
+    if bytes < MIN_EXPECTED_MODEL_BYTES {
+        return Err(anyhow!(
+            "Downloaded model is unexpectedly small ({bytes} bytes)."
+        ));
+    }
+    verify_checksum(known, partial)
+}
+
+/// Where the bytes in a `.part` file came from, with the validators the
+/// server sent for them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PartialSource {
+    url: String,
+    etag: Option<String>,
+    total: Option<u64>,
+}
+
+/// Decides whether `partial` may be resumed from `url`. Bytes written by
+/// another source are kept only if an ETag or total length was recorded for
+/// them, so the new source's response can be checked against it; otherwise
+/// they are deleted and the download starts over.
+fn prepare_partial_for(partial: &Path, url: &str) {
+    if !partial.exists() {
+        let _ = fs::remove_file(partial_source_path(partial));
+        return;
+    }
+    let resumable = read_partial_source(partial)
+        .is_some_and(|source| source.url == url || source.etag.is_some() || source.total.is_some());
+    if !resumable {
+        remove_partial(partial);
+    }
+}
+
+/// Reads the `.part.source` marker. Markers from older versions hold only
+/// the URL.
+fn read_partial_source(partial: &Path) -> Option<PartialSource> {
+    let raw = fs::read_to_string(partial_source_path(partial)).ok()?;
+    Some(
+        serde_json::from_str(&raw).unwrap_or_else(|_| PartialSource {
+            url: raw.trim().to_string(),
+            etag: None,
+            total: None,
+        }),
+    )
+}
+
+fn write_partial_source(partial: &Path, source: &PartialSource) -> Result<()> {
+    fs::write(partial_source_path(partial), serde_json::to_string(source)?)?;
+    Ok(())
+}
+
+/// `<model>.part.source`, describing where `<model>.part` came from.
+fn partial_source_path(partial: &Path) -> PathBuf {
+    partial.with_extension("part.source")
+}
+
+fn remove_partial(partial: &Path) {
+    let _ = fs::remove_file(partial);
+    let _ = fs::remove_file(partial_source_path(partial));
+}
+
+/// Creates `models_dir` if needed and proves it accepts writes with a probe
+/// file, so a read-only location fails up front with an actionable message
+/// instead of an opaque I/O error partway through a download.
+pub fn ensure_models_dir_writable(models_dir: &Path) -> Result<()> {
+    let probe = models_dir.join(".murmur-write-probe");
+    let result = fs::create_dir_all(models_dir).and_then(|()| File::create(&probe).map(drop));
+    let _ = fs::remove_file(&probe);
+    result.map_err(|err| {
+        anyhow!(
+            "The models directory {} is not writable ({err}). Make it writable, copy model files into it with an account that can, or move Murmur's data folder to a writable location.",
+            models_dir.display()
+        )
+    })
+}
+
+/// Compares the downloaded file against the model's published SHA-256.
+fn verify_checksum(known: &KnownModel, partial: &Path) -> Result<()> {
+    let expected = known.sha256;
+    let actual = file_sha256(partial)?;
+    if actual != expected {
+        return Err(ChecksumMismatch {
+            file_name: known.file_name.to_string(),
+            expected: expected.to_string(),
+            actual,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Lowercase hex SHA-256 of a file, read in chunks so multi-gigabyte models
+/// never have to fit in memory.
+fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0_u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+fn candidate_urls(known: &KnownModel, mirror_prefix: Option<&str>) -> Vec<String> {
+    let custom = mirror_prefix
+        .map(|prefix| prefix.trim().trim_end_matches('/'))
+        .filter(|prefix| !prefix.is_empty())
+        .map(|prefix| format!("{prefix}/{}", known.file_name));
+
+    custom
+        .into_iter()
+        .chain(known.download_urls.iter().map(|url| url.to_string()))
+        .collect()
+}
+
+fn download_model_once<F>(
+    download_url: &str,
+    partial: &Path,
+    cancel: &AtomicBool,
+    on_progress: &mut F,
+) -> Result<u64>
 where
     F: FnMut(u8),
 {
@@ -207,22 +428,87 @@ where
         .timeout(Duration::from_secs(60 * 20))
         .build()?;
 
-    let mut response = client
-        .get(download_url)
-        .header("User-Agent", "murmur/0.1")
-        .send()?
-        .error_for_status()?;
+    let source = read_partial_source(partial);
+    let resume_from = match &source {
+        Some(_) => fs::metadata(partial).map(|meta| meta.len()).unwrap_or(0),
+        None => 0,
+    };
+    let mut request = client.get(download_url).header("User-Agent", "murmur/0.1");
+    if resume_from > 0 {
+        request = request.header(RANGE, format!("bytes={resume_from}-"));
+        // A server whose copy has a different ETag sends the whole file
+        // instead, which starts the download over below.
+        if let Some(etag) = source.as_ref().and_then(|source| source.etag.as_deref()) {
+            request = request.header(IF_RANGE, etag);
+        }
+    }
+    let response = request.send()?;
+    if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        remove_partial(partial);
+        anyhow::bail!("Server cannot resume at byte {resume_from}; starting over");
+    }
+    let mut response = response.error_for_status()?;
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
 
-    let total_bytes = response.content_length();
-    let mut file = File::create(partial)?;
+    // 206 continues the partial file; a 200 means the server ignored the
+    // range, so the download starts from zero.
+    let (mut file, mut downloaded, total_bytes) = if resume_from > 0
+        && response.status() == StatusCode::PARTIAL_CONTENT
+    {
+        let content_range = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_content_range);
+        let Some((start, total)) = content_range.filter(|(start, _)| *start == resume_from) else {
+            remove_partial(partial);
+            anyhow::bail!("Server resumed at an unexpected offset; starting over");
+        };
+        let total = total.or_else(|| response.content_length().map(|remaining| start + remaining));
+        let recorded_total = source.as_ref().and_then(|source| source.total);
+        if recorded_total.is_some_and(|recorded| Some(recorded) != total) {
+            remove_partial(partial);
+            anyhow::bail!(
+                "The file on the server does not match the partial download; starting over"
+            );
+        }
+        (
+            OpenOptions::new().append(true).open(partial)?,
+            resume_from,
+            total,
+        )
+    } else {
+        (File::create(partial)?, 0, response.content_length())
+    };
+    write_partial_source(
+        partial,
+        &PartialSource {
+            url: download_url.to_string(),
+            etag,
+            total: total_bytes,
+        },
+    )?;
 
-    let mut downloaded: u64 = 0;
-    let mut last_percent: u8 = 0;
+    let percent_of = |downloaded: u64| {
+        total_bytes
+            .filter(|total| *total > 0)
+            .map(|total| ((downloaded.saturating_mul(100)) / total).min(100) as u8)
+    };
+    let mut last_percent = percent_of(downloaded).unwrap_or(0);
     let mut buffer = [0_u8; 64 * 1024];
 
-    on_progress(0);
+    on_progress(last_percent);
 
     loop {
+        if cancel.load(Ordering::Relaxed) {
+            file.flush()?;
+            anyhow::bail!("Download cancelled after {downloaded} bytes");
+        }
+
         let read = response.read(&mut buffer)?;
         if read == 0 {
             break;
@@ -231,13 +517,10 @@ where
         file.write_all(&buffer[..read])?;
         downloaded += read as u64;
 
-        if let Some(total) = total_bytes {
-            if total > 0 {
-                let percent = ((downloaded.saturating_mul(100)) / total).min(100) as u8;
-                if percent != last_percent {
-                    last_percent = percent;
-                    on_progress(percent);
-                }
+        if let Some(percent) = percent_of(downloaded) {
+            if percent != last_percent {
+                last_percent = percent;
+                on_progress(percent);
             }
         }
     }
@@ -246,6 +529,13 @@ where
     file.sync_all()?;
 
     if let Some(total) = total_bytes {
+        // More bytes than the server announced means the partial file was
+        // not a prefix of this model; resuming it again would never succeed.
+        if downloaded > total {
+            drop(file);
+            remove_partial(partial);
+            anyhow::bail!("Download overran its expected size of {total} bytes; starting over");
+        }
         if downloaded != total {
             anyhow::bail!(
                 "Incomplete download: expected {total} bytes, downloaded {downloaded} bytes"
@@ -256,6 +546,92 @@ where
     Ok(downloaded)
 }
 
+/// Parses `bytes <start>-<end>/<total>` into the start offset and the total
+/// size, which servers may report as `*` when unknown.
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _end) = range.split_once('-')?;
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start.trim().parse().ok()?, total))
+}
+
+/// Downloads that can be resumed: `.part` files of known models that are not
+/// installed yet, sorted by file name.
+pub fn partial_downloads(models_dir: &Path) -> Result<Vec<PartialDownload>> {
+    let mut partials = Vec::new();
+    for (file_name, bytes) in read_partial_files(models_dir)? {
+        let Some(known) = find_known_model(&file_name) else {
+            continue;
+        };
+        if models_dir.join(&file_name).exists() {
+            continue;
+        }
+        partials.push(PartialDownload {
+            label: known.label.to_string(),
+            file_name,
+            bytes,
+        });
+    }
+    Ok(partials)
+}
+
+/// Deletes `.part` files that can never be resumed because their model is
+/// unknown or already installed. Returns the model names they belonged to.
+pub fn remove_stale_partials(models_dir: &Path) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for (file_name, _) in read_partial_files(models_dir)? {
+        if find_known_model(&file_name).is_some() && !models_dir.join(&file_name).exists() {
+            continue;
+        }
+        let partial = models_dir.join(format!("{file_name}.part"));
+        fs::remove_file(&partial)?;
+        let _ = fs::remove_file(partial_source_path(&partial));
+        removed.push(file_name);
+    }
+    Ok(removed)
+}
+
+/// Deletes the `.part` file of a paused download. Returns whether one existed.
+pub fn discard_partial(models_dir: &Path, file_name: &str) -> Result<bool> {
+    if find_known_model(file_name).is_none() {
+        return Err(anyhow!("'{file_name}' is not a downloadable model"));
+    }
+    let partial = models_dir.join(format!("{file_name}.part"));
+    let _ = fs::remove_file(partial_source_path(&partial));
+    match fs::remove_file(&partial) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// `(model file name, bytes so far)` for every `<model>.part` file.
+fn read_partial_files(models_dir: &Path) -> Result<Vec<(String, u64)>> {
+    let mut partials = Vec::new();
+    if !models_dir.exists() {
+        return Ok(partials);
+    }
+
+    for entry in fs::read_dir(models_dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let name = entry.file_name();
+        let Some(file_name) = name.to_str().and_then(|name| name.strip_suffix(".part")) else {
+            continue;
+        };
+        partials.push((file_name.to_string(), metadata.len()));
+    }
+
+    partials.sort();
+    Ok(partials)
+}
+
 fn read_installed_model_files(models_dir: &Path) -> Result<Vec<String>> {
     let mut files = Vec::new();
     if !models_dir.exists() {
@@ -282,8 +658,456 @@ fn read_installed_model_files(models_dir: &Path) -> Result<Vec<String>> {
     Ok(files)
 }
 
+/// Renames an installed custom model and returns the file name it ended up
+/// with. Known models keep their names so they still match the static table.
+pub fn rename_model(models_dir: &Path, old_name: &str, new_name: &str) -> Result<String> {
+    if find_known_model(old_name).is_some() {
+        return Err(anyhow!("Built-in models cannot be renamed"));
+    }
+    let new_name = custom_model_file_name(new_name)?;
+    if find_known_model(&new_name).is_some() {
+        return Err(anyhow!("'{new_name}' is reserved for a built-in model"));
+    }
+
+    let installed = read_installed_model_files(models_dir)?;
+    if !installed.iter().any(|file| file == old_name) {
+        return Err(anyhow!("Model '{old_name}' is not installed"));
+    }
+    if new_name == old_name {
+        return Ok(new_name);
+    }
+    let target = models_dir.join(&new_name);
+    if target.exists() {
+        return Err(anyhow!("A model named '{new_name}' already exists"));
+    }
+
+    fs::rename(models_dir.join(old_name), target)?;
+    Ok(new_name)
+}
+
+/// Removes an installed model file and returns the bytes freed. The active
+/// model cannot be deleted; switch to another one first.
+pub fn delete_model(models_dir: &Path, file_name: &str, active_model: &str) -> Result<u64> {
+    if file_name == active_model {
+        return Err(anyhow!(
+            "'{file_name}' is the active model. Switch to another model before deleting it."
+        ));
+    }
+    // Only names read back from the directory are accepted, so nothing
+    // outside it can be removed.
+    let installed = read_installed_model_files(models_dir)?;
+    if !installed.iter().any(|file| file == file_name) {
+        return Err(anyhow!("Model '{file_name}' is not installed"));
+    }
+
+    let path = models_dir.join(file_name);
+    let bytes = fs::metadata(&path)?.len();
+    fs::remove_file(&path)?;
+    Ok(bytes)
+}
+
+/// Maps what a user or script typed to a model file name. Accepts the exact
+/// file name, a built-in label such as `large-v3-turbo`, or a custom model's
+/// name without `.bin`, ignoring case. When nothing matches exactly, a unique
+/// prefix is accepted; ambiguous input is rejected with the candidates.
+pub fn resolve_model_name(models_dir: &Path, query: &str) -> Result<String> {
+    let query = query.trim();
+    let mut candidates: Vec<(String, Vec<String>)> = KNOWN_MODELS
+        .iter()
+        .map(|known| {
+            (
+                known.file_name.to_string(),
+                vec![known.file_name.to_string(), known.label.to_string()],
+            )
+        })
+        .collect();
+    for file_name in read_installed_model_files(models_dir)? {
+        if find_known_model(&file_name).is_none() {
+            let stem = file_name.trim_end_matches(".bin").to_string();
+            candidates.push((file_name.clone(), vec![file_name, stem]));
+        }
+    }
+
+    if candidates.iter().any(|(file_name, _)| file_name == query) {
+        return Ok(query.to_string());
+    }
+
+    let query_lower = query.to_lowercase();
+    let matching = |matches: &dyn Fn(&str) -> bool| -> Vec<String> {
+        candidates
+            .iter()
+            .filter(|(_, names)| names.iter().any(|name| matches(&name.to_lowercase())))
+            .map(|(file_name, _)| file_name.clone())
+            .collect()
+    };
+    let mut found = matching(&|name| name == query_lower);
+    if found.is_empty() && !query_lower.is_empty() {
+        found = matching(&|name| name.starts_with(&query_lower));
+    }
+
+    match found.len() {
+        1 => Ok(found.remove(0)),
+        0 => Err(anyhow!(
+            "No model matches '{query}'. Available: {}",
+            candidates
+                .iter()
+                .map(|(file_name, _)| file_name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        _ => Err(anyhow!(
+            "'{query}' matches several models: {}",
+            found.join(", ")
+        )),
+    }
+}
+
+/// Turns a user-entered name into a bare `.bin` file name inside the models
+/// directory, rejecting anything that could point elsewhere.
+fn custom_model_file_name(raw: &str) -> Result<String> {
+    let name = raw.trim();
+    let stem = name.strip_suffix(".bin").unwrap_or(name);
+    if stem.is_empty() {
+        return Err(anyhow!("Model name cannot be empty"));
+    }
+    if stem.starts_with('.')
+        || stem.contains(['/', '\\', ':'])
+        || stem.chars().any(char::is_control)
+    {
+        return Err(anyhow!("Invalid model name '{name}'"));
+    }
+    Ok(format!("{stem}.bin"))
+}
+
+/// Reads the whisper hyperparameters from the start of a ggml model file without
+/// loading the weights.
+pub fn read_model_header(path: &Path) -> Result<ModelHeader> {
+    let mut file = File::open(path)?;
+    let mut raw = [0_u8; 48];
+    file.read_exact(&mut raw)
+        .map_err(|_| anyhow!("Model file is too small to contain a whisper header."))?;
+    parse_model_header(&raw)
+}
+
+fn parse_model_header(raw: &[u8]) -> Result<ModelHeader> {
+    let word = |idx: usize| -> Option<i32> {
+        let start = idx * 4;
+        raw.get(start..start + 4)
+            .map(|bytes| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    let magic = word(0).map(|value| value as u32);
+    match magic {
+        Some(GGML_MAGIC) => {}
+        Some(GGUF_MAGIC) => {
+            anyhow::bail!(
+                "GGUF model files are not supported by whisper.cpp; use a ggml .bin model."
+            )
+        }
+        _ => anyhow::bail!("File is not a ggml whisper model (unexpected header magic)."),
+    }
+
+    let hparams: Vec<i32> = (1..=11).filter_map(word).collect();
+    if hparams.len() < 11 {
+        anyhow::bail!("Model header is truncated.");
+    }
+    let (n_vocab, n_audio_layer, n_text_layer, n_mels, ftype) =
+        (hparams[0], hparams[4], hparams[8], hparams[9], hparams[10]);
+
+    if n_vocab <= 0 || n_audio_layer <= 0 || n_text_layer <= 0 || !(1..=512).contains(&n_mels) {
+        anyhow::bail!("Model header has implausible whisper hyperparameters.");
+    }
+
+    let (model_type, approx_params_millions) = match (n_audio_layer, n_text_layer) {
+        (4, _) => ("tiny", Some(39)),
+        (6, _) => ("base", Some(74)),
+        (12, _) => ("small", Some(244)),
+        (24, _) => ("medium", Some(769)),
+        (32, 4) => ("large-turbo", Some(809)),
+        (32, _) => ("large", Some(1550)),
+        _ => ("unknown", None),
+    };
+
+    Ok(ModelHeader {
+        format: "ggml".to_string(),
+        model_type: model_type.to_string(),
+        multilingual: n_vocab >= MULTILINGUAL_MIN_VOCAB,
+        approx_params_millions,
+        n_vocab,
+        n_audio_layer,
+        n_text_layer,
+        n_mels,
+        ftype: describe_ftype(ftype),
+    })
+}
+
+fn describe_ftype(ftype: i32) -> String {
+    // whisper.cpp stores the quantization version in the thousands place.
+    match ftype % 1000 {
+        0 => "f32".to_string(),
+        1 => "f16".to_string(),
+        2 => "q4_0".to_string(),
+        3 => "q4_1".to_string(),
+        7 => "q8_0".to_string(),
+        8 => "q5_0".to_string(),
+        9 => "q5_1".to_string(),
+        other => format!("type {other}"),
+    }
+}
+
 fn find_known_model(file_name: &str) -> Option<&'static KnownModel> {
     KNOWN_MODELS
         .iter()
         .find(|model| model.file_name == file_name)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_bytes(magic: u32, hparams: [i32; 11]) -> Vec<u8> {
+        let mut raw = magic.to_le_bytes().to_vec();
+        for value in hparams {
+            raw.extend_from_slice(&value.to_le_bytes());
+        }
+        raw
+    }
+
+    #[test]
+    fn candidate_urls_put_custom_mirror_first() {
+        let known = find_known_model("ggml-base.en.bin").unwrap();
+
+        let urls = candidate_urls(known, Some(" https://cache.example.com/models/ "));
+        assert_eq!(urls[0], "https://cache.example.com/models/ggml-base.en.bin");
+        assert_eq!(urls.len(), known.download_urls.len() + 1);
+        assert_eq!(
+            candidate_urls(known, Some("")).len(),
+            known.download_urls.len()
+        );
+    }
+
+    #[test]
+    fn resolve_model_name_accepts_labels_and_rejects_ambiguity() {
+        let dir = std::env::temp_dir().join(format!("murmur-resolve-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        File::create(dir.join("My Finetune.bin")).unwrap();
+
+        let resolve = |query: &str| resolve_model_name(&dir, query);
+        assert_eq!(resolve("ggml-base.en.bin").unwrap(), "ggml-base.en.bin");
+        assert_eq!(
+            resolve("Large-V3-Turbo").unwrap(),
+            "ggml-large-v3-turbo.bin"
+        );
+        assert_eq!(resolve("my finetune").unwrap(), "My Finetune.bin");
+        assert_eq!(resolve("tiny").unwrap(), "ggml-tiny.en.bin");
+        let ambiguous = resolve("large").unwrap_err().to_string();
+        assert!(ambiguous.contains("ggml-large-v3.bin"), "{ambiguous}");
+        assert!(resolve("nope").is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn parse_content_range_reads_start_and_total() {
+        assert_eq!(
+            parse_content_range("bytes 1024-2047/4096"),
+            Some((1024, Some(4096)))
+        );
+        assert_eq!(parse_content_range("bytes 10-19/*"), Some((10, None)));
+        assert_eq!(parse_content_range("bytes */4096"), None);
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+    }
+
+    #[test]
+    fn verify_checksum_rejects_mismatched_files() {
+        let dir = std::env::temp_dir().join(format!("murmur-sha-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ggml-tiny.en.bin.part");
+        fs::write(&path, b"abc").unwrap();
+        let abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        let model = |sha256| KnownModel {
+            file_name: "ggml-tiny.en.bin",
+            label: "tiny.en",
+            quality: "fastest",
+            download_urls: &[],
+            sha256,
+        };
+
+        assert!(verify_checksum(&model(abc), &path).is_ok());
+        let err = verify_checksum(&model(&abc[1..]), &path).unwrap_err();
+        assert!(err.downcast_ref::<ChecksumMismatch>().is_some());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn temp_models_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("murmur-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Serves `body` to one request on a local port, honoring a Range header.
+    /// Returns the URL and a handle yielding the raw request.
+    fn serve_once(body: Vec<u8>) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/ggml-tiny.en.bin", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                request.push_str(&line.to_ascii_lowercase());
+            }
+            let start = request
+                .lines()
+                .find_map(|line| line.strip_prefix("range: bytes="))
+                .and_then(|range| range.trim().trim_end_matches('-').parse::<usize>().ok());
+            let head = match start {
+                Some(start) => format!(
+                    "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {start}-{}/{}\r\nContent-Length: {}\r\n\r\n",
+                    body.len() - 1,
+                    body.len(),
+                    body.len() - start
+                ),
+                None => format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", body.len()),
+            };
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body[start.unwrap_or(0)..]).unwrap();
+            request
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn interrupted_download_resumes_from_its_partial_file() {
+        let dir = temp_models_dir("resume-test");
+        let partial = dir.join("ggml-tiny.en.bin.part");
+        let body: Vec<u8> = (0..=255).cycle().take(4_000).collect();
+        let (url, server) = serve_once(body.clone());
+
+        fs::write(&partial, &body[..1_500]).unwrap();
+        write_partial_source(
+            &partial,
+            &PartialSource {
+                url: url.clone(),
+                etag: None,
+                total: Some(body.len() as u64),
+            },
+        )
+        .unwrap();
+        prepare_partial_for(&partial, &url);
+
+        let downloaded =
+            download_model_once(&url, &partial, &AtomicBool::new(false), &mut |_| {}).unwrap();
+        assert!(server.join().unwrap().contains("range: bytes=1500-"));
+        assert_eq!(downloaded, body.len() as u64);
+        assert_eq!(fs::read(&partial).unwrap(), body);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn partial_download_moves_between_sources_only_when_validated() {
+        let dir = temp_models_dir("part-test");
+        let partial = dir.join("ggml-tiny.en.bin.part");
+        let first = "https://huggingface.co/ggml-tiny.en.bin";
+        let second = "https://hf-mirror.com/ggml-tiny.en.bin";
+        let source = |total: Option<u64>| PartialSource {
+            url: first.to_string(),
+            etag: None,
+            total,
+        };
+
+        fs::write(&partial, b"first half").unwrap();
+        write_partial_source(&partial, &source(None)).unwrap();
+        prepare_partial_for(&partial, first);
+        assert!(partial.exists());
+        prepare_partial_for(&partial, second);
+        assert!(!partial.exists());
+        assert!(!partial_source_path(&partial).exists());
+
+        fs::write(&partial, b"first half").unwrap();
+        write_partial_source(&partial, &source(Some(20))).unwrap();
+        prepare_partial_for(&partial, second);
+        assert!(partial.exists());
+        assert_eq!(read_partial_source(&partial), Some(source(Some(20))));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn every_known_model_has_a_sha256_digest() {
+        for known in KNOWN_MODELS {
+            assert_eq!(known.sha256.len(), 64, "{}", known.file_name);
+            assert!(
+                known
+                    .sha256
+                    .bytes()
+                    .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte)),
+                "{}",
+                known.file_name
+            );
+        }
+    }
+
+    #[test]
+    fn delete_model_refuses_active_and_unknown_models() {
+        let dir = std::env::temp_dir().join(format!("murmur-delete-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("ggml-base.en.bin"), [0_u8; 8]).unwrap();
+        fs::write(dir.join("ggml-tiny.en.bin"), [0_u8; 4]).unwrap();
+
+        assert!(delete_model(&dir, "ggml-base.en.bin", "ggml-base.en.bin").is_err());
+        assert!(delete_model(&dir, "../ggml-base.en.bin", "ggml-tiny.en.bin").is_err());
+        assert_eq!(
+            delete_model(&dir, "ggml-base.en.bin", "ggml-tiny.en.bin").unwrap(),
+            8
+        );
+        assert!(!dir.join("ggml-base.en.bin").exists());
+        assert_eq!(pick_default_model(&dir), "ggml-tiny.en.bin");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn custom_model_file_name_keeps_names_inside_models_dir() {
+        assert_eq!(
+            custom_model_file_name(" My Finetune ").unwrap(),
+            "My Finetune.bin"
+        );
+        assert_eq!(custom_model_file_name("tuned.bin").unwrap(), "tuned.bin");
+        for bad in [
+            "",
+            ".bin",
+            "../escape",
+            "nested/model",
+            "..\\up",
+            ".hidden",
+            "c:model",
+        ] {
+            assert!(custom_model_file_name(bad).is_err(), "{bad:?} was accepted");
+        }
+    }
+
+    #[test]
+    fn parse_model_header_reads_base_en() {
+        let raw = header_bytes(GGML_MAGIC, [51_864, 1500, 512, 8, 6, 448, 512, 8, 6, 80, 1]);
+        let header = parse_model_header(&raw).expect("valid header");
+
+        assert_eq!(header.model_type, "base");
+        assert!(!header.multilingual);
+        assert_eq!(header.ftype, "f16");
+    }
+
+    #[test]
+    fn parse_model_header_rejects_non_ggml() {
+        let raw = header_bytes(0x1234_5678, [0; 11]);
+        assert!(parse_model_header(&raw).is_err());
+    }
+}
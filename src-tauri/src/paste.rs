@@ -0,0 +1,399 @@
+use anyhow::Result;
+
+/// Sends the platform paste shortcut to the focused application, inserting
+/// whatever is on the clipboard at its cursor.
+pub fn paste_clipboard() -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        ensure_accessibility_permission()?;
+        mac::send_command_v()
+    }
+    #[cfg(target_os = "windows")]
+    {
+        win::send_ctrl_v()
+    }
+    #[cfg(target_os = "linux")]
+    {
+        linux_paste()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    Err(anyhow::anyhow!(
+        "Auto-paste is not supported on this platform."
+    ))
+}
+
+/// Types `text` into the focused application as keystrokes, after deleting
+/// `backspaces` characters before the cursor. Slower than pasting, but some
+/// apps handle typed input better, and the clipboard is left alone.
+pub fn type_text(backspaces: usize, text: &str) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    {
+        ensure_accessibility_permission()?;
+        mac::type_text(backspaces, text)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        win::type_text(backspaces, text)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        linux_type(backspaces, text)
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    Err(anyhow::anyhow!(
+        "Typing into other apps is not supported on this platform."
+    ))
+}
+
+/// What to send to turn `typed`, already in the field, into `target`: how
+/// many characters to delete from the end and the text to type after that.
+/// Only the part after the first difference is retyped.
+pub fn typing_edit<'a>(typed: &str, target: &'a str) -> (usize, &'a str) {
+    let shared: usize = typed
+        .chars()
+        .zip(target.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(ch, _)| ch.len_utf8())
+        .sum();
+    (typed[shared..].chars().count(), &target[shared..])
+}
+
+/// Whether Murmur may send keystrokes to other apps. Only macOS gates this,
+/// behind the Accessibility permission; elsewhere it is always `true`.
+pub fn accessibility_permission_status() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        mac::is_trusted(false)
+    }
+    #[cfg(not(target_os = "macos"))]
+    true
+}
+
+/// Shows the system prompt pointing at the Accessibility settings if access
+/// has not been granted. The grant happens in System Settings, so this
+/// returns the state at the time of the call rather than waiting for it.
+pub fn request_accessibility_permission() -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        mac::is_trusted(true)
+    }
+    #[cfg(not(target_os = "macos"))]
+    true
+}
+
+/// Checks access without prompting: pasting happens mid-dictation, so the
+/// system prompt is only shown when the user turns auto-paste on or asks.
+#[cfg(target_os = "macos")]
+fn ensure_accessibility_permission() -> Result<()> {
+    if mac::is_trusted(false) {
+        return Ok(());
+    }
+    Err(anyhow::anyhow!(
+        "Auto-paste and stream typing need Accessibility access. Enable Murmur in System Settings > Privacy & Security > Accessibility."
+    ))
+}
+
+/// Pastes with xdotool on X11, or wtype on Wayland.
+#[cfg(target_os = "linux")]
+fn linux_paste() -> Result<()> {
+    use std::process::Command;
+
+    let attempts: [(&str, &[&str]); 2] = [
+        ("xdotool", &["key", "--clearmodifiers", "ctrl+v"]),
+        ("wtype", &["-M", "ctrl", "v", "-m", "ctrl"]),
+    ];
+    for (program, args) in attempts {
+        if let Ok(status) = Command::new(program).args(args).status() {
+            if status.success() {
+                return Ok(());
+            }
+        }
+    }
+    Err(anyhow::anyhow!(
+        "Auto-paste needs xdotool (X11) or wtype (Wayland) installed."
+    ))
+}
+
+/// Types with wtype on Wayland, or xdotool on X11.
+#[cfg(target_os = "linux")]
+fn linux_type(backspaces: usize, text: &str) -> Result<()> {
+    use std::process::Command;
+
+    let run = |program: &str, args: &[&str]| {
+        Command::new(program)
+            .args(args)
+            .status()
+            .is_ok_and(|status| status.success())
+    };
+    let repeat = backspaces.to_string();
+    let typed = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let deletes: Vec<&str> = ["-k", "BackSpace"].repeat(backspaces);
+        (backspaces == 0 || run("wtype", &deletes))
+            && (text.is_empty() || run("wtype", &["--", text]))
+    } else {
+        (backspaces == 0
+            || run(
+                "xdotool",
+                &["key", "--clearmodifiers", "--repeat", &repeat, "BackSpace"],
+            ))
+            && (text.is_empty() || run("xdotool", &["type", "--clearmodifiers", "--", text]))
+    };
+    if typed {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Stream typing needs wtype (Wayland) or xdotool (X11) installed."
+        ))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod mac {
+    use anyhow::{anyhow, Result};
+    use std::ffi::c_void;
+
+    type CFTypeRef = *const c_void;
+
+    /// Only ever used by address.
+    #[repr(C)]
+    struct CallBacks {
+        _private: [u8; 0],
+    }
+
+    const KEY_V: u16 = 9;
+    const KEY_DELETE: u16 = 51;
+    const FLAG_COMMAND: u64 = 0x0010_0000;
+    const EVENT_SOURCE_COMBINED_SESSION: i32 = 0;
+    const HID_EVENT_TAP: u32 = 0;
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        static kAXTrustedCheckOptionPrompt: CFTypeRef;
+        fn AXIsProcessTrustedWithOptions(options: CFTypeRef) -> u8;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        static kCFBooleanTrue: CFTypeRef;
+        static kCFBooleanFalse: CFTypeRef;
+        static kCFTypeDictionaryKeyCallBacks: CallBacks;
+        static kCFTypeDictionaryValueCallBacks: CallBacks;
+        fn CFDictionaryCreate(
+            allocator: CFTypeRef,
+            keys: *const CFTypeRef,
+            values: *const CFTypeRef,
+            count: isize,
+            key_callbacks: *const CallBacks,
+            value_callbacks: *const CallBacks,
+        ) -> CFTypeRef;
+        fn CFRelease(cf: CFTypeRef);
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventSourceCreate(state_id: i32) -> CFTypeRef;
+        fn CGEventCreateKeyboardEvent(source: CFTypeRef, key: u16, key_down: bool) -> CFTypeRef;
+        fn CGEventSetFlags(event: CFTypeRef, flags: u64);
+        fn CGEventKeyboardSetUnicodeString(event: CFTypeRef, length: usize, string: *const u16);
+        fn CGEventPost(tap: u32, event: CFTypeRef);
+    }
+
+    /// `AXIsProcessTrustedWithOptions`, showing the system prompt when
+    /// `prompt` is set and access is missing.
+    pub fn is_trusted(prompt: bool) -> bool {
+        unsafe {
+            let value = if prompt {
+                kCFBooleanTrue
+            } else {
+                kCFBooleanFalse
+            };
+            let options = CFDictionaryCreate(
+                std::ptr::null(),
+                &kAXTrustedCheckOptionPrompt,
+                &value,
+                1,
+                &kCFTypeDictionaryKeyCallBacks,
+                &kCFTypeDictionaryValueCallBacks,
+            );
+            let trusted = AXIsProcessTrustedWithOptions(options) != 0;
+            if !options.is_null() {
+                CFRelease(options);
+            }
+            trusted
+        }
+    }
+
+    pub fn send_command_v() -> Result<()> {
+        unsafe {
+            let source = CGEventSourceCreate(EVENT_SOURCE_COMBINED_SESSION);
+            for key_down in [true, false] {
+                let event = CGEventCreateKeyboardEvent(source, KEY_V, key_down);
+                if event.is_null() {
+                    if !source.is_null() {
+                        CFRelease(source);
+                    }
+                    return Err(anyhow!("Failed to create the paste keystroke."));
+                }
+                CGEventSetFlags(event, FLAG_COMMAND);
+                CGEventPost(HID_EVENT_TAP, event);
+                CFRelease(event);
+            }
+            if !source.is_null() {
+                CFRelease(source);
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes with the Delete (backspace) key, then types each character as
+    /// a Unicode keystroke, so the keyboard layout does not matter.
+    pub fn type_text(backspaces: usize, text: &str) -> Result<()> {
+        unsafe {
+            let source = CGEventSourceCreate(EVENT_SOURCE_COMBINED_SESSION);
+            let mut result = Ok(());
+            for _ in 0..backspaces {
+                result = result.and_then(|()| post_key(source, KEY_DELETE, &[]));
+            }
+            let mut units = [0_u16; 2];
+            for ch in text.chars() {
+                result = result.and_then(|()| post_key(source, 0, ch.encode_utf16(&mut units)));
+            }
+            if !source.is_null() {
+                CFRelease(source);
+            }
+            result
+        }
+    }
+
+    /// Posts a key down and up for `key`, typing `units` instead of the key's
+    /// own character when they are given.
+    unsafe fn post_key(source: CFTypeRef, key: u16, units: &[u16]) -> Result<()> {
+        for key_down in [true, false] {
+            let event = CGEventCreateKeyboardEvent(source, key, key_down);
+            if event.is_null() {
+                return Err(anyhow!("Failed to create a typing keystroke."));
+            }
+            // A modifier still held from the hotkey must not change the key.
+            CGEventSetFlags(event, 0);
+            if !units.is_empty() {
+                CGEventKeyboardSetUnicodeString(event, units.len(), units.as_ptr());
+            }
+            CGEventPost(HID_EVENT_TAP, event);
+            CFRelease(event);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod win {
+    use anyhow::{anyhow, Result};
+
+    const INPUT_KEYBOARD: u32 = 1;
+    const KEYEVENTF_KEYUP: u32 = 0x0002;
+    const KEYEVENTF_UNICODE: u32 = 0x0004;
+    const VK_BACK: u16 = 0x08;
+    const VK_CONTROL: u16 = 0x11;
+    const VK_V: u16 = 0x56;
+
+    /// Mirrors the Win32 `KEYBDINPUT` layout.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct KeybdInput {
+        vk: u16,
+        scan: u16,
+        flags: u32,
+        time: u32,
+        extra_info: usize,
+    }
+
+    /// Mirrors the Win32 `INPUT` layout for keyboard input. The padding
+    /// covers `MOUSEINPUT`, the largest member of its union.
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Input {
+        kind: u32,
+        ki: KeybdInput,
+        _padding: [u8; 8],
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn SendInput(count: u32, inputs: *const Input, size: i32) -> u32;
+    }
+
+    fn key(vk: u16, flags: u32) -> Input {
+        keystroke(vk, 0, flags)
+    }
+
+    fn keystroke(vk: u16, scan: u16, flags: u32) -> Input {
+        Input {
+            kind: INPUT_KEYBOARD,
+            ki: KeybdInput {
+                vk,
+                scan,
+                flags,
+                time: 0,
+                extra_info: 0,
+            },
+            _padding: [0; 8],
+        }
+    }
+
+    fn send(inputs: &[Input]) -> Result<()> {
+        let sent = unsafe {
+            SendInput(
+                inputs.len() as u32,
+                inputs.as_ptr(),
+                std::mem::size_of::<Input>() as i32,
+            )
+        };
+        if sent as usize != inputs.len() {
+            return Err(anyhow!(
+                "Keystrokes were blocked. Murmur cannot type into apps running as administrator."
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn send_ctrl_v() -> Result<()> {
+        let inputs = [
+            key(VK_CONTROL, 0),
+            key(VK_V, 0),
+            key(VK_V, KEYEVENTF_KEYUP),
+            key(VK_CONTROL, KEYEVENTF_KEYUP),
+        ];
+        send(&inputs)
+    }
+
+    /// Backspaces, then each UTF-16 unit of `text` as a Unicode keystroke.
+    pub fn type_text(backspaces: usize, text: &str) -> Result<()> {
+        let mut inputs = Vec::new();
+        for _ in 0..backspaces {
+            inputs.push(key(VK_BACK, 0));
+            inputs.push(key(VK_BACK, KEYEVENTF_KEYUP));
+        }
+        for unit in text.encode_utf16() {
+            inputs.push(keystroke(0, unit, KEYEVENTF_UNICODE));
+            inputs.push(keystroke(0, unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP));
+        }
+        send(&inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typing_edit_retypes_only_what_changed() {
+        assert_eq!(typing_edit("", "Hello there."), (0, "Hello there."));
+        assert_eq!(typing_edit("Hello.", "Hello. More."), (0, " More."));
+        assert_eq!(
+            typing_edit("I have to cats", "I have two cats"),
+            (6, "wo cats")
+        );
+        assert_eq!(typing_edit("café au lait", "café noir"), (7, "noir"));
+        assert_eq!(typing_edit("Done.", "Done."), (0, ""));
+    }
+}
@@ -0,0 +1,883 @@
+use serde::{Deserialize, Serialize};
+
+/// How spelled-out and numeric numbers are normalized after transcription.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NumberFormat {
+    #[default]
+    Off,
+    Digits,
+    Words,
+}
+
+/// How segment start times are written when timestamps are kept in the text.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampFormat {
+    /// `[mm:ss]`; minutes keep counting past an hour.
+    #[default]
+    MinutesSeconds,
+    /// `[hh:mm:ss]`.
+    HoursMinutesSeconds,
+}
+
+const UNITS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [(&str, u64); 3] = [
+    ("thousand", 1_000),
+    ("million", 1_000_000),
+    ("billion", 1_000_000_000),
+];
+
+/// Spoken integers below this stay ungrouped so four-digit years read as
+/// "2025" rather than "2,025".
+const MIN_GROUPED_VALUE: u64 = 10_000;
+
+/// Largest value converted from digits to words. Longer digit strings are more
+/// likely to be identifiers than quantities.
+const MAX_WORDS_VALUE: u64 = 999_999_999;
+
+/// Digit grouping and decimal separators used when numbers are written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    group: char,
+    decimal: char,
+    /// South Asian grouping: the last three digits, then pairs (12,34,567).
+    indian_grouping: bool,
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self::new(',', '.')
+    }
+}
+
+impl NumberLocale {
+    const fn new(group: char, decimal: char) -> Self {
+        Self {
+            group,
+            decimal,
+            indian_grouping: false,
+        }
+    }
+
+    /// Separators for a locale tag such as `de-CH` or `fr_FR.UTF-8`. Languages
+    /// without an entry use English conventions.
+    pub fn for_tag(tag: &str) -> Self {
+        let Some(tag) = canonical_locale_tag(tag) else {
+            return Self::default();
+        };
+        let mut subtags = tag.split('-');
+        let language = subtags.next().unwrap_or_default();
+        let region = subtags.find(|subtag| subtag.len() != 4);
+
+        const APOSTROPHE: char = '\u{2019}';
+        const NBSP: char = '\u{a0}';
+        const NARROW_NBSP: char = '\u{202f}';
+        match (language, region) {
+            ("de" | "it", Some("CH" | "LI")) => Self::new(APOSTROPHE, '.'),
+            ("de", Some("AT")) | ("pt", Some("PT")) => Self::new(NBSP, ','),
+            ("en", Some("ZA")) => Self::new(NBSP, ','),
+            ("es", Some("MX" | "US" | "419" | "PR")) => Self::default(),
+            ("en", Some("IN")) | ("hi" | "bn" | "mr" | "gu" | "ta" | "te" | "kn" | "ml", _) => {
+                Self {
+                    indian_grouping: true,
+                    ..Self::default()
+                }
+            }
+            ("fr", _) => Self::new(NARROW_NBSP, ','),
+            (
+                "ca" | "da" | "de" | "el" | "es" | "hr" | "id" | "it" | "nl" | "pt" | "ro" | "sl"
+                | "sr" | "tr" | "vi",
+                _,
+            ) => Self::new('.', ','),
+            (
+                "be" | "bg" | "cs" | "et" | "fi" | "hu" | "kk" | "lt" | "lv" | "nb" | "nn" | "no"
+                | "pl" | "ru" | "sk" | "sv" | "uk",
+                _,
+            ) => Self::new(NBSP, ','),
+            _ => Self::default(),
+        }
+    }
+
+    /// The operating system's locale, falling back to English conventions.
+    pub fn system() -> Self {
+        sys_locale::get_locale()
+            .map(|tag| Self::for_tag(&tag))
+            .unwrap_or_default()
+    }
+
+    fn is_group_boundary(&self, remaining_digits: usize) -> bool {
+        if self.indian_grouping && remaining_digits > 3 {
+            (remaining_digits - 3).is_multiple_of(2)
+        } else {
+            remaining_digits.is_multiple_of(3)
+        }
+    }
+
+    fn group(&self, value: u64) -> String {
+        let digits = value.to_string();
+        let mut out = String::with_capacity(digits.len() * 2);
+        for (idx, digit) in digits.chars().enumerate() {
+            if idx > 0 && self.is_group_boundary(digits.len() - idx) {
+                out.push(self.group);
+            }
+            out.push(digit);
+        }
+        out
+    }
+}
+
+/// Normalizes POSIX and BCP 47 spellings (`pt_BR.UTF-8`, `zh-hant-tw`) to
+/// `language[-Script][-REGION]`, or `None` when `raw` is not a locale tag.
+/// `C` and `POSIX` map to `en-US`.
+pub fn canonical_locale_tag(raw: &str) -> Option<String> {
+    let base = raw.trim().split(['.', '@']).next().unwrap_or_default();
+    if base == "C" || base == "POSIX" {
+        return Some("en-US".to_string());
+    }
+
+    let mut subtags = base.split(['-', '_']);
+    let language = subtags.next()?;
+    if !(2..=3).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut tag = language.to_ascii_lowercase();
+    for subtag in subtags {
+        let normalized = match subtag.len() {
+            2 if subtag.chars().all(|c| c.is_ascii_alphabetic()) => subtag.to_ascii_uppercase(),
+            3 if subtag.chars().all(|c| c.is_ascii_digit()) => subtag.to_string(),
+            4 if subtag.chars().all(|c| c.is_ascii_alphabetic()) => {
+                let lower = subtag.to_ascii_lowercase();
+                lower[..1].to_ascii_uppercase() + &lower[1..]
+            }
+            _ => return None,
+        };
+        tag.push('-');
+        tag.push_str(&normalized);
+    }
+    Some(tag)
+}
+
+pub fn format_numbers(text: &str, mode: NumberFormat, locale: NumberLocale) -> String {
+    match mode {
+        NumberFormat::Off => text.to_string(),
+        NumberFormat::Digits => words_to_digits(text, locale),
+        NumberFormat::Words => digits_to_words(text, locale),
+    }
+}
+
+struct Token<'a> {
+    prefix: &'a str,
+    core: &'a str,
+    suffix: &'a str,
+}
+
+fn split_token(raw: &str) -> Token<'_> {
+    let start = raw.find(|c: char| c.is_alphanumeric()).unwrap_or(raw.len());
+    let end = raw
+        .rfind(|c: char| c.is_alphanumeric())
+        .map(|idx| idx + raw[idx..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(start);
+    Token {
+        prefix: &raw[..start],
+        core: &raw[start..end.max(start)],
+        suffix: &raw[end.max(start)..],
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WordKind {
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+}
+
+fn number_word(word: &str) -> Option<(WordKind, u64)> {
+    if let Some(idx) = UNITS.iter().position(|unit| *unit == word) {
+        let kind = if idx < 10 {
+            WordKind::Unit
+        } else {
+            WordKind::Teen
+        };
+        return Some((kind, idx as u64));
+    }
+    if let Some(idx) = TENS
+        .iter()
+        .position(|tens| !tens.is_empty() && *tens == word)
+    {
+        return Some((WordKind::Tens, idx as u64 * 10));
+    }
+    if word == "hundred" {
+        return Some((WordKind::Hundred, 100));
+    }
+    SCALES
+        .iter()
+        .find(|(name, _)| *name == word)
+        .map(|(_, value)| (WordKind::Scale, *value))
+}
+
+fn is_number_core(core: &str) -> bool {
+    !core.is_empty()
+        && core
+            .split('-')
+            .all(|part| number_word(&part.to_lowercase()).is_some())
+}
+
+/// Parses a run of number words into one or more groups. A new group starts
+/// whenever a word cannot extend the current number, so "twenty twenty five"
+/// yields `[20, 25]` and "three twenty" yields `[3, 20]`.
+fn parse_groups(words: &[String]) -> Option<Vec<u64>> {
+    let mut groups = Vec::new();
+    let mut total = 0_u64;
+    let mut current = 0_u64;
+    let mut last: Option<WordKind> = None;
+    // Scales within one number only get smaller ("million ... thousand").
+    let mut last_scale: Option<u64> = None;
+
+    for word in words {
+        let (kind, value) = number_word(word)?;
+        let starts_new_group = matches!(
+            (last, kind),
+            (
+                Some(WordKind::Unit | WordKind::Teen),
+                WordKind::Unit | WordKind::Teen | WordKind::Tens
+            ) | (Some(WordKind::Tens), WordKind::Teen | WordKind::Tens)
+        );
+
+        if starts_new_group {
+            groups.push(total + current);
+            total = 0;
+            current = 0;
+            last_scale = None;
+        }
+
+        // "hundred" and scale words only multiply a number said before them;
+        // alone or repeated ("thousand thousand") they are left as words.
+        match kind {
+            WordKind::Unit | WordKind::Teen | WordKind::Tens => current += value,
+            WordKind::Hundred => {
+                if current == 0 || current >= 100 {
+                    return None;
+                }
+                current *= 100;
+            }
+            WordKind::Scale => {
+                if current == 0 || last_scale.is_some_and(|scale| value >= scale) {
+                    return None;
+                }
+                total += current * value;
+                current = 0;
+                last_scale = Some(value);
+            }
+        }
+        last = Some(kind);
+    }
+
+    groups.push(total + current);
+    Some(groups)
+}
+
+/// Whether consecutive number groups spell out one string of digits: single
+/// digits ("five five five") or a year read in pairs ("nineteen eighty
+/// four"). "three twenty" is two numbers, not 320.
+fn is_digit_sequence(groups: &[u64]) -> bool {
+    groups.iter().all(|group| *group < 10)
+        || (groups.len() == 2 && groups.iter().all(|group| (10..100).contains(group)))
+}
+
+/// Reads "point" followed by single digit words ("point one four") starting at
+/// `start`. Returns the fraction digits and the index after the last one.
+fn spoken_fraction(tokens: &[Token], start: usize) -> Option<(String, usize)> {
+    let point = tokens.get(start)?;
+    if !point.prefix.is_empty()
+        || !point.suffix.is_empty()
+        || !point.core.eq_ignore_ascii_case("point")
+    {
+        return None;
+    }
+
+    let mut digits = String::new();
+    let mut idx = start + 1;
+    while let Some(token) = tokens.get(idx) {
+        if !token.prefix.is_empty() {
+            break;
+        }
+        match number_word(&token.core.to_lowercase()) {
+            Some((WordKind::Unit, value)) => digits.push_str(&value.to_string()),
+            _ => break,
+        }
+        idx += 1;
+        if !token.suffix.is_empty() {
+            break;
+        }
+    }
+    (!digits.is_empty()).then_some((digits, idx))
+}
+
+fn words_to_digits(text: &str, locale: NumberLocale) -> String {
+    let raw_tokens: Vec<&str> = text.split_whitespace().collect();
+    let tokens: Vec<Token> = raw_tokens.iter().map(|raw| split_token(raw)).collect();
+    let mut out: Vec<String> = Vec::with_capacity(tokens.len());
+    let mut idx = 0;
+
+    while idx < tokens.len() {
+        let mut end = idx;
+        let mut words: Vec<String> = Vec::new();
+        while end < tokens.len() {
+            let token = &tokens[end];
+            if end > idx && !token.prefix.is_empty() {
+                break;
+            }
+            let lower = token.core.to_lowercase();
+            let joins_run = lower == "and"
+                && end > idx
+                && matches!(
+                    words.last().and_then(|w| number_word(w)),
+                    Some((WordKind::Hundred | WordKind::Scale, _))
+                )
+                && tokens
+                    .get(end + 1)
+                    .is_some_and(|next| next.prefix.is_empty() && is_number_core(next.core));
+            if joins_run {
+                end += 1;
+                continue;
+            }
+            if !is_number_core(token.core) {
+                break;
+            }
+            words.extend(lower.split('-').map(str::to_string));
+            end += 1;
+            if !token.suffix.is_empty() {
+                break;
+            }
+        }
+
+        let groups = if words.is_empty() {
+            None
+        } else {
+            parse_groups(&words)
+        };
+        let fraction = match &groups {
+            Some(groups) if groups.len() == 1 && tokens[end - 1].suffix.is_empty() => {
+                spoken_fraction(&tokens, end)
+            }
+            _ => None,
+        };
+        let converted = groups.filter(|groups| {
+            // Leave lone small numbers as words; "one" and "two" are often
+            // not quantities in dictated prose.
+            fraction.is_some()
+                || words.len() > 1
+                || groups.first().is_some_and(|value| *value >= 10)
+        });
+
+        match converted {
+            Some(groups) => {
+                let digits = match (groups.as_slice(), fraction) {
+                    ([value], Some((fraction, next))) => {
+                        end = next;
+                        // Anything with a fraction is a quantity, never a year.
+                        let whole = if *value >= 1_000 {
+                            locale.group(*value)
+                        } else {
+                            value.to_string()
+                        };
+                        format!("{whole}{}{fraction}", locale.decimal)
+                    }
+                    ([value], None) if *value >= MIN_GROUPED_VALUE => locale.group(*value),
+                    // Digit sequences like phone numbers and years read as one
+                    // number; they are never grouped.
+                    _ if is_digit_sequence(&groups) => groups.iter().map(u64::to_string).collect(),
+                    // Anything else is several numbers said in a row.
+                    _ => groups
+                        .iter()
+                        .map(u64::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                };
+                let last = &tokens[end - 1];
+                out.push(format!("{}{}{}", tokens[idx].prefix, digits, last.suffix));
+                idx = end;
+            }
+            None => {
+                out.push(raw_tokens[idx].to_string());
+                idx += 1;
+            }
+        }
+    }
+
+    out.join(" ")
+}
+
+fn parse_plain_integer(core: &str, locale: NumberLocale) -> Option<u64> {
+    let digits: String = core.chars().filter(|c| *c != locale.group).collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    // Leading zeros usually mean codes, PINs, or zip codes rather than quantities.
+    if digits.is_empty() || (digits.len() > 1 && digits.starts_with('0')) {
+        return None;
+    }
+    let value: u64 = digits.parse().ok()?;
+    // Separators must sit exactly where this locale puts them; "1,5" is a
+    // decimal in some locales and a typo in others, never one thousand.
+    if digits.len() != core.len() && locale.group(value) != core {
+        return None;
+    }
+    Some(value).filter(|value| *value <= MAX_WORDS_VALUE)
+}
+
+fn digits_to_words(text: &str, locale: NumberLocale) -> String {
+    text.split_whitespace()
+        .map(|raw| {
+            let token = split_token(raw);
+            match parse_plain_integer(token.core, locale) {
+                Some(value) => format!("{}{}{}", token.prefix, spell_number(value), token.suffix),
+                None => raw.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn spell_below_thousand(value: u64) -> String {
+    let mut parts = Vec::new();
+    let hundreds = value / 100;
+    let rest = value % 100;
+    if hundreds > 0 {
+        parts.push(format!("{} hundred", UNITS[hundreds as usize]));
+    }
+    if rest > 0 {
+        if rest < 20 {
+            parts.push(UNITS[rest as usize].to_string());
+        } else if rest.is_multiple_of(10) {
+            parts.push(TENS[(rest / 10) as usize].to_string());
+        } else {
+            parts.push(format!(
+                "{}-{}",
+                TENS[(rest / 10) as usize],
+                UNITS[(rest % 10) as usize]
+            ));
+        }
+    }
+    parts.join(" ")
+}
+
+fn spell_number(value: u64) -> String {
+    if value == 0 {
+        return UNITS[0].to_string();
+    }
+
+    let mut parts = Vec::new();
+    let mut remaining = value;
+    for (name, scale) in SCALES.iter().rev() {
+        let count = remaining / scale;
+        if count > 0 {
+            parts.push(format!("{} {}", spell_below_thousand(count), name));
+            remaining %= scale;
+        }
+    }
+    if remaining > 0 {
+        parts.push(spell_below_thousand(remaining));
+    }
+    parts.join(" ")
+}
+
+/// Shortens `text` to at most `max_chars` characters for notifications,
+/// tooltips, and other previews. Whitespace runs collapse to single spaces and
+/// cuts land on `char` boundaries, ending in an ellipsis when anything was
+/// dropped. Never byte-slice transcription text for display; use this instead.
+pub fn truncate_preview(text: &str, max_chars: usize) -> String {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.chars().count() <= max_chars {
+        return collapsed;
+    }
+    if max_chars == 0 {
+        return String::new();
+    }
+
+    let mut preview: String = collapsed.chars().take(max_chars - 1).collect();
+    preview.truncate(preview.trim_end().len());
+    preview.push('…');
+    preview
+}
+
+/// Wraps a transcription for rich-text paste: the text as paragraphs, followed
+/// by a small italic attribution line.
+pub fn transcription_html(text: &str, created_at: &str, model: &str) -> String {
+    let paragraphs = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("<p>{}</p>", escape_html(line)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "{paragraphs}\n<p><em><small>Dictated {} with {}</small></em></p>",
+        escape_html(created_at),
+        escape_html(model)
+    )
+}
+
+/// Fills a live-output template. `{text}`, `{model}`, `{duration_ms}` and
+/// `{id}` are replaced; anything else is copied through.
+pub fn render_live_output(
+    template: &str,
+    id: i64,
+    text: &str,
+    model: &str,
+    duration_ms: i64,
+) -> String {
+    template
+        .replace("{model}", model)
+        .replace("{duration_ms}", &duration_ms.to_string())
+        .replace("{id}", &id.to_string())
+        // Last, so braces inside the transcription are never expanded.
+        .replace("{text}", text)
+}
+
+/// Writes one line per segment, each prefixed with its start time, e.g.
+/// `[00:03] Let's get started.`
+pub fn timestamped_text<'a>(
+    segments: impl IntoIterator<Item = (i64, &'a str)>,
+    format: TimestampFormat,
+) -> String {
+    segments
+        .into_iter()
+        .map(|(start_ms, text)| format!("[{}] {text}", format_timestamp(start_ms, format)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Interleaves two speakers' `(start_ms, text)` segments by start time and
+/// writes one `Label: text` line per turn. Consecutive segments from the same
+/// speaker are joined into a single turn.
+pub fn speaker_turns_text<'a>(
+    first: impl IntoIterator<Item = (i64, &'a str)>,
+    second: impl IntoIterator<Item = (i64, &'a str)>,
+    labels: [&str; 2],
+) -> String {
+    let mut segments: Vec<(i64, usize, &str)> = first
+        .into_iter()
+        .map(|(start_ms, text)| (start_ms, 0, text))
+        .chain(
+            second
+                .into_iter()
+                .map(|(start_ms, text)| (start_ms, 1, text)),
+        )
+        .filter(|(_, _, text)| !text.trim().is_empty())
+        .collect();
+    // Stable, so a tie keeps the first speaker first.
+    segments.sort_by_key(|(start_ms, _, _)| *start_ms);
+
+    let mut turns: Vec<(usize, String)> = Vec::new();
+    for (_, speaker, text) in segments {
+        match turns.last_mut() {
+            Some((last, turn)) if *last == speaker => {
+                turn.push(' ');
+                turn.push_str(text.trim());
+            }
+            _ => turns.push((speaker, text.trim().to_string())),
+        }
+    }
+
+    turns
+        .into_iter()
+        .map(|(speaker, turn)| format!("{}: {turn}", labels[speaker]))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_timestamp(ms: i64, format: TimestampFormat) -> String {
+    let total_secs = ms.max(0) / 1000;
+    match format {
+        TimestampFormat::MinutesSeconds => {
+            format!("{:02}:{:02}", total_secs / 60, total_secs % 60)
+        }
+        TimestampFormat::HoursMinutesSeconds => format!(
+            "{:02}:{:02}:{:02}",
+            total_secs / 3600,
+            (total_secs % 3600) / 60,
+            total_secs % 60
+        ),
+    }
+}
+
+fn escape_html(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for ch in raw.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_to_digits_handles_years_and_compounds() {
+        assert_eq!(
+            format_numbers(
+                "In twenty twenty five we shipped.",
+                NumberFormat::Digits,
+                NumberLocale::default()
+            ),
+            "In 2025 we shipped."
+        );
+        assert_eq!(
+            format_numbers(
+                "It costs three hundred and forty-two dollars",
+                NumberFormat::Digits,
+                NumberLocale::default()
+            ),
+            "It costs 342 dollars"
+        );
+        assert_eq!(
+            format_numbers(
+                "Call five five five, one two one two.",
+                NumberFormat::Digits,
+                NumberLocale::default()
+            ),
+            "Call 555, 1212."
+        );
+        assert_eq!(
+            format_numbers(
+                "Bring three twenty dollar bills, not twenty thirty forty.",
+                NumberFormat::Digits,
+                NumberLocale::default()
+            ),
+            "Bring 3 20 dollar bills, not 20 30 40."
+        );
+    }
+
+    #[test]
+    fn words_to_digits_leaves_lone_small_numbers() {
+        assert_eq!(
+            format_numbers(
+                "That one is better than two of them",
+                NumberFormat::Digits,
+                NumberLocale::default()
+            ),
+            "That one is better than two of them"
+        );
+        assert_eq!(
+            format_numbers(
+                "We waited fifteen minutes",
+                NumberFormat::Digits,
+                NumberLocale::default()
+            ),
+            "We waited 15 minutes"
+        );
+    }
+
+    #[test]
+    fn words_to_digits_needs_a_number_before_scale_words() {
+        for text in [
+            "A hundred people came",
+            "Thousand thousand thanks",
+            "It took a million years",
+            "Hundred hundred",
+        ] {
+            assert_eq!(
+                format_numbers(text, NumberFormat::Digits, NumberLocale::default()),
+                text
+            );
+        }
+        assert_eq!(
+            format_numbers(
+                "Three hundred thousand fans, one million two thousand views",
+                NumberFormat::Digits,
+                NumberLocale::default()
+            ),
+            "300,000 fans, 1,002,000 views"
+        );
+    }
+
+    #[test]
+    fn digits_to_words_skips_codes() {
+        assert_eq!(
+            format_numbers(
+                "I have 3 cats and 1,250 dollars.",
+                NumberFormat::Words,
+                NumberLocale::default()
+            ),
+            "I have three cats and one thousand two hundred fifty dollars."
+        );
+        assert_eq!(
+            format_numbers(
+                "Zip 02139 stays",
+                NumberFormat::Words,
+                NumberLocale::default()
+            ),
+            "Zip 02139 stays"
+        );
+    }
+
+    #[test]
+    fn number_formatting_follows_locale_separators() {
+        let german = NumberLocale::for_tag("de_DE.UTF-8");
+        let english = NumberLocale::for_tag("en-US");
+        let spoken = "It weighs one thousand point five kilos and costs twelve thousand";
+
+        assert_eq!(
+            format_numbers(spoken, NumberFormat::Digits, english),
+            "It weighs 1,000.5 kilos and costs 12,000"
+        );
+        assert_eq!(
+            format_numbers(spoken, NumberFormat::Digits, german),
+            "It weighs 1.000,5 kilos and costs 12.000"
+        );
+        assert_eq!(
+            format_numbers(
+                "three point one four and two thousand",
+                NumberFormat::Digits,
+                german
+            ),
+            "3,14 and 2000"
+        );
+        assert_eq!(
+            format_numbers("Sold 1.250 units at 3,5", NumberFormat::Words, german),
+            "Sold one thousand two hundred fifty units at 3,5"
+        );
+        assert_eq!(
+            format_numbers("Sold 1.250 units", NumberFormat::Words, english),
+            "Sold 1.250 units"
+        );
+    }
+
+    #[test]
+    fn number_locale_covers_regional_and_indian_grouping() {
+        let spoken = "one million two hundred thirty four thousand five hundred sixty seven";
+        assert_eq!(
+            format_numbers(spoken, NumberFormat::Digits, NumberLocale::for_tag("en-IN")),
+            "12,34,567"
+        );
+        assert_eq!(
+            format_numbers(spoken, NumberFormat::Digits, NumberLocale::for_tag("de-CH")),
+            "1\u{2019}234\u{2019}567"
+        );
+        assert_eq!(
+            format_numbers(spoken, NumberFormat::Digits, NumberLocale::for_tag("fr-FR")),
+            "1\u{202f}234\u{202f}567"
+        );
+        assert_eq!(
+            NumberLocale::for_tag("es-MX"),
+            NumberLocale::for_tag("en-US")
+        );
+
+        assert_eq!(
+            canonical_locale_tag("pt_BR.UTF-8").as_deref(),
+            Some("pt-BR")
+        );
+        assert_eq!(
+            canonical_locale_tag("zh-hant-tw").as_deref(),
+            Some("zh-Hant-TW")
+        );
+        assert_eq!(canonical_locale_tag("C").as_deref(), Some("en-US"));
+        assert_eq!(canonical_locale_tag("not a locale"), None);
+    }
+
+    #[test]
+    fn timestamped_text_prefixes_each_segment() {
+        let segments = [(3_400, "Let's get started."), (3_725_000, "Wrapping up.")];
+
+        assert_eq!(
+            timestamped_text(segments, TimestampFormat::MinutesSeconds),
+            "[00:03] Let's get started.\n[62:05] Wrapping up."
+        );
+        assert_eq!(
+            timestamped_text(segments, TimestampFormat::HoursMinutesSeconds),
+            "[00:00:03] Let's get started.\n[01:02:05] Wrapping up."
+        );
+    }
+
+    #[test]
+    fn speaker_turns_interleave_by_start_and_merge_runs() {
+        let host = [
+            (0, "Thanks for coming in."),
+            (1_500, "Let's start."),
+            (9_000, "Great."),
+        ];
+        let guest = [(4_000, "Happy to be here."), (9_000, "  ")];
+
+        assert_eq!(
+            speaker_turns_text(host, guest, ["Host", "Guest"]),
+            "Host: Thanks for coming in. Let's start.\nGuest: Happy to be here.\nHost: Great."
+        );
+    }
+
+    #[test]
+    fn truncate_preview_respects_char_boundaries() {
+        assert_eq!(truncate_preview("  hello\n world ", 20), "hello world");
+        assert_eq!(truncate_preview("héllo wörld", 6), "héllo…");
+        assert_eq!(truncate_preview("ab😀😀cd", 4), "ab😀…");
+        assert_eq!(truncate_preview("日本語のテキスト", 3), "日本…");
+        assert_eq!(truncate_preview("👍🏽👍🏽", 2), "👍…");
+        assert_eq!(truncate_preview("anything", 0), "");
+    }
+
+    #[test]
+    fn transcription_html_escapes_text_and_attribution() {
+        let html = transcription_html(
+            "a < b & \"c\"\n\nnext",
+            "2026-01-02 03:04:05",
+            "ggml-base.en.bin",
+        );
+        assert_eq!(
+            html,
+            "<p>a &lt; b &amp; &quot;c&quot;</p>\n<p>next</p>\n<p><em><small>Dictated 2026-01-02 03:04:05 with ggml-base.en.bin</small></em></p>"
+        );
+    }
+
+    #[test]
+    fn render_live_output_fills_placeholders_once() {
+        assert_eq!(
+            render_live_output(
+                "[{id}] {text} ({model}, {duration_ms} ms)",
+                7,
+                "hi {model}",
+                "base",
+                1200
+            ),
+            "[7] hi {model} (base, 1200 ms)"
+        );
+    }
+}
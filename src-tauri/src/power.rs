@@ -0,0 +1,142 @@
+use serde::Serialize;
+use std::thread;
+use std::time::Duration;
+
+/// How often the power watcher re-reads the power source.
+const POWER_POLL_INTERVAL: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+}
+
+/// The machine's current power source, or `None` when it has no battery or the
+/// platform does not report one.
+pub fn current() -> Option<PowerSource> {
+    #[cfg(target_os = "macos")]
+    {
+        let output = std::process::Command::new("pmset")
+            .args(["-g", "batt"])
+            .output()
+            .ok()?;
+        parse_pmset(&String::from_utf8_lossy(&output.stdout))
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let mut status = win::SystemPowerStatus::default();
+        if unsafe { win::GetSystemPowerStatus(&mut status) } == 0 {
+            return None;
+        }
+        if status.battery_flag == win::BATTERY_FLAG_NO_BATTERY {
+            return None;
+        }
+        match status.ac_line_status {
+            0 => Some(PowerSource::Battery),
+            1 => Some(PowerSource::Ac),
+            _ => None,
+        }
+    }
+    #[cfg(target_os = "linux")]
+    {
+        linux_power_source(std::path::Path::new("/sys/class/power_supply"))
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    None
+}
+
+/// Polls the power source in the background and calls `on_change` whenever it
+/// differs from the last one handled, including once at startup. Returning
+/// `false` means "not now" (e.g. mid-recording); the same source is offered
+/// again on the next poll.
+pub fn spawn_watcher<F>(mut on_change: F)
+where
+    F: FnMut(PowerSource) -> bool + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut handled = None;
+        loop {
+            if let Some(source) = current() {
+                if handled != Some(source) && on_change(source) {
+                    handled = Some(source);
+                }
+            }
+            thread::sleep(POWER_POLL_INTERVAL);
+        }
+    });
+}
+
+#[cfg(any(target_os = "macos", test))]
+fn parse_pmset(output: &str) -> Option<PowerSource> {
+    let first_line = output.lines().next()?;
+    if first_line.contains("'AC Power'") {
+        Some(PowerSource::Ac)
+    } else if first_line.contains("'Battery Power'") {
+        Some(PowerSource::Battery)
+    } else {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn linux_power_source(supply_dir: &std::path::Path) -> Option<PowerSource> {
+    let read = |path: std::path::PathBuf| {
+        std::fs::read_to_string(path)
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut has_battery = false;
+    for entry in std::fs::read_dir(supply_dir).ok()?.flatten() {
+        let supply = entry.path();
+        if read(supply.join("type")) != "Battery" {
+            continue;
+        }
+        has_battery = true;
+        if read(supply.join("status")) == "Discharging" {
+            return Some(PowerSource::Battery);
+        }
+    }
+    has_battery.then_some(PowerSource::Ac)
+}
+
+#[cfg(target_os = "windows")]
+mod win {
+    pub const BATTERY_FLAG_NO_BATTERY: u8 = 128;
+
+    /// Mirrors the Win32 `SYSTEM_POWER_STATUS` layout; only the first two
+    /// fields are read.
+    #[repr(C)]
+    #[derive(Default)]
+    #[allow(dead_code)]
+    pub struct SystemPowerStatus {
+        pub ac_line_status: u8,
+        pub battery_flag: u8,
+        pub battery_life_percent: u8,
+        pub system_status_flag: u8,
+        pub battery_life_time: u32,
+        pub battery_full_life_time: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pmset_reads_power_source_line() {
+        let battery =
+            "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=123)\t87%; discharging;";
+        let ac = "Now drawing from 'AC Power'\n -InternalBattery-0 (id=123)\t100%; charged;";
+
+        assert_eq!(parse_pmset(battery), Some(PowerSource::Battery));
+        assert_eq!(parse_pmset(ac), Some(PowerSource::Ac));
+        assert_eq!(parse_pmset(""), None);
+    }
+}
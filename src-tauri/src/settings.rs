@@ -1,3 +1,9 @@
+use crate::audio;
+use crate::db;
+use crate::logging::LogLevel;
+use crate::postprocess::{self, NumberFormat, NumberLocale, TimestampFormat};
+use crate::power::PowerSource;
+use crate::whisper::{DecodeParams, LatencyPriority, TranscriptionMode, MAX_INITIAL_PROMPT_CHARS};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -6,6 +12,25 @@ use tauri_plugin_global_shortcut::{Modifiers, Shortcut};
 
 pub const DEFAULT_HOTKEY: &str = "control+shift+KeyS";
 pub const DEFAULT_AUTO_COPY: bool = false;
+pub const DEFAULT_QUICK_NOTE_SECONDS: u32 = 10;
+pub const MIN_QUICK_NOTE_SECONDS: u32 = 3;
+pub const MAX_QUICK_NOTE_SECONDS: u32 = 30;
+pub const DEFAULT_MAX_RECORDING_SECONDS: u32 = 30;
+pub const MIN_MAX_RECORDING_SECONDS: u32 = 5;
+pub const MAX_MAX_RECORDING_SECONDS: u32 = 600;
+pub const DEFAULT_MIN_RECORDING_MS: u32 = 200;
+pub const MIN_MIN_RECORDING_MS: u32 = 100;
+pub const MAX_MIN_RECORDING_MS: u32 = 2_000;
+pub const DEFAULT_MAX_PAUSE_MS: u32 = 800;
+pub const MIN_MAX_PAUSE_MS: u32 = 200;
+pub const MAX_MAX_PAUSE_MS: u32 = 5_000;
+pub const DEFAULT_SILENCE_TIMEOUT_MS: u32 = 2_000;
+pub const MIN_SILENCE_TIMEOUT_MS: u32 = 500;
+pub const MAX_SILENCE_TIMEOUT_MS: u32 = 10_000;
+pub const DEFAULT_MIN_SPEECH_MS: u32 = 600;
+pub const MAX_MIN_SPEECH_MS: u32 = 5_000;
+pub const DEFAULT_LIVE_OUTPUT_TEMPLATE: &str = "{text}";
+pub const DEFAULT_LIVE_OUTPUT_SEPARATOR: &str = "\n";
 
 fn default_hotkey() -> String {
     DEFAULT_HOTKEY.to_string()
@@ -15,37 +40,656 @@ fn default_auto_copy() -> bool {
     DEFAULT_AUTO_COPY
 }
 
+fn default_db_synchronous() -> db::SyncMode {
+    db::SyncMode::Full
+}
+
+fn default_db_busy_timeout_ms() -> u64 {
+    db::DEFAULT_BUSY_TIMEOUT_MS
+}
+
+fn default_quick_note_seconds() -> u32 {
+    DEFAULT_QUICK_NOTE_SECONDS
+}
+
+fn default_max_recording_seconds() -> u32 {
+    DEFAULT_MAX_RECORDING_SECONDS
+}
+
+fn default_capture_warmup_ms() -> u32 {
+    audio::DEFAULT_CAPTURE_WARMUP_MS
+}
+
+fn default_min_recording_ms() -> u32 {
+    DEFAULT_MIN_RECORDING_MS
+}
+
+fn default_use_gpu() -> bool {
+    true
+}
+
+fn default_preload_model() -> bool {
+    true
+}
+
+/// Where each finished recording is delivered. The file variants overwrite the
+/// named file on every transcription so other tools can watch it; `Both` also
+/// keeps the clipboard behavior.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputTarget {
+    #[default]
+    Clipboard,
+    File(String),
+    Both(String),
+}
+
+impl OutputTarget {
+    pub fn file(&self) -> Option<&Path> {
+        match self {
+            OutputTarget::Clipboard => None,
+            OutputTarget::File(path) | OutputTarget::Both(path) => Some(Path::new(path)),
+        }
+    }
+
+    pub fn uses_clipboard(&self) -> bool {
+        !matches!(self, OutputTarget::File(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimestampOutput {
+    pub enabled: bool,
+    pub format: TimestampFormat,
+}
+
+/// How the recording hotkey behaves.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyMode {
+    /// Press once to start, again to stop.
+    #[default]
+    Toggle,
+    /// Record while the hotkey is held; releasing it stops.
+    PushToTalk,
+}
+
+/// How auto-paste and stream typing fit text into the focused field.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteMode {
+    /// Insert the text as transcribed.
+    #[default]
+    Insert,
+    /// Start with a space unless the text before the cursor already ends in
+    /// whitespace, so consecutive dictations do not run together.
+    AppendWithSpace,
+    /// Insert the text as a fragment over a selected word or phrase: the
+    /// closing period whisper adds to a single sentence is dropped.
+    ReplaceSelection,
+}
+
+impl PasteMode {
+    /// `text` as it should be inserted. `previous` is the character before
+    /// the cursor as far as Murmur knows: the last one it inserted. Murmur
+    /// cannot read other apps' fields, so this is a best effort.
+    pub fn fit(self, previous: Option<char>, text: &str) -> String {
+        match self {
+            PasteMode::Insert => text.to_string(),
+            PasteMode::AppendWithSpace => {
+                let needs_space = previous.is_some_and(|ch| !ch.is_whitespace())
+                    && text.starts_with(|ch: char| !ch.is_whitespace());
+                if needs_space {
+                    format!(" {text}")
+                } else {
+                    text.to_string()
+                }
+            }
+            PasteMode::ReplaceSelection => {
+                let trimmed = text.trim();
+                match trimmed.strip_suffix('.') {
+                    Some(fragment) if !fragment.contains(['.', '!', '?']) => fragment.to_string(),
+                    _ => trimmed.to_string(),
+                }
+            }
+        }
+    }
+}
+
+/// What happens to a capture shorter than `min_recording_ms`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ShortRecordingPolicy {
+    /// Report it as an error.
+    #[default]
+    Error,
+    /// Drop it without a message.
+    Ignore,
+    /// Transcribe it anyway; very short audio is padded with silence.
+    Transcribe,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShortRecording {
+    pub min_ms: u32,
+    pub policy: ShortRecordingPolicy,
+}
+
+fn default_live_output_template() -> String {
+    DEFAULT_LIVE_OUTPUT_TEMPLATE.to_string()
+}
+
+fn default_live_output_separator() -> String {
+    DEFAULT_LIVE_OUTPUT_SEPARATOR.to_string()
+}
+
+/// Where and how finished transcriptions are appended for other apps (OBS
+/// text sources, running notes) to pick up.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LiveOutput {
+    pub file: Option<String>,
+    pub template: String,
+    pub separator: String,
+}
+
+/// Last main-window placement in physical pixels: outer position, inner size.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Models to switch to when the machine moves between wall power and battery.
+/// Unset entries leave the active model alone.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PowerModels {
+    #[serde(default)]
+    pub ac: Option<String>,
+    #[serde(default)]
+    pub battery: Option<String>,
+}
+
+impl PowerModels {
+    pub fn for_source(&self, source: PowerSource) -> Option<&str> {
+        match source {
+            PowerSource::Ac => self.ac.as_deref(),
+            PowerSource::Battery => self.battery.as_deref(),
+        }
+    }
+}
+
+/// Channel-based speaker attribution for dual-mic recordings: each microphone
+/// is transcribed on its own and the turns are labeled.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SpeakerTurns {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Labels for the primary and secondary microphone.
+    #[serde(default = "default_speaker_labels")]
+    pub labels: [String; 2],
+}
+
+impl Default for SpeakerTurns {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            labels: default_speaker_labels(),
+        }
+    }
+}
+
+fn default_speaker_labels() -> [String; 2] {
+    ["Speaker A".to_string(), "Speaker B".to_string()]
+}
+
+/// Shortening of long pauses in a recording before it is transcribed; see
+/// `audio::trim_long_pauses`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PauseTrim {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Pauses longer than this are cut down to this length.
+    #[serde(default = "default_max_pause_ms")]
+    pub max_pause_ms: u32,
+}
+
+impl Default for PauseTrim {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_pause_ms: default_max_pause_ms(),
+        }
+    }
+}
+
+fn default_max_pause_ms() -> u32 {
+    DEFAULT_MAX_PAUSE_MS
+}
+
+/// Sample levels that count as activity, kept apart for the live meter and
+/// for decisions about a recording; see `audio::analyze_signal`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ActivityThresholds {
+    #[serde(default = "default_meter_activity_threshold")]
+    pub meter: f32,
+    #[serde(default = "default_decision_activity_threshold")]
+    pub decision: f32,
+}
+
+impl Default for ActivityThresholds {
+    fn default() -> Self {
+        Self {
+            meter: default_meter_activity_threshold(),
+            decision: default_decision_activity_threshold(),
+        }
+    }
+}
+
+impl ActivityThresholds {
+    fn clamped(self) -> Self {
+        let clamp = |value: f32, default: f32| {
+            if value.is_finite() {
+                value.clamp(audio::MIN_ACTIVITY_THRESHOLD, audio::MAX_ACTIVITY_THRESHOLD)
+            } else {
+                default
+            }
+        };
+        Self {
+            meter: clamp(self.meter, default_meter_activity_threshold()),
+            decision: clamp(self.decision, default_decision_activity_threshold()),
+        }
+    }
+}
+
+fn default_meter_activity_threshold() -> f32 {
+    audio::DEFAULT_METER_ACTIVITY_THRESHOLD
+}
+
+fn default_decision_activity_threshold() -> f32 {
+    audio::DEFAULT_DECISION_ACTIVITY_THRESHOLD
+}
+
+/// Ending a recording on its own once the speaker has gone quiet.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AutoStop {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Trailing silence that stops the recording.
+    #[serde(default = "default_silence_timeout_ms")]
+    pub silence_timeout_ms: u32,
+    /// Speech needed before a silence counts, so a pause to think right
+    /// after starting does not end the recording.
+    #[serde(default = "default_min_speech_ms")]
+    pub min_speech_ms: u32,
+}
+
+impl Default for AutoStop {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            silence_timeout_ms: default_silence_timeout_ms(),
+            min_speech_ms: default_min_speech_ms(),
+        }
+    }
+}
+
+impl AutoStop {
+    fn clamped(self) -> Self {
+        Self {
+            silence_timeout_ms: self
+                .silence_timeout_ms
+                .clamp(MIN_SILENCE_TIMEOUT_MS, MAX_SILENCE_TIMEOUT_MS),
+            min_speech_ms: self.min_speech_ms.min(MAX_MIN_SPEECH_MS),
+            ..self
+        }
+    }
+}
+
+fn default_silence_timeout_ms() -> u32 {
+    DEFAULT_SILENCE_TIMEOUT_MS
+}
+
+fn default_min_speech_ms() -> u32 {
+    DEFAULT_MIN_SPEECH_MS
+}
+
+/// A monitor's full area in physical pixels, taskbar and menu bar included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl WindowGeometry {
+    /// Moves and shrinks the window onto the monitor it overlaps most. Returns
+    /// `None` when it overlaps none of them, e.g. after a display was unplugged.
+    pub fn clamped_to(&self, monitors: &[MonitorBounds]) -> Option<WindowGeometry> {
+        let overlap = |monitor: &MonitorBounds| -> i64 {
+            let left = self.x.max(monitor.x) as i64;
+            let top = self.y.max(monitor.y) as i64;
+            let right =
+                (self.x as i64 + self.width as i64).min(monitor.x as i64 + monitor.width as i64);
+            let bottom =
+                (self.y as i64 + self.height as i64).min(monitor.y as i64 + monitor.height as i64);
+            (right - left).max(0) * (bottom - top).max(0)
+        };
+
+        let monitor = monitors
+            .iter()
+            .filter(|monitor| overlap(monitor) > 0)
+            .max_by_key(|monitor| overlap(monitor))?;
+
+        let width = self.width.min(monitor.width);
+        let height = self.height.min(monitor.height);
+        let max_x = monitor.x as i64 + (monitor.width - width) as i64;
+        let max_y = monitor.y as i64 + (monitor.height - height) as i64;
+        Some(WindowGeometry {
+            x: (self.x as i64).clamp(monitor.x as i64, max_x) as i32,
+            y: (self.y as i64).clamp(monitor.y as i64, max_y) as i32,
+            width,
+            height,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
     #[serde(default = "default_hotkey")]
     pub hotkey: String,
+    #[serde(default)]
+    pub hotkey_mode: HotkeyMode,
     #[serde(default = "default_auto_copy")]
     pub auto_copy: bool,
+    /// Paste each transcription into the focused app. Needs Accessibility
+    /// access on macOS.
+    #[serde(default)]
+    pub auto_paste: bool,
+    /// Type each transcription into the focused app as keystrokes instead of
+    /// pasting it. Continuous dictation types each utterance as it is
+    /// finalized. Needs the same access as auto-paste.
+    #[serde(default)]
+    pub stream_typing: bool,
+    #[serde(default)]
+    pub paste_mode: PasteMode,
+    /// Save each recording's audio next to its history row so it can be
+    /// re-transcribed later. Costs about 1.9 MB per minute.
+    #[serde(default)]
+    pub keep_audio: bool,
     #[serde(default)]
     pub active_model: Option<String>,
+    #[serde(default)]
+    pub window_toggle_hotkey: Option<String>,
+    #[serde(default)]
+    pub number_format: NumberFormat,
+    /// Locale tag whose separators formatted numbers use; `None` follows the
+    /// system locale.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Prefix each segment with its start time in stored and copied text.
+    #[serde(default)]
+    pub timestamps_in_text: bool,
+    #[serde(default)]
+    pub timestamp_format: TimestampFormat,
+    #[serde(default = "default_db_synchronous")]
+    pub db_synchronous: db::SyncMode,
+    #[serde(default = "default_db_busy_timeout_ms")]
+    pub db_busy_timeout_ms: u64,
+    #[serde(default = "default_capture_warmup_ms")]
+    pub capture_warmup_ms: u32,
+    #[serde(default)]
+    pub capture_buffer_size: audio::CaptureBufferSize,
+    /// Microphone to record from, by device name; `None` uses the system
+    /// default input.
+    #[serde(default)]
+    pub input_device: Option<String>,
+    #[serde(default)]
+    pub dual_mic_mode: audio::DualMicMode,
+    #[serde(default)]
+    pub dual_mic_primary: Option<String>,
+    #[serde(default)]
+    pub dual_mic_secondary: Option<String>,
+    #[serde(default)]
+    pub speaker_turns: SpeakerTurns,
+    #[serde(default)]
+    pub pause_trim: PauseTrim,
+    #[serde(default)]
+    pub auto_stop: AutoStop,
+    #[serde(default)]
+    pub activity_thresholds: ActivityThresholds,
+    #[serde(default)]
+    pub log_level: LogLevel,
+    #[serde(default)]
+    pub transcription_mode: TranscriptionMode,
+    #[serde(default)]
+    pub latency_priority: LatencyPriority,
+    /// Language to decode as: an ISO 639-1 code or "auto". Unset means
+    /// English first. See `whisper::Language`.
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Vocabulary whisper is primed with, e.g. "Kubernetes, Grafana". Long
+    /// prompts take room from the transcript in each 30 s window, so it is
+    /// capped at `MAX_INITIAL_PROMPT_CHARS`.
+    #[serde(default)]
+    pub initial_prompt: Option<String>,
+    /// Run whisper on the GPU when the build has a backend for it. A model
+    /// that fails to load there falls back to the CPU.
+    #[serde(default = "default_use_gpu")]
+    pub use_gpu: bool,
+    /// Load the active model in the background at startup and after a model
+    /// switch. Off keeps it out of memory until the first transcription.
+    #[serde(default = "default_preload_model")]
+    pub preload_model: bool,
+    /// Hand-tuned decode parameters. When set, `latency_priority` is ignored.
+    #[serde(default)]
+    pub decode_override: Option<DecodeParams>,
+    /// Decode reproducibly for bug reports: fixed threads, no sampling.
+    #[serde(default)]
+    pub deterministic: bool,
+    #[serde(default)]
+    pub window_geometry: Option<WindowGeometry>,
+    #[serde(default = "default_quick_note_seconds")]
+    pub quick_note_seconds: u32,
+    /// Recordings stop capturing after this long; the rest is dropped.
+    #[serde(default = "default_max_recording_seconds")]
+    pub max_recording_seconds: u32,
+    #[serde(default)]
+    pub low_priority: bool,
+    /// Proxy or cache tried before the built-in model URLs, serving
+    /// `<prefix>/<file_name>`.
+    #[serde(default)]
+    pub model_mirror_prefix: Option<String>,
+    #[serde(default)]
+    pub power_models: PowerModels,
+    /// Absolute path that every finished transcription is appended to.
+    #[serde(default)]
+    pub live_output_file: Option<String>,
+    #[serde(default = "default_live_output_template")]
+    pub live_output_template: String,
+    #[serde(default = "default_live_output_separator")]
+    pub live_output_separator: String,
+    #[serde(default = "default_min_recording_ms")]
+    pub min_recording_ms: u32,
+    #[serde(default)]
+    pub short_recording_policy: ShortRecordingPolicy,
+    #[serde(default)]
+    pub output_target: OutputTarget,
+    /// Emit a per-stage timing breakdown after every transcription.
+    #[serde(default)]
+    pub debug_mode: bool,
+    /// Keep only this many of the newest transcriptions; `None` keeps all.
+    #[serde(default)]
+    pub max_history_rows: Option<u32>,
+    /// Delete transcriptions older than this many days; `None` keeps them
+    /// forever.
+    #[serde(default)]
+    pub history_retention_days: Option<u32>,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
             hotkey: DEFAULT_HOTKEY.to_string(),
+            hotkey_mode: HotkeyMode::default(),
             auto_copy: DEFAULT_AUTO_COPY,
+            auto_paste: false,
+            stream_typing: false,
+            paste_mode: PasteMode::default(),
+            keep_audio: false,
             active_model: None,
+            window_toggle_hotkey: None,
+            number_format: NumberFormat::default(),
+            locale: None,
+            timestamps_in_text: false,
+            timestamp_format: TimestampFormat::default(),
+            db_synchronous: default_db_synchronous(),
+            db_busy_timeout_ms: default_db_busy_timeout_ms(),
+            capture_warmup_ms: default_capture_warmup_ms(),
+            capture_buffer_size: audio::CaptureBufferSize::default(),
+            input_device: None,
+            dual_mic_mode: audio::DualMicMode::default(),
+            dual_mic_primary: None,
+            dual_mic_secondary: None,
+            speaker_turns: SpeakerTurns::default(),
+            pause_trim: PauseTrim::default(),
+            activity_thresholds: ActivityThresholds::default(),
+            auto_stop: AutoStop::default(),
+            log_level: LogLevel::default(),
+            transcription_mode: TranscriptionMode::default(),
+            latency_priority: LatencyPriority::default(),
+            decode_override: None,
+            language: None,
+            initial_prompt: None,
+            use_gpu: default_use_gpu(),
+            preload_model: default_preload_model(),
+            deterministic: false,
+            window_geometry: None,
+            quick_note_seconds: default_quick_note_seconds(),
+            max_recording_seconds: default_max_recording_seconds(),
+            low_priority: false,
+            model_mirror_prefix: None,
+            power_models: PowerModels::default(),
+            live_output_file: None,
+            live_output_template: default_live_output_template(),
+            live_output_separator: default_live_output_separator(),
+            min_recording_ms: default_min_recording_ms(),
+            short_recording_policy: ShortRecordingPolicy::default(),
+            output_target: OutputTarget::default(),
+            debug_mode: false,
+            max_history_rows: None,
+            history_retention_days: None,
         }
     }
 }
 
 pub fn load(path: &Path) -> AppSettings {
-    let mut settings = match fs::read_to_string(path) {
+    let settings = match fs::read_to_string(path) {
         Ok(raw) => serde_json::from_str::<AppSettings>(&raw).unwrap_or_default(),
         Err(_) => AppSettings::default(),
     };
 
+    normalize(settings)
+}
+
+fn normalize(mut settings: AppSettings) -> AppSettings {
     settings.hotkey =
         canonicalize_hotkey(&settings.hotkey).unwrap_or_else(|| DEFAULT_HOTKEY.to_string());
+    settings.window_toggle_hotkey = settings
+        .window_toggle_hotkey
+        .as_deref()
+        .and_then(canonicalize_hotkey)
+        .filter(|value| *value != settings.hotkey);
+    settings.db_busy_timeout_ms = settings
+        .db_busy_timeout_ms
+        .clamp(db::MIN_BUSY_TIMEOUT_MS, db::MAX_BUSY_TIMEOUT_MS);
+    settings.capture_warmup_ms = settings.capture_warmup_ms.min(audio::MAX_CAPTURE_WARMUP_MS);
+    settings.capture_buffer_size = settings.capture_buffer_size.clamped();
+    settings.min_recording_ms = settings
+        .min_recording_ms
+        .clamp(MIN_MIN_RECORDING_MS, MAX_MIN_RECORDING_MS);
+    settings.locale = settings
+        .locale
+        .as_deref()
+        .and_then(postprocess::canonical_locale_tag);
+    settings.max_history_rows = settings.max_history_rows.filter(|rows| *rows > 0);
+    settings.history_retention_days = settings.history_retention_days.filter(|days| *days > 0);
+    settings.max_recording_seconds = settings
+        .max_recording_seconds
+        .clamp(MIN_MAX_RECORDING_SECONDS, MAX_MAX_RECORDING_SECONDS);
+    settings.quick_note_seconds = settings
+        .quick_note_seconds
+        .clamp(MIN_QUICK_NOTE_SECONDS, MAX_QUICK_NOTE_SECONDS);
+    settings.pause_trim.max_pause_ms = settings
+        .pause_trim
+        .max_pause_ms
+        .clamp(MIN_MAX_PAUSE_MS, MAX_MAX_PAUSE_MS);
+    settings.auto_stop = settings.auto_stop.clamped();
+    settings.activity_thresholds = settings.activity_thresholds.clamped();
+    settings.decode_override = settings.decode_override.map(DecodeParams::clamped);
+    settings.language = settings
+        .language
+        .map(|language| language.trim().to_ascii_lowercase())
+        .filter(|language| !language.is_empty());
+    settings.initial_prompt = normalize_initial_prompt(settings.initial_prompt);
     settings
 }
 
+impl AppSettings {
+    pub fn db_tuning(&self) -> db::DbTuning {
+        db::DbTuning {
+            synchronous: self.db_synchronous,
+            busy_timeout_ms: self.db_busy_timeout_ms,
+        }
+    }
+
+    /// The decode parameters transcription runs with: the override when set,
+    /// otherwise the latency priority's.
+    pub fn decode_params(&self) -> DecodeParams {
+        self.decode_override
+            .unwrap_or_else(|| self.latency_priority.decode_params())
+    }
+
+    pub fn short_recording(&self) -> ShortRecording {
+        ShortRecording {
+            min_ms: self.min_recording_ms,
+            policy: self.short_recording_policy,
+        }
+    }
+
+    pub fn number_locale(&self) -> NumberLocale {
+        self.locale
+            .as_deref()
+            .map(NumberLocale::for_tag)
+            .unwrap_or_else(NumberLocale::system)
+    }
+
+    pub fn dual_mic(&self) -> audio::DualMic {
+        audio::DualMic {
+            mode: self.dual_mic_mode,
+            primary: self.dual_mic_primary.clone(),
+            secondary: self.dual_mic_secondary.clone(),
+        }
+    }
+
+    pub fn timestamp_output(&self) -> TimestampOutput {
+        TimestampOutput {
+            enabled: self.timestamps_in_text,
+            format: self.timestamp_format,
+        }
+    }
+
+    pub fn live_output(&self) -> LiveOutput {
+        LiveOutput {
+            file: self.live_output_file.clone(),
+            template: self.live_output_template.clone(),
+            separator: self.live_output_separator.clone(),
+        }
+    }
+}
+
 pub fn save_hotkey(path: &Path, hotkey: &str) -> std::result::Result<(), String> {
     let canonical =
         canonicalize_hotkey(hotkey).ok_or_else(|| "Invalid hotkey format".to_string())?;
@@ -55,12 +699,228 @@ pub fn save_hotkey(path: &Path, hotkey: &str) -> std::result::Result<(), String>
     write(path, &settings)
 }
 
+pub fn save_window_toggle_hotkey(
+    path: &Path,
+    hotkey: Option<&str>,
+) -> std::result::Result<(), String> {
+    let canonical = match hotkey {
+        Some(raw) => {
+            Some(canonicalize_hotkey(raw).ok_or_else(|| "Invalid hotkey format".to_string())?)
+        }
+        None => None,
+    };
+
+    let mut settings = load(path);
+    settings.window_toggle_hotkey = canonical;
+    write(path, &settings)
+}
+
+pub fn save_hotkey_mode(path: &Path, mode: HotkeyMode) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.hotkey_mode = mode;
+    write(path, &settings)
+}
+
+pub fn save_auto_paste(path: &Path, enabled: bool) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.auto_paste = enabled;
+    write(path, &settings)
+}
+
+pub fn save_stream_typing(path: &Path, enabled: bool) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.stream_typing = enabled;
+    write(path, &settings)
+}
+
+pub fn save_paste_mode(path: &Path, mode: PasteMode) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.paste_mode = mode;
+    write(path, &settings)
+}
+
+pub fn save_keep_audio(path: &Path, enabled: bool) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.keep_audio = enabled;
+    write(path, &settings)
+}
+
 pub fn save_auto_copy(path: &Path, enabled: bool) -> std::result::Result<(), String> {
     let mut settings = load(path);
     settings.auto_copy = enabled;
     write(path, &settings)
 }
 
+pub fn save_debug_mode(path: &Path, enabled: bool) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.debug_mode = enabled;
+    write(path, &settings)
+}
+
+pub fn save_max_history_rows(
+    path: &Path,
+    max_rows: Option<u32>,
+) -> std::result::Result<(), String> {
+    if max_rows == Some(0) {
+        return Err("History cap must keep at least one transcription".to_string());
+    }
+
+    let mut settings = load(path);
+    settings.max_history_rows = max_rows;
+    write(path, &settings)
+}
+
+pub fn save_history_retention_days(
+    path: &Path,
+    days: Option<u32>,
+) -> std::result::Result<(), String> {
+    if days == Some(0) {
+        return Err("History retention must be at least one day".to_string());
+    }
+
+    let mut settings = load(path);
+    settings.history_retention_days = days;
+    write(path, &settings)
+}
+
+pub fn save_use_gpu(path: &Path, enabled: bool) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.use_gpu = enabled;
+    write(path, &settings)
+}
+
+pub fn save_preload_model(path: &Path, enabled: bool) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.preload_model = enabled;
+    write(path, &settings)
+}
+
+pub fn save_deterministic(path: &Path, enabled: bool) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.deterministic = enabled;
+    write(path, &settings)
+}
+
+pub fn save_low_priority(path: &Path, enabled: bool) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.low_priority = enabled;
+    write(path, &settings)
+}
+
+pub fn save_model_mirror_prefix(
+    path: &Path,
+    prefix: Option<&str>,
+) -> std::result::Result<Option<String>, String> {
+    let prefix = prefix
+        .map(|value| value.trim().trim_end_matches('/'))
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string());
+    if let Some(value) = &prefix {
+        if !(value.starts_with("https://") || value.starts_with("http://")) {
+            return Err("Mirror prefix must start with http:// or https://".to_string());
+        }
+    }
+
+    let mut settings = load(path);
+    settings.model_mirror_prefix = prefix.clone();
+    write(path, &settings)?;
+    Ok(prefix)
+}
+
+pub fn save_locale(
+    path: &Path,
+    locale: Option<&str>,
+) -> std::result::Result<Option<String>, String> {
+    let locale = match locale.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(raw) => Some(
+            postprocess::canonical_locale_tag(raw)
+                .ok_or_else(|| format!("'{raw}' is not a locale tag like en-US or de-DE"))?,
+        ),
+        None => None,
+    };
+
+    let mut settings = load(path);
+    settings.locale = locale.clone();
+    write(path, &settings)?;
+    Ok(locale)
+}
+
+pub fn save_timestamp_output(
+    path: &Path,
+    output: TimestampOutput,
+) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.timestamps_in_text = output.enabled;
+    settings.timestamp_format = output.format;
+    write(path, &settings)
+}
+
+pub fn save_power_models(
+    path: &Path,
+    models: PowerModels,
+) -> std::result::Result<PowerModels, String> {
+    let clean = |model: Option<String>| {
+        model
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let models = PowerModels {
+        ac: clean(models.ac),
+        battery: clean(models.battery),
+    };
+
+    let mut settings = load(path);
+    settings.power_models = models.clone();
+    write(path, &settings)?;
+    Ok(models)
+}
+
+pub fn save_live_output(
+    path: &Path,
+    output: LiveOutput,
+) -> std::result::Result<LiveOutput, String> {
+    let file = output
+        .file
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(value) = &file {
+        if !Path::new(value).is_absolute() {
+            return Err("Live output file must be an absolute path".to_string());
+        }
+    }
+    if output.template.trim().is_empty() {
+        return Err("Live output template cannot be empty".to_string());
+    }
+
+    let mut settings = load(path);
+    settings.live_output_file = file;
+    settings.live_output_template = output.template;
+    settings.live_output_separator = output.separator;
+    write(path, &settings)?;
+    Ok(settings.live_output())
+}
+
+pub fn save_output_target(
+    path: &Path,
+    target: OutputTarget,
+) -> std::result::Result<OutputTarget, String> {
+    let target = match target {
+        OutputTarget::Clipboard => OutputTarget::Clipboard,
+        OutputTarget::File(file) => OutputTarget::File(file.trim().to_string()),
+        OutputTarget::Both(file) => OutputTarget::Both(file.trim().to_string()),
+    };
+    if let Some(file) = target.file() {
+        if !file.is_absolute() {
+            return Err("Output file must be an absolute path".to_string());
+        }
+    }
+
+    let mut settings = load(path);
+    settings.output_target = target.clone();
+    write(path, &settings)?;
+    Ok(target)
+}
+
 pub fn save_active_model(path: &Path, model: Option<&str>) -> std::result::Result<(), String> {
     let mut settings = load(path);
     settings.active_model = model
@@ -70,6 +930,277 @@ pub fn save_active_model(path: &Path, model: Option<&str>) -> std::result::Resul
     write(path, &settings)
 }
 
+pub fn save_number_format(path: &Path, format: NumberFormat) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.number_format = format;
+    write(path, &settings)
+}
+
+pub fn save_short_recording(
+    path: &Path,
+    short_recording: ShortRecording,
+) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.min_recording_ms = short_recording
+        .min_ms
+        .clamp(MIN_MIN_RECORDING_MS, MAX_MIN_RECORDING_MS);
+    settings.short_recording_policy = short_recording.policy;
+    write(path, &settings)
+}
+
+pub fn save_max_recording_seconds(path: &Path, seconds: u32) -> std::result::Result<u32, String> {
+    let mut settings = load(path);
+    settings.max_recording_seconds =
+        seconds.clamp(MIN_MAX_RECORDING_SECONDS, MAX_MAX_RECORDING_SECONDS);
+    write(path, &settings)?;
+    Ok(settings.max_recording_seconds)
+}
+
+pub fn save_quick_note_seconds(path: &Path, seconds: u32) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.quick_note_seconds = seconds.clamp(MIN_QUICK_NOTE_SECONDS, MAX_QUICK_NOTE_SECONDS);
+    write(path, &settings)
+}
+
+pub fn save_capture_warmup_ms(path: &Path, warmup_ms: u32) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.capture_warmup_ms = warmup_ms.min(audio::MAX_CAPTURE_WARMUP_MS);
+    write(path, &settings)
+}
+
+pub fn save_capture_buffer_size(
+    path: &Path,
+    buffer_size: audio::CaptureBufferSize,
+) -> std::result::Result<audio::CaptureBufferSize, String> {
+    let mut settings = load(path);
+    settings.capture_buffer_size = buffer_size.clamped();
+    write(path, &settings)?;
+    Ok(settings.capture_buffer_size)
+}
+
+pub fn save_latency_priority(
+    path: &Path,
+    priority: LatencyPriority,
+) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.latency_priority = priority;
+    write(path, &settings)
+}
+
+pub fn save_language(path: &Path, language: Option<String>) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.language = language;
+    write(path, &settings)
+}
+
+/// Trims the prompt, drops an empty one, and cuts a hand-edited one that is
+/// too long.
+fn normalize_initial_prompt(prompt: Option<String>) -> Option<String> {
+    // whisper takes the prompt as a C string, which a NUL would cut short.
+    let prompt = prompt?.replace('\0', "");
+    let prompt = prompt.trim();
+    if prompt.is_empty() {
+        return None;
+    }
+    Some(prompt.chars().take(MAX_INITIAL_PROMPT_CHARS).collect())
+}
+
+pub fn save_initial_prompt(
+    path: &Path,
+    prompt: Option<String>,
+) -> std::result::Result<Option<String>, String> {
+    let prompt = normalize_initial_prompt(prompt);
+    let mut settings = load(path);
+    settings.initial_prompt = prompt.clone();
+    write(path, &settings)?;
+    Ok(prompt)
+}
+
+pub fn save_decode_override(
+    path: &Path,
+    decode: Option<DecodeParams>,
+) -> std::result::Result<Option<DecodeParams>, String> {
+    let mut settings = load(path);
+    settings.decode_override = decode.map(DecodeParams::clamped);
+    write(path, &settings)?;
+    Ok(settings.decode_override)
+}
+
+pub fn save_speaker_turns(
+    path: &Path,
+    mut turns: SpeakerTurns,
+) -> std::result::Result<SpeakerTurns, String> {
+    for (label, fallback) in turns.labels.iter_mut().zip(default_speaker_labels()) {
+        let trimmed = label.trim();
+        if trimmed.contains('\n') {
+            return Err("Speaker labels must fit on one line".to_string());
+        }
+        *label = if trimmed.is_empty() {
+            fallback
+        } else {
+            trimmed.to_string()
+        };
+    }
+
+    let mut settings = load(path);
+    settings.speaker_turns = turns.clone();
+    write(path, &settings)?;
+    Ok(turns)
+}
+
+pub fn save_pause_trim(path: &Path, mut trim: PauseTrim) -> std::result::Result<PauseTrim, String> {
+    trim.max_pause_ms = trim.max_pause_ms.clamp(MIN_MAX_PAUSE_MS, MAX_MAX_PAUSE_MS);
+    let mut settings = load(path);
+    settings.pause_trim = trim;
+    write(path, &settings)?;
+    Ok(trim)
+}
+
+pub fn save_activity_thresholds(
+    path: &Path,
+    thresholds: ActivityThresholds,
+) -> std::result::Result<ActivityThresholds, String> {
+    let thresholds = thresholds.clamped();
+    let mut settings = load(path);
+    settings.activity_thresholds = thresholds;
+    write(path, &settings)?;
+    Ok(thresholds)
+}
+
+pub fn save_auto_stop(path: &Path, auto_stop: AutoStop) -> std::result::Result<AutoStop, String> {
+    let auto_stop = auto_stop.clamped();
+    let mut settings = load(path);
+    settings.auto_stop = auto_stop;
+    write(path, &settings)?;
+    Ok(auto_stop)
+}
+
+pub fn save_input_device(path: &Path, device: Option<String>) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.input_device = device;
+    write(path, &settings)
+}
+
+/// Saves the dual-mic setup. `default_device` is the name of the system
+/// default input, which a `None` primary records from.
+pub fn save_dual_mic(
+    path: &Path,
+    dual: audio::DualMic,
+    default_device: Option<&str>,
+) -> std::result::Result<audio::DualMic, String> {
+    let clean = |name: Option<String>| {
+        name.map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let (primary, secondary) = (clean(dual.primary), clean(dual.secondary));
+    if dual.mode != audio::DualMicMode::Off {
+        if secondary.is_none() {
+            return Err("Choose a second microphone for dual-mic mode".to_string());
+        }
+        // A `None` primary is the default device, which may be the same one.
+        if primary.as_deref().or(default_device) == secondary.as_deref() {
+            return Err("Dual-mic mode needs two different microphones".to_string());
+        }
+    }
+
+    let mut settings = load(path);
+    settings.dual_mic_mode = dual.mode;
+    settings.dual_mic_primary = primary;
+    settings.dual_mic_secondary = secondary;
+    write(path, &settings)?;
+    Ok(settings.dual_mic())
+}
+
+pub fn save_transcription_mode(
+    path: &Path,
+    mode: TranscriptionMode,
+) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.transcription_mode = mode;
+    write(path, &settings)
+}
+
+pub fn save_window_geometry(
+    path: &Path,
+    geometry: Option<WindowGeometry>,
+) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.window_geometry = geometry;
+    write(path, &settings)
+}
+
+pub fn save_log_level(path: &Path, level: LogLevel) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.log_level = level;
+    write(path, &settings)
+}
+
+pub fn save_db_tuning(path: &Path, tuning: db::DbTuning) -> std::result::Result<(), String> {
+    let mut settings = load(path);
+    settings.db_synchronous = tuning.synchronous;
+    settings.db_busy_timeout_ms = tuning
+        .busy_timeout_ms
+        .clamp(db::MIN_BUSY_TIMEOUT_MS, db::MAX_BUSY_TIMEOUT_MS);
+    write(path, &settings)
+}
+
+pub fn export(path: &Path) -> std::result::Result<String, String> {
+    serde_json::to_string_pretty(&load(path))
+        .map_err(|e| format!("Failed to serialize settings: {e}"))
+}
+
+/// Parses a settings blob produced by `export`. Unlike `load`, which falls back
+/// to defaults so the app always starts, this rejects unknown fields and invalid
+/// hotkeys so a bad import never silently resets the user's configuration.
+pub fn parse_import(raw: &str) -> std::result::Result<AppSettings, String> {
+    let value: serde_json::Value =
+        serde_json::from_str(raw).map_err(|e| format!("Settings are not valid JSON: {e}"))?;
+    let object = value
+        .as_object()
+        .ok_or_else(|| "Settings must be a JSON object".to_string())?;
+
+    let known = serde_json::to_value(AppSettings::default())
+        .map_err(|e| format!("Failed to serialize settings: {e}"))?;
+    let unknown: Vec<&str> = object
+        .keys()
+        .filter(|key| known.get(key.as_str()).is_none())
+        .map(String::as_str)
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!("Unknown settings field(s): {}", unknown.join(", ")));
+    }
+
+    let imported: AppSettings =
+        serde_json::from_value(value).map_err(|e| format!("Invalid settings value: {e}"))?;
+
+    if canonicalize_hotkey(&imported.hotkey).is_none() {
+        return Err(format!("Invalid hotkey '{}'", imported.hotkey));
+    }
+    if let Some(window_toggle) = &imported.window_toggle_hotkey {
+        match canonicalize_hotkey(window_toggle) {
+            None => return Err(format!("Invalid window toggle hotkey '{window_toggle}'")),
+            Some(canonical)
+                if canonicalize_hotkey(&imported.hotkey).as_ref() == Some(&canonical) =>
+            {
+                return Err("Window toggle hotkey must differ from the recording hotkey".into())
+            }
+            Some(_) => {}
+        }
+    }
+
+    if let Some(prefix) = &imported.model_mirror_prefix {
+        if !(prefix.starts_with("https://") || prefix.starts_with("http://")) {
+            return Err(format!("Invalid model mirror prefix '{prefix}'"));
+        }
+    }
+
+    Ok(normalize(imported))
+}
+
+pub fn save_all(path: &Path, settings: &AppSettings) -> std::result::Result<(), String> {
+    write(path, settings)
+}
+
 fn write(path: &Path, settings: &AppSettings) -> std::result::Result<(), String> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
@@ -82,6 +1213,30 @@ fn write(path: &Path, settings: &AppSettings) -> std::result::Result<(), String>
     Ok(())
 }
 
+/// Human-friendly label for a hotkey, e.g. "⌃⇧S" on macOS or "Ctrl+Shift+S"
+/// elsewhere. Display only; `canonicalize_hotkey` is what gets stored.
+pub fn display_hotkey(shortcut: &Shortcut, mac_symbols: bool) -> String {
+    let modifiers: [(Modifiers, &str, &str); 4] = [
+        (Modifiers::CONTROL, "⌃", "Ctrl"),
+        (Modifiers::ALT, "⌥", "Alt"),
+        (Modifiers::SHIFT, "⇧", "Shift"),
+        (Modifiers::SUPER, "⌘", "Super"),
+    ];
+    let code = shortcut.key.to_string();
+    let key = code
+        .strip_prefix("Key")
+        .or_else(|| code.strip_prefix("Digit"))
+        .unwrap_or(&code);
+
+    let mut parts: Vec<&str> = modifiers
+        .iter()
+        .filter(|(modifier, _, _)| shortcut.mods.contains(*modifier))
+        .map(|(_, symbol, name)| if mac_symbols { *symbol } else { *name })
+        .collect();
+    parts.push(key);
+    parts.join(if mac_symbols { "" } else { "+" })
+}
+
 pub fn canonicalize_hotkey(raw: &str) -> Option<String> {
     let shortcut = Shortcut::from_str(raw.trim()).ok()?;
     let required_mods = Modifiers::SHIFT | Modifiers::CONTROL | Modifiers::ALT | Modifiers::SUPER;
@@ -90,3 +1245,160 @@ pub fn canonicalize_hotkey(raw: &str) -> Option<String> {
     }
     Some(shortcut.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_import_round_trips_and_canonicalizes() {
+        let original = AppSettings {
+            hotkey: "ctrl+alt+KeyD".to_string(),
+            auto_copy: true,
+            ..AppSettings::default()
+        };
+        let raw = serde_json::to_string(&original).unwrap();
+
+        let imported = parse_import(&raw).unwrap();
+        assert_eq!(
+            imported.hotkey,
+            canonicalize_hotkey("ctrl+alt+KeyD").unwrap()
+        );
+        assert!(imported.auto_copy);
+    }
+
+    #[test]
+    fn parse_import_rejects_unknown_fields_and_bad_hotkeys() {
+        assert!(
+            parse_import(r#"{"hotkey":"control+shift+KeyS","colour":"red"}"#)
+                .unwrap_err()
+                .contains("colour")
+        );
+        assert!(parse_import(r#"{"hotkey":"KeyS"}"#)
+            .unwrap_err()
+            .contains("Invalid hotkey"));
+    }
+
+    #[test]
+    fn hand_edited_quick_note_seconds_are_clamped() {
+        for (seconds, expected) in [
+            (0, MIN_QUICK_NOTE_SECONDS),
+            (3_600, MAX_QUICK_NOTE_SECONDS),
+            (12, 12),
+        ] {
+            let settings = normalize(AppSettings {
+                quick_note_seconds: seconds,
+                ..AppSettings::default()
+            });
+            assert_eq!(settings.quick_note_seconds, expected);
+        }
+    }
+
+    #[test]
+    fn initial_prompt_is_trimmed_and_capped() {
+        assert_eq!(normalize_initial_prompt(Some("  \n ".into())), None);
+        assert_eq!(
+            normalize_initial_prompt(Some(" Kubernetes, Grafana ".into())).as_deref(),
+            Some("Kubernetes, Grafana")
+        );
+        let long = "é".repeat(MAX_INITIAL_PROMPT_CHARS + 10);
+        let capped = normalize_initial_prompt(Some(long)).unwrap();
+        assert_eq!(capped.chars().count(), MAX_INITIAL_PROMPT_CHARS);
+        assert_eq!(
+            normalize_initial_prompt(Some("Kuber\0netes".into())).as_deref(),
+            Some("Kubernetes")
+        );
+        assert_eq!(normalize_initial_prompt(Some("\0 ".into())), None);
+    }
+
+    #[test]
+    fn paste_mode_fits_text_to_the_field() {
+        let text = "Send it today.";
+        assert_eq!(PasteMode::Insert.fit(Some('x'), text), text);
+        assert_eq!(
+            PasteMode::AppendWithSpace.fit(Some('.'), text),
+            " Send it today."
+        );
+        assert_eq!(PasteMode::AppendWithSpace.fit(Some('\n'), text), text);
+        assert_eq!(PasteMode::AppendWithSpace.fit(None, text), text);
+        assert_eq!(
+            PasteMode::ReplaceSelection.fit(None, " Grafana. "),
+            "Grafana"
+        );
+        assert_eq!(
+            PasteMode::ReplaceSelection.fit(None, "Yes. Send it today."),
+            "Yes. Send it today."
+        );
+    }
+
+    #[test]
+    fn decode_override_replaces_priority_and_is_clamped() {
+        assert_eq!(
+            AppSettings::default().decode_params(),
+            LatencyPriority::Balanced.decode_params()
+        );
+
+        let imported = parse_import(
+            r#"{"hotkey":"control+shift+KeyS","decode_override":{"best_of":1,"beam_size":50,"temperature_fallback":true,"temperature":3.0}}"#,
+        )
+        .unwrap();
+        let decode = imported.decode_params();
+        assert_eq!(decode.beam_size, Some(8));
+        assert_eq!(decode.temperature, 1.0);
+        assert_eq!(decode.entropy_thold, 2.4);
+        assert_eq!(decode.logprob_thold, -1.0);
+    }
+
+    #[test]
+    fn window_geometry_clamps_to_overlapping_monitor() {
+        let monitors = [
+            MonitorBounds {
+                x: 0,
+                y: 0,
+                width: 1440,
+                height: 900,
+            },
+            MonitorBounds {
+                x: 1440,
+                y: 0,
+                width: 1920,
+                height: 1080,
+            },
+        ];
+
+        let straddling = WindowGeometry {
+            x: 1300,
+            y: 850,
+            width: 420,
+            height: 600,
+        };
+        assert_eq!(
+            straddling.clamped_to(&monitors),
+            Some(WindowGeometry {
+                x: 1440,
+                y: 480,
+                width: 420,
+                height: 600,
+            })
+        );
+
+        let stranded = WindowGeometry {
+            x: 4000,
+            y: 0,
+            width: 420,
+            height: 600,
+        };
+        assert_eq!(stranded.clamped_to(&monitors), None);
+        assert_eq!(stranded.clamped_to(&monitors[..1]), None);
+    }
+
+    #[test]
+    fn display_hotkey_uses_platform_labels() {
+        let shortcut = Shortcut::from_str("shift+ctrl+KeyS").unwrap();
+        assert_eq!(display_hotkey(&shortcut, true), "⌃⇧S");
+        assert_eq!(display_hotkey(&shortcut, false), "Ctrl+Shift+S");
+
+        let shortcut = Shortcut::from_str("super+alt+Digit1").unwrap();
+        assert_eq!(display_hotkey(&shortcut, true), "⌥⌘1");
+    }
+}
@@ -1,9 +1,17 @@
-use crate::audio::RecordingSession;
+use crate::audio::{self, CaptureConfig, CapturedAudio, RecordingSession};
+use crate::db::{self, TranscriptionSource};
+use crate::logging::{self, LogLevel};
+use crate::models;
+use crate::postprocess::NumberFormat;
 use crate::settings;
+use crate::whisper::{self, TranscriptionMode};
 use serde::Serialize;
-use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex, RwLock};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock, TryLockError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -18,6 +26,92 @@ struct Inner {
     status: AppStatus,
     recording: Option<RecordingSession>,
     cancel_requested: Option<Arc<AtomicBool>>,
+    /// Increments per recording so timers can tell whether "their" recording
+    /// is still the one running.
+    recording_id: u64,
+    source: TranscriptionSource,
+    mode: TranscriptionMode,
+}
+
+/// A recording handed off for transcription.
+pub struct TakenRecording {
+    pub session: RecordingSession,
+    pub cancel_requested: Arc<AtomicBool>,
+    /// Quick notes are always copied when done.
+    pub source: TranscriptionSource,
+    /// Chosen when the recording started, so it can differ from the default.
+    pub mode: TranscriptionMode,
+}
+
+/// What `SharedState::recover` found and undid.
+pub struct StateRecovery {
+    pub previous: AppStatus,
+    pub session: Option<RecordingSession>,
+    /// Names of the locks that were poisoned.
+    pub poisoned: Vec<&'static str>,
+}
+
+fn clear_rwlock_poison<T>(lock: &RwLock<T>, name: &'static str, poisoned: &mut Vec<&'static str>) {
+    if lock.is_poisoned() {
+        drop(lock.write().unwrap_or_else(PoisonError::into_inner));
+        lock.clear_poison();
+        poisoned.push(name);
+    }
+}
+
+fn clear_mutex_poison<T>(lock: &Mutex<T>, name: &'static str, poisoned: &mut Vec<&'static str>) {
+    if lock.is_poisoned() {
+        drop(lock.lock().unwrap_or_else(PoisonError::into_inner));
+        lock.clear_poison();
+        poisoned.push(name);
+    }
+}
+
+/// Raw samples of the last recording, kept for previewing preprocessing.
+#[derive(Clone)]
+pub struct LastCapture {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+/// A finished recording on its way to a transcript, with how it was started.
+/// The latest one whose transcription failed is kept so it can be retried
+/// without recording again.
+pub struct PendingCapture {
+    pub captured: CapturedAudio,
+    pub source: TranscriptionSource,
+    pub mode: TranscriptionMode,
+}
+
+/// A continuous dictation session: one recording per utterance, with the
+/// results joined together until the user stops.
+struct ContinuousSession {
+    started_at: Instant,
+    text: String,
+    /// What stream typing has put into the focused field so far.
+    typed: String,
+    utterances: usize,
+    stopping: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ContinuousStatus {
+    pub utterances: usize,
+    pub elapsed_ms: u64,
+    /// Set once a stop was requested; the last utterance is still finishing.
+    pub stopping: bool,
+    pub text: String,
+}
+
+impl ContinuousSession {
+    fn status(&self) -> ContinuousStatus {
+        ContinuousStatus {
+            utterances: self.utterances,
+            elapsed_ms: self.started_at.elapsed().as_millis() as u64,
+            stopping: self.stopping,
+            text: self.text.clone(),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -26,9 +120,37 @@ pub struct SharedState {
     db_path: Arc<PathBuf>,
     models_dir: Arc<PathBuf>,
     settings_path: Arc<PathBuf>,
+    /// Everything in settings.json, so reads never touch the disk. Writes go
+    /// through `save_settings`, which refreshes it.
+    settings: Arc<RwLock<settings::AppSettings>>,
     active_model: Arc<RwLock<String>>,
+    input_device: Arc<RwLock<Option<String>>>,
     hotkey: Arc<RwLock<String>>,
+    hotkey_mode: Arc<RwLock<settings::HotkeyMode>>,
+    /// Whether the push-to-talk hotkey is held down right now.
+    push_to_talk_held: Arc<AtomicBool>,
+    /// Recording id started by push-to-talk and not yet stopped; 0 when none.
+    push_to_talk_recording: Arc<AtomicU64>,
+    window_toggle_hotkey: Arc<RwLock<Option<String>>>,
     auto_copy: Arc<RwLock<bool>>,
+    number_format: Arc<RwLock<NumberFormat>>,
+    capture_warmup_ms: Arc<RwLock<u32>>,
+    transcription_mode: Arc<RwLock<TranscriptionMode>>,
+    quick_note_seconds: Arc<RwLock<u32>>,
+    last_capture_config: Arc<Mutex<Option<CaptureConfig>>>,
+    last_capture: Arc<Mutex<Option<LastCapture>>>,
+    failed_capture: Arc<Mutex<Option<PendingCapture>>>,
+    low_priority: Arc<RwLock<bool>>,
+    live_output_written: Arc<Mutex<Option<PathBuf>>>,
+    /// Last character auto-paste or stream typing put into a field.
+    last_inserted: Arc<Mutex<Option<char>>>,
+    short_recording: Arc<RwLock<settings::ShortRecording>>,
+    heartbeat: Arc<AtomicU64>,
+    /// Cancel flag of the file transcription in progress, if any.
+    file_job_cancel: Arc<Mutex<Option<Arc<AtomicBool>>>>,
+    /// Cancel flags of running model downloads, by model file name.
+    downloads: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    continuous: Arc<Mutex<Option<ContinuousSession>>>,
 }
 
 impl SharedState {
@@ -37,21 +159,48 @@ impl SharedState {
         models_dir: PathBuf,
         settings_path: PathBuf,
         active_model: String,
-        hotkey: String,
-        auto_copy: bool,
+        input_device: Option<String>,
+        settings: &settings::AppSettings,
     ) -> Self {
         Self {
             inner: Arc::new(Mutex::new(Inner {
                 status: AppStatus::Idle,
                 recording: None,
                 cancel_requested: None,
+                recording_id: 0,
+                source: TranscriptionSource::default(),
+                mode: TranscriptionMode::default(),
             })),
             db_path: Arc::new(db_path),
             models_dir: Arc::new(models_dir),
             settings_path: Arc::new(settings_path),
+            settings: Arc::new(RwLock::new(settings::AppSettings {
+                input_device: input_device.clone(),
+                ..settings.clone()
+            })),
             active_model: Arc::new(RwLock::new(active_model)),
-            hotkey: Arc::new(RwLock::new(hotkey)),
-            auto_copy: Arc::new(RwLock::new(auto_copy)),
+            input_device: Arc::new(RwLock::new(input_device)),
+            hotkey: Arc::new(RwLock::new(settings.hotkey.clone())),
+            window_toggle_hotkey: Arc::new(RwLock::new(settings.window_toggle_hotkey.clone())),
+            hotkey_mode: Arc::new(RwLock::new(settings.hotkey_mode)),
+            push_to_talk_held: Arc::new(AtomicBool::new(false)),
+            push_to_talk_recording: Arc::new(AtomicU64::new(0)),
+            auto_copy: Arc::new(RwLock::new(settings.auto_copy)),
+            number_format: Arc::new(RwLock::new(settings.number_format)),
+            capture_warmup_ms: Arc::new(RwLock::new(settings.capture_warmup_ms)),
+            transcription_mode: Arc::new(RwLock::new(settings.transcription_mode)),
+            quick_note_seconds: Arc::new(RwLock::new(settings.quick_note_seconds)),
+            last_capture_config: Arc::new(Mutex::new(None)),
+            last_capture: Arc::new(Mutex::new(None)),
+            failed_capture: Arc::new(Mutex::new(None)),
+            low_priority: Arc::new(RwLock::new(settings.low_priority)),
+            live_output_written: Arc::new(Mutex::new(None)),
+            last_inserted: Arc::new(Mutex::new(None)),
+            short_recording: Arc::new(RwLock::new(settings.short_recording())),
+            heartbeat: Arc::new(AtomicU64::new(0)),
+            file_job_cancel: Arc::new(Mutex::new(None)),
+            downloads: Arc::new(Mutex::new(HashMap::new())),
+            continuous: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -62,7 +211,33 @@ impl SharedState {
             .unwrap_or(AppStatus::Idle)
     }
 
-    pub fn set_recording(&self, session: RecordingSession) -> Result<(), &'static str> {
+    /// Like `status`, but never waits: `None` means another thread holds the
+    /// state lock right now.
+    pub fn try_status(&self) -> Option<AppStatus> {
+        match self.inner.try_lock() {
+            Ok(inner) => Some(inner.status),
+            Err(TryLockError::Poisoned(err)) => Some(err.into_inner().status),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    /// Advances the heartbeat counter and returns the new value.
+    pub fn beat(&self) -> u64 {
+        self.heartbeat.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    pub fn heartbeat(&self) -> u64 {
+        self.heartbeat.load(Ordering::Relaxed)
+    }
+
+    /// Stores the live session and returns its recording id. `mode` applies to
+    /// this recording only.
+    pub fn set_recording(
+        &self,
+        session: RecordingSession,
+        source: TranscriptionSource,
+        mode: TranscriptionMode,
+    ) -> Result<u64, &'static str> {
         let mut guard = self.inner.lock().map_err(|_| "State lock poisoned")?;
         if guard.status != AppStatus::Idle {
             return Err("App is not idle");
@@ -70,19 +245,128 @@ impl SharedState {
         guard.cancel_requested = None;
         guard.recording = Some(session);
         guard.status = AppStatus::Recording;
-        Ok(())
+        guard.recording_id += 1;
+        guard.source = source;
+        guard.mode = mode;
+        Ok(guard.recording_id)
+    }
+
+    /// Hands `timer` to recording `recording_id` so stopping it joins the
+    /// thread. If that recording has already ended, the timer is joined here.
+    pub fn attach_recording_timer(&self, recording_id: u64, timer: JoinHandle<()>) {
+        let timer = match self.inner.lock() {
+            Ok(mut guard) if guard.recording_id == recording_id => match guard.recording.as_mut() {
+                Some(session) => {
+                    session.add_timer(timer);
+                    return;
+                }
+                None => timer,
+            },
+            _ => timer,
+        };
+        timer.thread().unpark();
+        let _ = timer.join();
+    }
+
+    /// Id of the recording in progress, if any.
+    pub fn active_recording_id(&self) -> Option<u64> {
+        self.inner
+            .lock()
+            .ok()
+            .filter(|inner| inner.status == AppStatus::Recording)
+            .map(|inner| inner.recording_id)
     }
 
-    pub fn take_recording(&self) -> Result<(RecordingSession, Arc<AtomicBool>), &'static str> {
+    /// Pauses the recording in progress. Continuous dictation and quick notes
+    /// run on their own timing and cannot be paused.
+    pub fn pause_recording(&self) -> Result<bool, &'static str> {
         let mut guard = self.inner.lock().map_err(|_| "State lock poisoned")?;
         if guard.status != AppStatus::Recording {
             return Err("App is not recording");
         }
+        match guard.source {
+            TranscriptionSource::Continuous => {
+                return Err("Continuous dictation cannot be paused.")
+            }
+            TranscriptionSource::QuickNote => return Err("Quick notes cannot be paused."),
+            _ => {}
+        }
+        let session = guard
+            .recording
+            .as_mut()
+            .ok_or("Recording session missing")?;
+        Ok(session.pause())
+    }
+
+    pub fn resume_recording(&self) -> Result<bool, &'static str> {
+        let mut guard = self.inner.lock().map_err(|_| "State lock poisoned")?;
+        if guard.status != AppStatus::Recording {
+            return Err("App is not recording");
+        }
+        let session = guard
+            .recording
+            .as_mut()
+            .ok_or("Recording session missing")?;
+        Ok(session.resume())
+    }
+
+    /// How long the recording in progress has spent paused, and whether it is
+    /// paused right now. `None` when nothing is recording.
+    pub fn recording_pause(&self) -> Option<(bool, Duration)> {
+        let guard = self.inner.lock().ok()?;
+        guard
+            .recording
+            .as_ref()
+            .map(|session| (session.is_paused(), session.paused_time()))
+    }
+
+    /// The live sample buffer of the recording in progress, for watching
+    /// levels while it runs.
+    pub fn live_samples(&self) -> Option<Arc<Mutex<Vec<f32>>>> {
+        let guard = self.inner.lock().ok()?;
+        guard
+            .recording
+            .as_ref()
+            .map(|session| session.samples.clone())
+    }
+
+    /// Ends the recording in progress and moves to `Processing`. With
+    /// `recording_id`, only that recording is ended, so a stop that raced a
+    /// newer recording leaves it running.
+    pub fn take_recording(
+        &self,
+        recording_id: Option<u64>,
+    ) -> Result<TakenRecording, &'static str> {
+        let mut guard = self.inner.lock().map_err(|_| "State lock poisoned")?;
+        if guard.status != AppStatus::Recording {
+            return Err("App is not recording");
+        }
+        if recording_id.is_some_and(|id| id != guard.recording_id) {
+            return Err("That recording has already ended");
+        }
         guard.status = AppStatus::Processing;
         let cancel_requested = Arc::new(AtomicBool::new(false));
         guard.cancel_requested = Some(cancel_requested.clone());
-        let recording = guard.recording.take().ok_or("Recording session missing")?;
-        Ok((recording, cancel_requested))
+        let session = guard.recording.take().ok_or("Recording session missing")?;
+        Ok(TakenRecording {
+            session,
+            cancel_requested,
+            source: std::mem::take(&mut guard.source),
+            mode: std::mem::take(&mut guard.mode),
+        })
+    }
+
+    /// Moves from `Idle` to `Processing` to transcribe a failed capture again.
+    /// Returns the cancel flag for it, as `take_recording` does.
+    pub fn begin_retry(&self) -> Result<Arc<AtomicBool>, &'static str> {
+        let mut guard = self.inner.lock().map_err(|_| "State lock poisoned")?;
+        if guard.status != AppStatus::Idle {
+            return Err("Finish the current recording before retrying.");
+        }
+        guard.status = AppStatus::Processing;
+        let cancel_requested = Arc::new(AtomicBool::new(false));
+        guard.cancel_requested = Some(cancel_requested.clone());
+        Ok(cancel_requested)
     }
 
     pub fn request_cancel_processing(&self) -> Result<bool, &'static str> {
@@ -104,20 +388,357 @@ impl SharedState {
 
     pub fn set_idle(&self) {
         if let Ok(mut guard) = self.inner.lock() {
+            // A stop that outlived `force_reset` must not clobber a recording
+            // started after it.
+            if guard.status == AppStatus::Recording {
+                return;
+            }
             guard.status = AppStatus::Idle;
             guard.recording = None;
             guard.cancel_requested = None;
         }
     }
 
+    /// Returns to `Idle` from any status, cancelling in-flight work. Hands back
+    /// the status it left and the live session, if one was still held.
+    pub fn force_reset(&self) -> (AppStatus, Option<RecordingSession>) {
+        let Ok(mut guard) = self.inner.lock() else {
+            return (AppStatus::Idle, None);
+        };
+        if let Some(flag) = guard.cancel_requested.take() {
+            flag.store(true, Ordering::Relaxed);
+        }
+        let previous = std::mem::replace(&mut guard.status, AppStatus::Idle);
+        guard.source = TranscriptionSource::default();
+        guard.mode = TranscriptionMode::default();
+        (previous, guard.recording.take())
+    }
+
+    /// Rebuilds the recording state as a clean `Idle` and clears poison from
+    /// every lock, so a panic mid-recording does not leave the app stuck.
+    /// Cached settings keep their values; the settings cache is reread from
+    /// the saved file.
+    pub fn recover(&self) -> StateRecovery {
+        let mut poisoned = Vec::new();
+        let mut guard = self.inner.lock().unwrap_or_else(|err| {
+            poisoned.push("recording state");
+            err.into_inner()
+        });
+        if let Some(flag) = guard.cancel_requested.take() {
+            flag.store(true, Ordering::Relaxed);
+        }
+        let previous = guard.status;
+        let session = guard.recording.take();
+        *guard = Inner {
+            status: AppStatus::Idle,
+            recording: None,
+            cancel_requested: None,
+            // Keep counting so timers from the old recording see a newer id.
+            recording_id: guard.recording_id + 1,
+            source: TranscriptionSource::default(),
+            mode: TranscriptionMode::default(),
+        };
+        drop(guard);
+        self.inner.clear_poison();
+
+        clear_rwlock_poison(&self.active_model, "active model", &mut poisoned);
+        clear_rwlock_poison(&self.input_device, "input device", &mut poisoned);
+        clear_rwlock_poison(&self.hotkey, "hotkey", &mut poisoned);
+        clear_rwlock_poison(
+            &self.window_toggle_hotkey,
+            "window toggle hotkey",
+            &mut poisoned,
+        );
+        clear_rwlock_poison(&self.hotkey_mode, "hotkey mode", &mut poisoned);
+        clear_rwlock_poison(&self.auto_copy, "auto copy", &mut poisoned);
+        clear_rwlock_poison(&self.number_format, "number format", &mut poisoned);
+        clear_rwlock_poison(&self.capture_warmup_ms, "capture warmup", &mut poisoned);
+        clear_rwlock_poison(
+            &self.transcription_mode,
+            "transcription mode",
+            &mut poisoned,
+        );
+        clear_rwlock_poison(&self.quick_note_seconds, "quick note length", &mut poisoned);
+        clear_rwlock_poison(&self.low_priority, "low priority", &mut poisoned);
+        clear_rwlock_poison(&self.short_recording, "short recording", &mut poisoned);
+        clear_rwlock_poison(&self.settings, "settings", &mut poisoned);
+        // A panic mid-save may have left the cache out of step with the file.
+        if let Ok(mut cached) = self.settings.write() {
+            *cached = settings::load(&self.settings_path);
+        }
+        clear_mutex_poison(&self.last_capture_config, "capture config", &mut poisoned);
+        clear_mutex_poison(&self.last_capture, "last capture", &mut poisoned);
+        clear_mutex_poison(&self.failed_capture, "failed capture", &mut poisoned);
+        clear_mutex_poison(&self.live_output_written, "live output", &mut poisoned);
+        clear_mutex_poison(&self.file_job_cancel, "file job", &mut poisoned);
+        clear_mutex_poison(&self.downloads, "downloads", &mut poisoned);
+        clear_mutex_poison(&self.continuous, "continuous dictation", &mut poisoned);
+
+        StateRecovery {
+            previous,
+            session,
+            poisoned,
+        }
+    }
+
+    /// Registers a file transcription and returns the flag that cancels it,
+    /// or `None` while another one is running. File jobs run outside the
+    /// recording status, so they get their own flag.
+    pub fn begin_file_job(&self) -> Option<Arc<AtomicBool>> {
+        let mut guard = self.file_job_cancel.lock().ok()?;
+        if guard.is_some() {
+            return None;
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        *guard = Some(flag.clone());
+        Some(flag)
+    }
+
+    pub fn end_file_job(&self, flag: &Arc<AtomicBool>) {
+        if let Ok(mut guard) = self.file_job_cancel.lock() {
+            if guard
+                .as_ref()
+                .is_some_and(|current| Arc::ptr_eq(current, flag))
+            {
+                *guard = None;
+            }
+        }
+    }
+
+    /// Flags the running file transcription to stop. Returns whether one was
+    /// running.
+    pub fn request_cancel_file_job(&self) -> bool {
+        self.file_job_cancel
+            .lock()
+            .ok()
+            .and_then(|guard| guard.clone())
+            .map(|flag| !flag.swap(true, Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    /// Opens a continuous dictation session. Returns `false` when one is
+    /// already running.
+    pub fn begin_continuous(&self) -> bool {
+        let Ok(mut guard) = self.continuous.lock() else {
+            return false;
+        };
+        if guard.is_some() {
+            return false;
+        }
+        *guard = Some(ContinuousSession {
+            started_at: Instant::now(),
+            text: String::new(),
+            typed: String::new(),
+            utterances: 0,
+            stopping: false,
+        });
+        true
+    }
+
+    pub fn continuous_status(&self) -> Option<ContinuousStatus> {
+        self.continuous
+            .lock()
+            .ok()
+            .and_then(|guard| guard.as_ref().map(ContinuousSession::status))
+    }
+
+    /// Asks the running session to end after its current utterance. Returns
+    /// whether a session was running and not already stopping.
+    pub fn request_stop_continuous(&self) -> bool {
+        let Ok(mut guard) = self.continuous.lock() else {
+            return false;
+        };
+        match guard.as_mut() {
+            Some(session) if !session.stopping => {
+                session.stopping = true;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds one utterance to the session and returns the updated status, or
+    /// `None` when no session is open.
+    pub fn append_continuous(&self, text: &str) -> Option<ContinuousStatus> {
+        let mut guard = self.continuous.lock().ok()?;
+        let session = guard.as_mut()?;
+        let text = text.trim();
+        // An utterance that came back empty adds nothing to the session.
+        if text.is_empty() || text == db::NO_SPEECH_PLACEHOLDER {
+            return Some(session.status());
+        }
+        if !session.text.is_empty() {
+            session.text.push(' ');
+        }
+        session.text.push_str(text);
+        session.utterances += 1;
+        Some(session.status())
+    }
+
+    /// Records `text` as what stream typing has now put into the field for
+    /// the session, and returns what it had typed before.
+    pub fn swap_continuous_typed(&self, text: &str) -> Option<String> {
+        let mut guard = self.continuous.lock().ok()?;
+        let session = guard.as_mut()?;
+        Some(std::mem::replace(&mut session.typed, text.to_string()))
+    }
+
+    pub fn end_continuous(&self) -> Option<ContinuousStatus> {
+        self.continuous
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take())
+            .map(|session| session.status())
+    }
+
+    /// Registers a model download and returns its cancel flag, or `None` when
+    /// the same model is already downloading.
+    pub fn begin_download(&self, file_name: &str) -> Option<Arc<AtomicBool>> {
+        let mut guard = self.downloads.lock().ok()?;
+        if guard.contains_key(file_name) {
+            return None;
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        guard.insert(file_name.to_string(), flag.clone());
+        Some(flag)
+    }
+
+    pub fn end_download(&self, file_name: &str) {
+        if let Ok(mut guard) = self.downloads.lock() {
+            guard.remove(file_name);
+        }
+    }
+
+    pub fn is_downloading(&self, file_name: &str) -> bool {
+        self.downloads
+            .lock()
+            .map(|guard| guard.contains_key(file_name))
+            .unwrap_or(false)
+    }
+
+    /// Pauses a running download. Returns whether one was running.
+    pub fn request_cancel_download(&self, file_name: &str) -> bool {
+        self.downloads
+            .lock()
+            .ok()
+            .and_then(|guard| guard.get(file_name).cloned())
+            .map(|flag| !flag.swap(true, Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    /// Remembers the config of the recording that just finished and returns
+    /// the previous one.
+    pub fn replace_capture_config(&self, config: CaptureConfig) -> Option<CaptureConfig> {
+        self.last_capture_config
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.replace(config))
+    }
+
+    pub fn remember_capture(&self, samples: Vec<f32>, sample_rate: u32) {
+        if let Ok(mut guard) = self.last_capture.lock() {
+            *guard = Some(LastCapture {
+                samples,
+                sample_rate,
+            });
+        }
+    }
+
+    pub fn last_capture(&self) -> Option<LastCapture> {
+        self.last_capture
+            .lock()
+            .ok()
+            .and_then(|guard| guard.clone())
+    }
+
+    pub fn keep_failed_capture(&self, capture: PendingCapture) {
+        if let Ok(mut guard) = self.failed_capture.lock() {
+            *guard = Some(capture);
+        }
+    }
+
+    pub fn take_failed_capture(&self) -> Option<PendingCapture> {
+        self.failed_capture
+            .lock()
+            .ok()
+            .and_then(|mut guard| guard.take())
+    }
+
+    /// Drops the kept capture once a transcription has succeeded, so stale
+    /// audio is never retried.
+    pub fn clear_failed_capture(&self) {
+        if let Ok(mut guard) = self.failed_capture.lock() {
+            *guard = None;
+        }
+    }
+
+    /// Records a write to the live output file. Returns whether this session
+    /// had already written to the same path.
+    pub fn mark_live_output_written(&self, path: &Path) -> bool {
+        self.live_output_written
+            .lock()
+            .map(|mut guard| guard.replace(path.to_path_buf()).as_deref() == Some(path))
+            .unwrap_or(false)
+    }
+
+    /// The last character auto-paste or stream typing inserted, taken as
+    /// the one before the cursor.
+    pub fn last_inserted_char(&self) -> Option<char> {
+        self.last_inserted.lock().ok().and_then(|guard| *guard)
+    }
+
+    pub fn record_inserted(&self, text: &str) {
+        if let (Ok(mut guard), Some(last)) = (self.last_inserted.lock(), text.chars().last()) {
+            *guard = Some(last);
+        }
+    }
+
     pub fn db_path(&self) -> PathBuf {
         (*self.db_path).clone()
     }
 
+    pub fn settings_path(&self) -> PathBuf {
+        (*self.settings_path).clone()
+    }
+
+    /// The current settings, from memory.
+    pub fn settings(&self) -> settings::AppSettings {
+        self.settings
+            .read()
+            .map(|cached| cached.clone())
+            .unwrap_or_else(|_| settings::load(&self.settings_path))
+    }
+
+    /// Runs `save` against settings.json, then refreshes the cached settings
+    /// from the file. The cache stays locked meanwhile, so concurrent saves
+    /// cannot interleave their read-modify-write and a failed write never
+    /// shows up in memory.
+    pub fn save_settings<T>(
+        &self,
+        save: impl FnOnce(&Path) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut cached = self
+            .settings
+            .write()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        let saved = save(&self.settings_path)?;
+        *cached = settings::load(&self.settings_path);
+        Ok(saved)
+    }
+
     pub fn models_dir(&self) -> PathBuf {
         (*self.models_dir).clone()
     }
 
+    /// Audio kept for re-transcription, one WAV per history row, next to
+    /// the database.
+    pub fn audio_dir(&self) -> PathBuf {
+        self.db_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join("audio")
+    }
+
     pub fn hotkey(&self) -> String {
         self.hotkey
             .read()
@@ -136,7 +757,7 @@ impl SharedState {
             *guard = hotkey.clone();
         }
 
-        if let Err(err) = settings::save_hotkey(self.settings_path.as_ref().as_path(), &hotkey) {
+        if let Err(err) = self.save_settings(|path| settings::save_hotkey(path, &hotkey)) {
             if let Ok(mut guard) = self.hotkey.write() {
                 *guard = previous;
             }
@@ -146,6 +767,85 @@ impl SharedState {
         Ok(())
     }
 
+    pub fn window_toggle_hotkey(&self) -> Option<String> {
+        self.window_toggle_hotkey
+            .read()
+            .map(|value| value.clone())
+            .unwrap_or(None)
+    }
+
+    pub fn set_window_toggle_hotkey(&self, hotkey: Option<String>) -> Result<(), String> {
+        let previous = self.window_toggle_hotkey();
+
+        {
+            let mut guard = self
+                .window_toggle_hotkey
+                .write()
+                .map_err(|_| "Window hotkey lock poisoned".to_string())?;
+            *guard = hotkey.clone();
+        }
+
+        if let Err(err) =
+            self.save_settings(|path| settings::save_window_toggle_hotkey(path, hotkey.as_deref()))
+        {
+            if let Ok(mut guard) = self.window_toggle_hotkey.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn hotkey_mode(&self) -> settings::HotkeyMode {
+        self.hotkey_mode
+            .read()
+            .map(|value| *value)
+            .unwrap_or_default()
+    }
+
+    pub fn set_hotkey_mode(&self, mode: settings::HotkeyMode) -> Result<(), String> {
+        let previous = self.hotkey_mode();
+
+        {
+            let mut guard = self
+                .hotkey_mode
+                .write()
+                .map_err(|_| "Hotkey mode lock poisoned".to_string())?;
+            *guard = mode;
+        }
+
+        if let Err(err) = self.save_settings(|path| settings::save_hotkey_mode(path, mode)) {
+            if let Ok(mut guard) = self.hotkey_mode.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_push_to_talk_held(&self, held: bool) {
+        self.push_to_talk_held.store(held, Ordering::SeqCst);
+    }
+
+    pub fn push_to_talk_held(&self) -> bool {
+        self.push_to_talk_held.load(Ordering::SeqCst)
+    }
+
+    /// Marks `recording_id` as started by push-to-talk.
+    pub fn claim_push_to_talk(&self, recording_id: u64) {
+        self.push_to_talk_recording
+            .store(recording_id, Ordering::SeqCst);
+    }
+
+    /// Hands the push-to-talk recording to whoever stops it. Only the first
+    /// caller gets it, so a release racing the start cannot stop it twice.
+    pub fn take_push_to_talk(&self) -> Option<u64> {
+        let recording_id = self.push_to_talk_recording.swap(0, Ordering::SeqCst);
+        (recording_id != 0).then_some(recording_id)
+    }
+
     pub fn auto_copy(&self) -> bool {
         self.auto_copy
             .read()
@@ -164,7 +864,7 @@ impl SharedState {
             *guard = enabled;
         }
 
-        if let Err(err) = settings::save_auto_copy(self.settings_path.as_ref().as_path(), enabled) {
+        if let Err(err) = self.save_settings(|path| settings::save_auto_copy(path, enabled)) {
             if let Ok(mut guard) = self.auto_copy.write() {
                 *guard = previous;
             }
@@ -174,6 +874,255 @@ impl SharedState {
         Ok(())
     }
 
+    pub fn low_priority(&self) -> bool {
+        self.low_priority
+            .read()
+            .map(|value| *value)
+            .unwrap_or(false)
+    }
+
+    pub fn set_low_priority(&self, enabled: bool) -> Result<(), String> {
+        let previous = self.low_priority();
+
+        {
+            let mut guard = self
+                .low_priority
+                .write()
+                .map_err(|_| "Low priority lock poisoned".to_string())?;
+            *guard = enabled;
+        }
+
+        if let Err(err) = self.save_settings(|path| settings::save_low_priority(path, enabled)) {
+            if let Ok(mut guard) = self.low_priority.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn number_format(&self) -> NumberFormat {
+        self.number_format
+            .read()
+            .map(|value| *value)
+            .unwrap_or_default()
+    }
+
+    pub fn set_number_format(&self, format: NumberFormat) -> Result<(), String> {
+        let previous = self.number_format();
+
+        {
+            let mut guard = self
+                .number_format
+                .write()
+                .map_err(|_| "Number format lock poisoned".to_string())?;
+            *guard = format;
+        }
+
+        if let Err(err) = self.save_settings(|path| settings::save_number_format(path, format)) {
+            if let Ok(mut guard) = self.number_format.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn transcription_mode(&self) -> TranscriptionMode {
+        self.transcription_mode
+            .read()
+            .map(|value| *value)
+            .unwrap_or_default()
+    }
+
+    pub fn set_transcription_mode(&self, mode: TranscriptionMode) -> Result<(), String> {
+        let previous = self.transcription_mode();
+
+        {
+            let mut guard = self
+                .transcription_mode
+                .write()
+                .map_err(|_| "Transcription mode lock poisoned".to_string())?;
+            *guard = mode;
+        }
+
+        if let Err(err) = self.save_settings(|path| settings::save_transcription_mode(path, mode)) {
+            if let Ok(mut guard) = self.transcription_mode.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn short_recording(&self) -> settings::ShortRecording {
+        self.short_recording
+            .read()
+            .map(|value| *value)
+            .unwrap_or_else(|_| settings::AppSettings::default().short_recording())
+    }
+
+    pub fn set_short_recording(
+        &self,
+        short_recording: settings::ShortRecording,
+    ) -> Result<(), String> {
+        let previous = self.short_recording();
+        let short_recording = settings::ShortRecording {
+            min_ms: short_recording.min_ms.clamp(
+                settings::MIN_MIN_RECORDING_MS,
+                settings::MAX_MIN_RECORDING_MS,
+            ),
+            ..short_recording
+        };
+
+        {
+            let mut guard = self
+                .short_recording
+                .write()
+                .map_err(|_| "Short recording lock poisoned".to_string())?;
+            *guard = short_recording;
+        }
+
+        if let Err(err) =
+            self.save_settings(|path| settings::save_short_recording(path, short_recording))
+        {
+            if let Ok(mut guard) = self.short_recording.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn quick_note_seconds(&self) -> u32 {
+        self.quick_note_seconds
+            .read()
+            .map(|value| *value)
+            .unwrap_or(settings::DEFAULT_QUICK_NOTE_SECONDS)
+    }
+
+    pub fn set_quick_note_seconds(&self, seconds: u32) -> Result<(), String> {
+        let previous = self.quick_note_seconds();
+        let seconds = seconds.clamp(
+            settings::MIN_QUICK_NOTE_SECONDS,
+            settings::MAX_QUICK_NOTE_SECONDS,
+        );
+
+        {
+            let mut guard = self
+                .quick_note_seconds
+                .write()
+                .map_err(|_| "Quick note duration lock poisoned".to_string())?;
+            *guard = seconds;
+        }
+
+        if let Err(err) =
+            self.save_settings(|path| settings::save_quick_note_seconds(path, seconds))
+        {
+            if let Ok(mut guard) = self.quick_note_seconds.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    pub fn capture_warmup_ms(&self) -> u32 {
+        self.capture_warmup_ms
+            .read()
+            .map(|value| *value)
+            .unwrap_or(audio::DEFAULT_CAPTURE_WARMUP_MS)
+    }
+
+    pub fn set_capture_warmup_ms(&self, warmup_ms: u32) -> Result<(), String> {
+        let previous = self.capture_warmup_ms();
+        let warmup_ms = warmup_ms.min(audio::MAX_CAPTURE_WARMUP_MS);
+
+        {
+            let mut guard = self
+                .capture_warmup_ms
+                .write()
+                .map_err(|_| "Capture warm-up lock poisoned".to_string())?;
+            *guard = warmup_ms;
+        }
+
+        if let Err(err) =
+            self.save_settings(|path| settings::save_capture_warmup_ms(path, warmup_ms))
+        {
+            if let Ok(mut guard) = self.capture_warmup_ms.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Persists a full settings snapshot and updates every cached value. Hotkey
+    /// registration is the caller's job since it needs the app handle.
+    pub fn apply_settings(&self, imported: &settings::AppSettings) -> Result<(), String> {
+        self.save_settings(|path| settings::save_all(path, imported))?;
+
+        if let Ok(mut guard) = self.hotkey.write() {
+            *guard = imported.hotkey.clone();
+        }
+        if let Ok(mut guard) = self.window_toggle_hotkey.write() {
+            *guard = imported.window_toggle_hotkey.clone();
+        }
+        if let Ok(mut guard) = self.auto_copy.write() {
+            *guard = imported.auto_copy;
+        }
+        if let Ok(mut guard) = self.hotkey_mode.write() {
+            *guard = imported.hotkey_mode;
+        }
+        if let Ok(mut guard) = self.number_format.write() {
+            *guard = imported.number_format;
+        }
+        if let Ok(mut guard) = self.capture_warmup_ms.write() {
+            *guard = imported.capture_warmup_ms;
+        }
+        if let Ok(mut guard) = self.transcription_mode.write() {
+            *guard = imported.transcription_mode;
+        }
+        if let Ok(mut guard) = self.quick_note_seconds.write() {
+            *guard = imported.quick_note_seconds;
+        }
+        if let Ok(mut guard) = self.low_priority.write() {
+            *guard = imported.low_priority;
+        }
+        if let Ok(mut guard) = self.short_recording.write() {
+            *guard = imported.short_recording();
+        }
+        if let Ok(mut guard) = self.input_device.write() {
+            *guard = imported.input_device.clone();
+        }
+        if let Some(model) = &imported.active_model {
+            if let Ok(mut guard) = self.active_model.write() {
+                *guard = model.clone();
+            }
+        }
+        db::configure(imported.db_tuning());
+        logging::set_level(imported.log_level);
+        Ok(())
+    }
+
+    pub fn set_log_level(&self, level: LogLevel) -> Result<(), String> {
+        self.save_settings(|path| settings::save_log_level(path, level))?;
+        logging::set_level(level);
+        Ok(())
+    }
+
+    pub fn set_db_tuning(&self, tuning: db::DbTuning) -> Result<(), String> {
+        self.save_settings(|path| settings::save_db_tuning(path, tuning))?;
+        db::configure(tuning);
+        Ok(())
+    }
+
     pub fn active_model_name(&self) -> String {
         self.active_model
             .read()
@@ -194,8 +1143,13 @@ impl SharedState {
         if !model_path.exists() {
             return Err("Selected model is not installed in the models directory".to_string());
         }
+        models::read_model_header(&model_path)
+            .map_err(|err| format!("'{file_name}' is not a usable whisper model: {err}"))?;
 
         let previous = self.active_model_name();
+        if previous != file_name {
+            whisper::clear_context_cache();
+        }
 
         let mut guard = self
             .active_model
@@ -204,10 +1158,9 @@ impl SharedState {
         *guard = file_name;
         drop(guard);
 
-        if let Err(err) = settings::save_active_model(
-            self.settings_path.as_ref().as_path(),
-            Some(&self.active_model_name()),
-        ) {
+        if let Err(err) = self.save_settings(|path| {
+            settings::save_active_model(path, Some(&self.active_model_name()))
+        }) {
             if let Ok(mut rollback) = self.active_model.write() {
                 *rollback = previous;
             }
@@ -216,4 +1169,33 @@ impl SharedState {
 
         Ok(())
     }
+
+    /// Microphone chosen for recording; `None` uses the system default.
+    pub fn input_device(&self) -> Option<String> {
+        self.input_device
+            .read()
+            .map(|value| value.clone())
+            .unwrap_or(None)
+    }
+
+    pub fn set_input_device(&self, device: Option<String>) -> Result<(), String> {
+        let previous = self.input_device();
+
+        {
+            let mut guard = self
+                .input_device
+                .write()
+                .map_err(|_| "Input device lock poisoned".to_string())?;
+            *guard = device.clone();
+        }
+
+        if let Err(err) = self.save_settings(|path| settings::save_input_device(path, device)) {
+            if let Ok(mut guard) = self.input_device.write() {
+                *guard = previous;
+            }
+            return Err(err);
+        }
+
+        Ok(())
+    }
 }
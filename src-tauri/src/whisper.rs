@@ -1,21 +1,327 @@
+use crate::logging;
 use anyhow::{anyhow, Result};
-use std::path::Path;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Instant, SystemTime};
 use whisper_rs::{
     FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperError,
 };
 
+/// Version of the `whisper-rs` bindings this build links against, read from
+/// `Cargo.lock` by the build script.
+pub const WHISPER_RS_VERSION: &str = env!("WHISPER_RS_VERSION");
+
+/// Whether whisper.cpp was built with a GPU backend. The `metal` feature only
+/// takes effect on macOS targets.
+pub const GPU_COMPILED: bool = cfg!(target_os = "macos");
+
+/// Rough realtime factor per million parameters when no benchmark exists yet.
+/// Tuned loosely against CPU runs: base.en lands near 0.2, large-v3 near 4.
+const HEURISTIC_RTF_PER_MILLION_PARAMS: f64 = 1.0 / 400.0;
+
+/// `Clean` is the normal dictation output. `Verbatim` keeps fillers ("um",
+/// "uh") and false starts for interview-style transcripts, and skips number
+/// normalization afterwards. Chosen per recording; the setting is the default.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriptionMode {
+    #[default]
+    Clean,
+    Verbatim,
+}
+
+/// What to favor when decoding, chosen as an intent instead of raw numbers.
+/// Each maps to fixed whisper parameters:
+///
+/// - `fast`: greedy sampling, best_of 1, no temperature fallback. A decode
+///   that whisper considers low-confidence is kept instead of retried.
+/// - `balanced`: greedy sampling, best_of 2, temperature fallback on (whisper
+///   re-decodes hotter in 0.2 steps when the output looks unreliable).
+/// - `accurate`: beam search with 5 beams, temperature fallback on. Noticeably
+///   slower, most of all on large models and CPU-only machines.
+///
+/// Retries after a decode error always drop to greedy best_of 1.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LatencyPriority {
+    Fast,
+    #[default]
+    Balanced,
+    Accurate,
+}
+
+/// Concrete decode parameters behind a `LatencyPriority`. Power users can
+/// also set them directly, which overrides the priority.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DecodeParams {
+    pub best_of: i32,
+    /// Beam search width; `None` means greedy sampling.
+    pub beam_size: Option<i32>,
+    pub temperature_fallback: bool,
+    /// Temperature of the first decode. 0 always picks the likeliest token.
+    #[serde(default)]
+    pub temperature: f32,
+    /// A decode whose tokens are more uncertain than this (a sign of
+    /// repetition) counts as failed and is retried hotter.
+    #[serde(default = "default_entropy_thold")]
+    pub entropy_thold: f32,
+    /// A decode whose average token log probability is below this counts as
+    /// failed and is retried hotter.
+    #[serde(default = "default_logprob_thold")]
+    pub logprob_thold: f32,
+}
+
+/// whisper.cpp's own defaults for the failure thresholds.
+const DEFAULT_ENTROPY_THOLD: f32 = 2.4;
+const DEFAULT_LOGPROB_THOLD: f32 = -1.0;
+const MAX_BEST_OF: i32 = 8;
+const MAX_BEAM_SIZE: i32 = 8;
+
+fn default_entropy_thold() -> f32 {
+    DEFAULT_ENTROPY_THOLD
+}
+
+fn default_logprob_thold() -> f32 {
+    DEFAULT_LOGPROB_THOLD
+}
+
+/// Fields every priority shares.
+const BASE_DECODE: DecodeParams = DecodeParams {
+    best_of: 1,
+    beam_size: None,
+    temperature_fallback: false,
+    temperature: 0.0,
+    entropy_thold: DEFAULT_ENTROPY_THOLD,
+    logprob_thold: DEFAULT_LOGPROB_THOLD,
+};
+
+impl LatencyPriority {
+    pub fn decode_params(self) -> DecodeParams {
+        match self {
+            LatencyPriority::Fast => BASE_DECODE,
+            LatencyPriority::Balanced => DecodeParams {
+                best_of: 2,
+                temperature_fallback: true,
+                ..BASE_DECODE
+            },
+            LatencyPriority::Accurate => DecodeParams {
+                beam_size: Some(5),
+                temperature_fallback: true,
+                ..BASE_DECODE
+            },
+        }
+    }
+}
+
+impl DecodeParams {
+    /// Keeps user-supplied values inside what whisper handles sensibly.
+    pub fn clamped(self) -> Self {
+        let finite_or = |value: f32, fallback: f32| {
+            if value.is_finite() {
+                value
+            } else {
+                fallback
+            }
+        };
+        Self {
+            best_of: self.best_of.clamp(1, MAX_BEST_OF),
+            beam_size: self.beam_size.map(|size| size.clamp(1, MAX_BEAM_SIZE)),
+            temperature_fallback: self.temperature_fallback,
+            temperature: finite_or(self.temperature, 0.0).clamp(0.0, 1.0),
+            entropy_thold: finite_or(self.entropy_thold, DEFAULT_ENTROPY_THOLD).clamp(0.0, 10.0),
+            logprob_thold: finite_or(self.logprob_thold, DEFAULT_LOGPROB_THOLD).clamp(-10.0, 0.0),
+        }
+    }
+}
+
+/// The language whisper decodes as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    /// English, with auto-detection only as a last retry when English
+    /// decoding produced nothing.
+    #[default]
+    EnglishFirst,
+    /// Whisper detects the language of each recording. Multilingual models only.
+    AutoDetect,
+    /// Always decode as this ISO 639-1 code.
+    Fixed(&'static str),
+}
+
+impl Language {
+    /// Parses the `language` setting: unset for the default, "auto", or a
+    /// language code whisper knows.
+    pub fn from_setting(raw: Option<&str>) -> Result<Self> {
+        let Some(raw) = raw.map(str::trim).filter(|raw| !raw.is_empty()) else {
+            return Ok(Language::EnglishFirst);
+        };
+        if raw.eq_ignore_ascii_case("auto") {
+            return Ok(Language::AutoDetect);
+        }
+        whisper_rs::get_lang_id(&raw.to_ascii_lowercase())
+            .and_then(whisper_rs::get_lang_str)
+            .map(Language::Fixed)
+            .ok_or_else(|| anyhow!("Unknown language code '{raw}'"))
+    }
+
+    /// Whether an English-only (`.en`) model can decode this.
+    pub fn english_only_ok(self) -> bool {
+        matches!(self, Language::EnglishFirst | Language::Fixed("en"))
+    }
+}
+
+/// Per-run knobs for `transcribe`.
+#[derive(Debug, Clone, Default)]
+pub struct TranscribeOptions {
+    pub mode: TranscriptionMode,
+    pub language: Language,
+    /// Use fewer threads at background priority so foreground apps stay
+    /// responsive while whisper runs.
+    pub low_priority: bool,
+    /// Ask whisper for per-segment timestamps instead of one block per window.
+    pub timestamps: bool,
+    pub decode: DecodeParams,
+    /// Names and jargon to steer spelling, passed to whisper as preceding
+    /// text. At most `MAX_INITIAL_PROMPT_CHARS`.
+    pub initial_prompt: Option<String>,
+    /// Load the model on the GPU when the build has a GPU backend; see
+    /// `gpu_backend`.
+    pub use_gpu: bool,
+    /// Pin everything that can vary between runs; see `reproducible`.
+    pub deterministic: bool,
+}
+
+/// Longest accepted initial prompt. whisper gives the prompt at most half of
+/// its 448-token text context and drops the start of anything longer, and
+/// every prompt token is one less for the transcript of a 30 s window.
+pub const MAX_INITIAL_PROMPT_CHARS: usize = 500;
+
+/// Thread count used in deterministic mode, whatever the machine has. Float
+/// sums split across a different number of threads can round differently.
+pub const DETERMINISTIC_THREADS: i32 = 4;
+
+impl TranscribeOptions {
+    /// The options actually decoded with. Deterministic mode forces greedy
+    /// best_of 1 at temperature 0 without fallback, the only whisper path
+    /// that never samples. whisper.cpp seeds its sampler with a fixed value
+    /// per state and whisper-rs does not expose a seed, so nothing else
+    /// needs pinning.
+    fn reproducible(self) -> Self {
+        if !self.deterministic {
+            return self;
+        }
+        Self {
+            decode: DecodeParams {
+                best_of: 1,
+                beam_size: None,
+                temperature_fallback: false,
+                temperature: 0.0,
+                ..self.decode
+            },
+            ..self
+        }
+    }
+}
+
+impl Default for DecodeParams {
+    fn default() -> Self {
+        LatencyPriority::default().decode_params()
+    }
+}
+
+/// One decoded segment, with times relative to the start of the audio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Segment {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: String,
+    /// Mean probability of the segment's text tokens, from 0 to 1.
+    #[serde(default)]
+    pub avg_prob: f32,
+}
+
+/// Where the time inside one `transcribe` call went.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TranscribeTiming {
+    /// Resampling and level normalization.
+    pub prepare_ms: u64,
+    pub model_load_ms: u64,
+    /// The model was already loaded; `model_load_ms` is only the lookup.
+    pub model_cached: bool,
+    /// Every decode attempt, including retries.
+    pub decode_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Transcript {
+    pub segments: Vec<Segment>,
+    /// Language code the audio was decoded as ("en", "de", ...). Whisper's
+    /// own guess when the decode auto-detected it.
+    pub language: Option<String>,
+    /// Whether whisper decoded segment timestamps. Without them each segment
+    /// spans a whole 30 second window, too coarse for captions.
+    pub timed: bool,
+    pub timing: TranscribeTiming,
+}
+
+impl Transcript {
+    /// Segment texts joined with single spaces.
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Mean token probability across the transcript, from 0 to 1. Segments
+    /// only keep their own mean, so each is weighted by its text length as a
+    /// stand-in for its token count. `None` when there is no text.
+    pub fn avg_confidence(&self) -> Option<f64> {
+        let (weighted, chars) =
+            self.segments
+                .iter()
+                .fold((0.0_f64, 0_usize), |(weighted, chars), segment| {
+                    let len = segment.text.chars().count();
+                    (
+                        weighted + f64::from(segment.avg_prob) * len as f64,
+                        chars + len,
+                    )
+                });
+        (chars > 0).then(|| weighted / chars as f64)
+    }
+}
+
+/// whisper drops disfluencies unless the context suggests they belong in the
+/// transcript, so verbatim decoding is primed with a disfluent prompt.
+const VERBATIM_PROMPT: &str = "Umm, let me think like, hmm... Okay, here's what I'm, uh, thinking.";
+
 #[derive(Clone, Copy)]
 enum LanguageMode {
-    English,
+    Fixed(&'static str),
     AutoDetect,
 }
 
+impl Language {
+    /// Languages for the first decode and for the last-resort retry.
+    fn attempt_modes(self) -> (LanguageMode, LanguageMode) {
+        match self {
+            Language::EnglishFirst => (LanguageMode::Fixed("en"), LanguageMode::AutoDetect),
+            Language::AutoDetect => (LanguageMode::AutoDetect, LanguageMode::AutoDetect),
+            Language::Fixed(code) => (LanguageMode::Fixed(code), LanguageMode::Fixed(code)),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
 struct DecodeAttempt {
     language: LanguageMode,
-    best_of: i32,
+    decode: DecodeParams,
     threads: i32,
 }
 
@@ -23,12 +329,391 @@ pub fn transcribe(
     model_path: &Path,
     input: &[f32],
     sample_rate: u32,
+    options: TranscribeOptions,
+    cancel_flag: Option<Arc<AtomicBool>>,
+) -> Result<Transcript> {
+    let options = options.reproducible();
+    let Some(loaded) = load(model_path, input, sample_rate, options.use_gpu)? else {
+        return Ok(Transcript::default());
+    };
+    check_language(&loaded.ctx, model_path, options.language)?;
+    let mut timing = loaded.timing;
+
+    let threads = decode_threads(&options);
+    // whisper.cpp's worker threads are spawned from this one. On macOS they
+    // inherit its QoS class; Windows thread priority is not inherited, so there
+    // only this thread is lowered and the reduced thread count does the rest.
+    // Dropped at the end of this function, restoring the pool thread.
+    let _priority = options.low_priority.then(BackgroundPriority::enter);
+
+    let stage_started = Instant::now();
+    let mut transcript = decode_with_retries(
+        &loaded.ctx,
+        &loaded.audio_16k,
+        cancel_flag,
+        &options,
+        threads,
+    )?;
+    timing.decode_ms = stage_started.elapsed().as_millis() as u64;
+    transcript.timing = timing;
+    Ok(transcript)
+}
+
+/// Audio is decoded in windows of this length by `transcribe_chunked`,
+/// matching the window whisper itself works in.
+pub const CHUNK_SAMPLES_16K: usize = 30 * 16_000;
+
+/// Like `transcribe`, but decodes long audio in `CHUNK_SAMPLES_16K` pieces
+/// and calls `on_chunk(index, total, transcript_so_far)` after each one.
+/// Setting `cancel_flag` stops the job at the next chunk with an error.
+/// Chunks are cut at fixed offsets, so a word on a boundary can be split.
+pub fn transcribe_chunked<F>(
+    model_path: &Path,
+    input: &[f32],
+    sample_rate: u32,
+    options: TranscribeOptions,
+    cancel_flag: Option<Arc<AtomicBool>>,
+    mut on_chunk: F,
+) -> Result<Transcript>
+where
+    F: FnMut(usize, usize, &Transcript),
+{
+    let options = options.reproducible();
+    let Some(loaded) = load(model_path, input, sample_rate, options.use_gpu)? else {
+        return Ok(Transcript::default());
+    };
+    check_language(&loaded.ctx, model_path, options.language)?;
+    let mut timing = loaded.timing;
+
+    let threads = decode_threads(&options);
+    let _priority = options.low_priority.then(BackgroundPriority::enter);
+
+    let stage_started = Instant::now();
+    let chunks: Vec<&[f32]> = loaded.audio_16k.chunks(CHUNK_SAMPLES_16K).collect();
+    let mut transcript = Transcript::default();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let cancelled = || {
+            cancel_flag
+                .as_ref()
+                .is_some_and(|flag| flag.load(Ordering::Relaxed))
+        };
+        if cancelled() {
+            return Err(anyhow!("Transcription cancelled"));
+        }
+
+        let decoded =
+            decode_with_retries(&loaded.ctx, chunk, cancel_flag.clone(), &options, threads)?;
+        if cancelled() {
+            return Err(anyhow!("Transcription cancelled"));
+        }
+
+        let offset_ms = (index * CHUNK_SAMPLES_16K / 16) as i64;
+        if transcript.language.is_none() {
+            transcript.language = decoded.language;
+        }
+        transcript
+            .segments
+            .extend(decoded.segments.into_iter().map(|segment| Segment {
+                start_ms: segment.start_ms + offset_ms,
+                end_ms: segment.end_ms + offset_ms,
+                ..segment
+            }));
+        on_chunk(index, chunks.len(), &transcript);
+    }
+
+    timing.decode_ms = stage_started.elapsed().as_millis() as u64;
+    transcript.timing = timing;
+    Ok(transcript)
+}
+
+/// Like `transcribe`, but always asks whisper for segment timestamps and
+/// returns the timed segments, for views that link text back to the audio.
+/// Slower than the plain path, which skips timestamp decoding.
+pub fn transcribe_with_segments(
+    model_path: &Path,
+    input: &[f32],
+    sample_rate: u32,
+    options: TranscribeOptions,
     cancel_flag: Option<Arc<AtomicBool>>,
-) -> Result<String> {
-    const MIN_AUDIO_SAMPLES_16K: usize = 3_200; // 200ms at 16kHz
+) -> Result<Vec<Segment>> {
+    let options = TranscribeOptions {
+        timestamps: true,
+        ..options
+    };
+    Ok(transcribe(model_path, input, sample_rate, options, cancel_flag)?.segments)
+}
 
+/// Time spent in each stage of one `profile` run, measured separately.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PipelineProfile {
+    pub audio_ms: i64,
+    pub source_rate: u32,
+    pub resample_ms: u64,
+    /// Level normalization on the resampled audio.
+    pub preprocess_ms: u64,
+    pub model_load_ms: u64,
+    pub model_cached: bool,
+    /// One decode with the primary settings; no retries.
+    pub decode_ms: u64,
+    pub threads: i32,
+    pub text: String,
+}
+
+/// Runs the pipeline once on `input`, timing resampling, preprocessing,
+/// model load, and decode on their own, for finding where time goes.
+pub fn profile(
+    model_path: &Path,
+    input: &[f32],
+    sample_rate: u32,
+    options: TranscribeOptions,
+) -> Result<PipelineProfile> {
     if input.is_empty() {
-        return Ok(String::new());
+        return Err(anyhow!("Audio file is empty"));
+    }
+    let options = options.reproducible();
+    let mut profile = PipelineProfile {
+        audio_ms: input.len() as i64 * 1000 / i64::from(sample_rate.max(1)),
+        source_rate: sample_rate,
+        threads: decode_threads(&options),
+        ..PipelineProfile::default()
+    };
+
+    let stage_started = Instant::now();
+    let resampled = resample_to_16k(input, sample_rate);
+    profile.resample_ms = stage_started.elapsed().as_millis() as u64;
+
+    let stage_started = Instant::now();
+    let mut audio_16k = preprocess_audio(&resampled);
+    profile.preprocess_ms = stage_started.elapsed().as_millis() as u64;
+    pad_with_silence(&mut audio_16k, MIN_DECODE_SAMPLES_16K);
+
+    let stage_started = Instant::now();
+    let (ctx, origin) = cached_context(model_path, options.use_gpu)?;
+    profile.model_load_ms = stage_started.elapsed().as_millis() as u64;
+    profile.model_cached = origin == ContextOrigin::Cached;
+    check_language(&ctx, model_path, options.language)?;
+
+    let _priority = options.low_priority.then(BackgroundPriority::enter);
+    let attempt = DecodeAttempt {
+        language: options.language.attempt_modes().0,
+        decode: options.decode,
+        threads: profile.threads,
+    };
+    let stage_started = Instant::now();
+    let transcript = decode_once(&ctx, &audio_16k, None, &options, attempt)?;
+    profile.decode_ms = stage_started.elapsed().as_millis() as u64;
+    profile.text = transcript.text();
+    Ok(profile)
+}
+
+/// A model loaded next to its prepared audio, ready to decode.
+struct Loaded {
+    ctx: Arc<WhisperContext>,
+    audio_16k: Vec<f32>,
+    timing: TranscribeTiming,
+}
+
+/// The last model used, kept loaded so back-to-back transcriptions skip
+/// reading the ggml file again. The tradeoff is memory: the model's full size
+/// (about 150 MB for base.en, 3 GB for large-v3) stays resident while the app
+/// runs. Only one model is kept, and `clear_context_cache` releases it.
+static CONTEXT_CACHE: Mutex<Option<CachedContext>> = Mutex::new(None);
+
+/// Held while a model loads, so two transcriptions starting together load it
+/// once. `CONTEXT_CACHE` itself is only locked briefly, so clearing it never
+/// waits for a load.
+static CONTEXT_LOAD: Mutex<()> = Mutex::new(());
+
+/// Bumped whenever the cache is cleared. A load that started under an older
+/// generation is not cached when it finishes.
+static CONTEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Set when a model failed to load on the GPU and was loaded on the CPU
+/// instead, until `take_gpu_fallback` reports it.
+static GPU_FALLBACK: AtomicBool = AtomicBool::new(false);
+
+struct CachedContext {
+    path: PathBuf,
+    /// Modification time at load, so a file replaced in place is reloaded.
+    modified: Option<SystemTime>,
+    /// The GPU setting it was loaded for, so toggling it reloads the model.
+    use_gpu: bool,
+    ctx: Arc<WhisperContext>,
+}
+
+/// The GPU backend whisper.cpp was built with ("Metal", "CUDA", "ROCm"), or
+/// `None` when it only runs on the CPU. Metal comes from the `metal` feature
+/// of whisper-rs, enabled in Cargo.toml and used on macOS; CUDA and ROCm need
+/// this crate's `cuda` or `hipblas` feature and their SDKs at build time.
+pub fn gpu_backend() -> Option<&'static str> {
+    if cfg!(feature = "cuda") {
+        Some("CUDA")
+    } else if cfg!(feature = "hipblas") {
+        Some("ROCm")
+    } else if cfg!(target_os = "macos") {
+        Some("Metal")
+    } else {
+        None
+    }
+}
+
+/// Whether a model has fallen back to the CPU since the last call.
+pub fn take_gpu_fallback() -> bool {
+    GPU_FALLBACK.swap(false, Ordering::Relaxed)
+}
+
+/// Where `cached_context` got its context from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContextOrigin {
+    Cached,
+    Loaded,
+    /// Loaded, but the cache was cleared meanwhile, so it was not kept.
+    Superseded,
+}
+
+/// The context for `model_path`, loaded from disk unless it is the cached
+/// one.
+fn cached_context(
+    model_path: &Path,
+    use_gpu: bool,
+) -> Result<(Arc<WhisperContext>, ContextOrigin)> {
+    let modified = std::fs::metadata(model_path)
+        .and_then(|meta| meta.modified())
+        .ok();
+    let _loading = CONTEXT_LOAD.lock().unwrap_or_else(PoisonError::into_inner);
+    let generation = CONTEXT_GENERATION.load(Ordering::SeqCst);
+    {
+        let mut cache = CONTEXT_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(cached) = cache.as_ref().filter(|cached| {
+            cached.path == model_path && cached.modified == modified && cached.use_gpu == use_gpu
+        }) {
+            return Ok((cached.ctx.clone(), ContextOrigin::Cached));
+        }
+        // Release the previous model first so two are never resident at once
+        // (unless a running transcription still holds it).
+        *cache = None;
+    }
+
+    let load_on = |gpu: bool| {
+        let mut params = WhisperContextParameters::default();
+        params.use_gpu(gpu);
+        WhisperContext::new_with_params(model_path.to_string_lossy().as_ref(), params)
+    };
+    let on_gpu = use_gpu && gpu_backend().is_some();
+    let ctx = match load_on(on_gpu) {
+        // GPU init fails on drivers or devices whisper.cpp cannot use; the
+        // CPU still works, only slower.
+        Err(err) if on_gpu => {
+            logging::warn(format!(
+                "whisper: GPU load of {} failed, using the CPU: {err}",
+                model_path.display()
+            ));
+            GPU_FALLBACK.store(true, Ordering::Relaxed);
+            load_on(false)
+        }
+        result => result,
+    }
+    .inspect_err(|err| {
+        logging::error(format!(
+            "whisper: failed to load {}: {err}",
+            model_path.display()
+        ))
+    })?;
+    let ctx = Arc::new(ctx);
+
+    let mut cache = CONTEXT_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    if CONTEXT_GENERATION.load(Ordering::SeqCst) != generation {
+        return Ok((ctx, ContextOrigin::Superseded));
+    }
+    *cache = Some(CachedContext {
+        path: model_path.to_path_buf(),
+        modified,
+        use_gpu,
+        ctx: ctx.clone(),
+    });
+    Ok((ctx, ContextOrigin::Loaded))
+}
+
+/// How `warm_up` went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarmUp {
+    /// The model was already loaded.
+    Cached,
+    Loaded,
+    /// The model changed while it loaded, so it was dropped again.
+    Superseded,
+}
+
+/// Loads `model_path` into the context cache and decodes a second of
+/// silence with it, so the first real transcription skips both the model
+/// load and whisper's first-run setup.
+pub fn warm_up(model_path: &Path, use_gpu: bool) -> Result<WarmUp> {
+    let ctx = match cached_context(model_path, use_gpu)? {
+        (_, ContextOrigin::Cached) => return Ok(WarmUp::Cached),
+        (_, ContextOrigin::Superseded) => return Ok(WarmUp::Superseded),
+        (ctx, ContextOrigin::Loaded) => ctx,
+    };
+    let mut state = ctx.create_state()?;
+    let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+    params.set_n_threads(decode_threads(&TranscribeOptions::default()));
+    params.set_language(Some("en"));
+    params.set_no_context(true);
+    params.set_single_segment(true);
+    params.set_print_special(false);
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    state.full(params, &vec![0.0; MIN_DECODE_SAMPLES_16K])?;
+    Ok(WarmUp::Loaded)
+}
+
+/// Drops the cached model if it is `model_path`, e.g. a model that was just
+/// deleted. A load still in progress is not cached either.
+pub fn release_context(model_path: &Path) {
+    CONTEXT_GENERATION.fetch_add(1, Ordering::SeqCst);
+    let mut cache = CONTEXT_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    if cache
+        .as_ref()
+        .is_some_and(|cached| cached.path == model_path)
+    {
+        *cache = None;
+    }
+}
+
+/// Drops the cached model, freeing its memory once no transcription is
+/// using it. A load still in progress is not cached either.
+pub fn clear_context_cache() {
+    CONTEXT_GENERATION.fetch_add(1, Ordering::SeqCst);
+    CONTEXT_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+}
+
+/// Refuses to decode a non-English language with an English-only model,
+/// which would silently produce English anyway.
+fn check_language(ctx: &WhisperContext, model_path: &Path, language: Language) -> Result<()> {
+    if ctx.is_multilingual() || language.english_only_ok() {
+        return Ok(());
+    }
+    let name = model_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Err(anyhow!(
+        "{name} only understands English. Choose a multilingual model (one without \".en\") or set the language back to English."
+    ))
+}
+
+/// Prepares `input` and loads the model. `None` means there is nothing to
+/// decode.
+fn load(
+    model_path: &Path,
+    input: &[f32],
+    sample_rate: u32,
+    use_gpu: bool,
+) -> Result<Option<Loaded>> {
+    if input.is_empty() {
+        return Ok(None);
     }
 
     if !model_path.exists() {
@@ -38,51 +723,82 @@ pub fn transcribe(
         ));
     }
 
-    let audio_16k = preprocess_audio(&resample_to_16k(input, sample_rate));
+    let mut timing = TranscribeTiming::default();
+    let stage_started = Instant::now();
+    let audio_16k = prepare_audio(input, sample_rate);
     if audio_16k.is_empty() {
-        return Ok(String::new());
-    }
-    if audio_16k.len() < MIN_AUDIO_SAMPLES_16K {
-        // Very short captures often fail inside whisper with a generic error.
-        return Ok(String::new());
+        return Ok(None);
     }
+    timing.prepare_ms = stage_started.elapsed().as_millis() as u64;
 
-    let model_path_str = model_path.to_string_lossy();
-    let ctx = WhisperContext::new_with_params(
-        model_path_str.as_ref(),
-        WhisperContextParameters::default(),
-    )?;
+    let stage_started = Instant::now();
+    let (ctx, origin) = cached_context(model_path, use_gpu)?;
+    timing.model_load_ms = stage_started.elapsed().as_millis() as u64;
+    timing.model_cached = origin == ContextOrigin::Cached;
 
+    Ok(Some(Loaded {
+        ctx,
+        audio_16k,
+        timing,
+    }))
+}
+
+fn decode_threads(options: &TranscribeOptions) -> i32 {
+    if options.deterministic {
+        return DETERMINISTIC_THREADS;
+    }
     let threads = std::thread::available_parallelism()
         .map(|n| n.get().clamp(1, 6) as i32)
         .unwrap_or(4);
+    if options.low_priority {
+        (threads / 2).max(1)
+    } else {
+        threads
+    }
+}
+
+fn decode_with_retries(
+    ctx: &WhisperContext,
+    audio_16k: &[f32],
+    cancel_flag: Option<Arc<AtomicBool>>,
+    options: &TranscribeOptions,
+    threads: i32,
+) -> Result<Transcript> {
+    let mut padded = audio_16k.to_vec();
+    pad_with_silence(&mut padded, MIN_DECODE_SAMPLES_16K);
 
     // Retry with progressively simpler decode settings when whisper returns
     // known transient decode failures (notably -7 on some systems/models).
+    let retry = DecodeParams {
+        best_of: 1,
+        beam_size: None,
+        ..options.decode
+    };
+    let (language, last_resort) = options.language.attempt_modes();
     let attempts = [
         DecodeAttempt {
-            language: LanguageMode::English,
-            best_of: 2,
+            language,
+            decode: options.decode,
             threads,
         },
         DecodeAttempt {
-            language: LanguageMode::English,
-            best_of: 1,
+            language,
+            decode: retry,
             threads: threads.clamp(1, 3),
         },
         DecodeAttempt {
-            language: LanguageMode::AutoDetect,
-            best_of: 1,
+            language: last_resort,
+            decode: retry,
             threads: threads.clamp(1, 3),
         },
     ];
 
     let mut saw_recoverable_decode_error = false;
     for attempt in attempts {
-        match decode_once(&ctx, &audio_16k, cancel_flag.clone(), attempt) {
-            Ok(text) => {
-                if !text.trim().is_empty() {
-                    return Ok(text);
+        match decode_once(ctx, &padded, cancel_flag.clone(), options, attempt) {
+            Ok(transcript) => {
+                if !transcript.is_empty() {
+                    return Ok(transcript);
                 }
             }
             Err(WhisperError::GenericError(-6)) | Err(WhisperError::GenericError(-7)) => {
@@ -93,41 +809,158 @@ pub fn transcribe(
     }
 
     if saw_recoverable_decode_error {
-        eprintln!("whisper: decode produced recoverable errors (-6/-7) across all attempts");
+        logging::warn("whisper: decode produced recoverable errors (-6/-7) across all attempts");
+    }
+
+    Ok(Transcript::default())
+}
+
+/// Lowers the current thread's scheduling priority until dropped. Threads it
+/// spawns meanwhile inherit the lower QoS class on macOS, but not on Windows.
+struct BackgroundPriority;
+
+#[cfg(target_os = "macos")]
+mod qos {
+    pub const QOS_CLASS_DEFAULT: u32 = 0x15;
+    pub const QOS_CLASS_UTILITY: u32 = 0x11;
+
+    extern "C" {
+        pub fn pthread_set_qos_class_self_np(qos_class: u32, relative_priority: i32) -> i32;
     }
+}
 
-    Ok(String::new())
+#[cfg(target_os = "windows")]
+mod qos {
+    pub const THREAD_PRIORITY_NORMAL: i32 = 0;
+    pub const THREAD_PRIORITY_BELOW_NORMAL: i32 = -1;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        pub fn GetCurrentThread() -> *mut std::ffi::c_void;
+        pub fn SetThreadPriority(thread: *mut std::ffi::c_void, priority: i32) -> i32;
+    }
+}
+
+impl BackgroundPriority {
+    fn enter() -> Self {
+        #[cfg(target_os = "macos")]
+        unsafe {
+            qos::pthread_set_qos_class_self_np(qos::QOS_CLASS_UTILITY, 0);
+        }
+        #[cfg(target_os = "windows")]
+        unsafe {
+            qos::SetThreadPriority(qos::GetCurrentThread(), qos::THREAD_PRIORITY_BELOW_NORMAL);
+        }
+        // Linux can lower a thread's nice value but not raise it back without
+        // CAP_SYS_NICE, which would leave the pooled thread demoted; there the
+        // reduced thread count is the only change.
+        BackgroundPriority
+    }
+}
+
+impl Drop for BackgroundPriority {
+    fn drop(&mut self) {
+        #[cfg(target_os = "macos")]
+        unsafe {
+            qos::pthread_set_qos_class_self_np(qos::QOS_CLASS_DEFAULT, 0);
+        }
+        #[cfg(target_os = "windows")]
+        unsafe {
+            qos::SetThreadPriority(qos::GetCurrentThread(), qos::THREAD_PRIORITY_NORMAL);
+        }
+    }
+}
+
+/// Predicts a (low, high) transcription time in milliseconds for `duration_ms`
+/// of audio. Measured realtime factors win; otherwise the model size drives a
+/// deliberately wide guess.
+pub fn estimate_transcription_ms(
+    duration_ms: u64,
+    realtime_factors: &[f64],
+    approx_params_millions: Option<u32>,
+) -> (u64, u64) {
+    let audio = duration_ms as f64;
+    let finite = realtime_factors
+        .iter()
+        .copied()
+        .filter(|rtf| rtf.is_finite() && *rtf > 0.0);
+    let (low_rtf, high_rtf) = match finite.fold(None, |range: Option<(f64, f64)>, rtf| {
+        Some(range.map_or((rtf, rtf), |(low, high)| (low.min(rtf), high.max(rtf))))
+    }) {
+        Some((low, high)) => (low * 0.9, high * 1.1),
+        None => {
+            let mut rtf = approx_params_millions.map_or(1.0, |params| {
+                f64::from(params) * HEURISTIC_RTF_PER_MILLION_PARAMS
+            });
+            if GPU_COMPILED {
+                rtf /= 3.0;
+            }
+            (rtf * 0.5, rtf * 2.0)
+        }
+    };
+
+    (
+        (audio * low_rtf).round() as u64,
+        (audio * high_rtf).round() as u64,
+    )
+}
+
+/// whisper.cpp's own summary of the CPU/GPU features it was compiled with.
+pub fn backend_info() -> String {
+    whisper_rs::print_system_info().trim().to_string()
 }
 
 fn decode_once(
     ctx: &WhisperContext,
     audio_16k: &[f32],
     cancel_flag: Option<Arc<AtomicBool>>,
+    options: &TranscribeOptions,
     attempt: DecodeAttempt,
-) -> std::result::Result<String, WhisperError> {
+) -> std::result::Result<Transcript, WhisperError> {
     let mut state = ctx.create_state()?;
-    let mut params = FullParams::new(SamplingStrategy::Greedy {
-        best_of: attempt.best_of,
-    });
+    let strategy = match attempt.decode.beam_size {
+        Some(beam_size) => SamplingStrategy::BeamSearch {
+            beam_size,
+            patience: -1.0,
+        },
+        None => SamplingStrategy::Greedy {
+            best_of: attempt.decode.best_of,
+        },
+    };
+    let mut params = FullParams::new(strategy);
 
     params.set_n_threads(attempt.threads);
     params.set_translate(false);
+    // No language means whisper detects it. `set_detect_language` is not
+    // used: it makes whisper stop after detection without transcribing.
     match attempt.language {
-        LanguageMode::English => {
-            params.set_language(Some("en"));
-        }
-        LanguageMode::AutoDetect => {
-            params.set_language(None);
-            params.set_detect_language(true);
-        }
+        LanguageMode::Fixed(code) => params.set_language(Some(code)),
+        LanguageMode::AutoDetect => params.set_language(None),
     }
     params.set_no_context(true);
-    params.set_no_timestamps(true);
+    params.set_no_timestamps(!options.timestamps);
     params.set_suppress_blank(true);
-    params.set_temperature(0.0);
+    params.set_temperature(attempt.decode.temperature);
+    params.set_entropy_thold(attempt.decode.entropy_thold);
+    params.set_logprob_thold(attempt.decode.logprob_thold);
+    if !attempt.decode.temperature_fallback {
+        params.set_temperature_inc(0.0);
+    }
     params.set_print_special(false);
     params.set_print_progress(false);
     params.set_print_realtime(false);
+    let verbatim = options.mode == TranscriptionMode::Verbatim;
+    // The vocabulary goes first: whisper continues from the end of the
+    // prompt, which has to stay disfluent for verbatim mode to work.
+    let prompt = match (options.initial_prompt.as_deref(), verbatim) {
+        (Some(vocabulary), true) => Some(format!("{vocabulary} {VERBATIM_PROMPT}")),
+        (Some(vocabulary), false) => Some(vocabulary.to_string()),
+        (None, true) => Some(VERBATIM_PROMPT.to_string()),
+        (None, false) => None,
+    };
+    if let Some(prompt) = &prompt {
+        params.set_initial_prompt(prompt);
+    }
 
     if let Some(cancel_flag) = cancel_flag {
         params.set_abort_callback_safe(move || cancel_flag.load(Ordering::Relaxed));
@@ -135,7 +968,18 @@ fn decode_once(
 
     state.full(params, audio_16k)?;
 
-    let mut text = String::new();
+    let mut transcript = Transcript {
+        language: match attempt.language {
+            LanguageMode::Fixed(code) => Some(code.to_string()),
+            LanguageMode::AutoDetect => state
+                .full_lang_id_from_state()
+                .ok()
+                .and_then(whisper_rs::get_lang_str)
+                .map(str::to_string),
+        },
+        timed: options.timestamps,
+        ..Transcript::default()
+    };
     let n_segments = state.full_n_segments()?;
     for idx in 0..n_segments {
         let segment = state.full_get_segment_text(idx)?;
@@ -144,13 +988,99 @@ fn decode_once(
             continue;
         }
 
-        if !text.is_empty() {
-            text.push(' ');
+        // Special tokens (timestamps, end of text) carry no speech.
+        let mut prob_sum = 0.0_f32;
+        let mut text_tokens = 0_u32;
+        for token in 0..state.full_n_tokens(idx)? {
+            if state.full_get_token_id(idx, token)? >= ctx.token_eot() {
+                continue;
+            }
+            prob_sum += state.full_get_token_prob(idx, token)?;
+            text_tokens += 1;
         }
-        text.push_str(trimmed);
+
+        // whisper reports segment bounds in 10 ms units.
+        transcript.segments.push(Segment {
+            start_ms: state.full_get_segment_t0(idx)? * 10,
+            end_ms: state.full_get_segment_t1(idx)? * 10,
+            text: trimmed.to_string(),
+            avg_prob: if text_tokens == 0 {
+                0.0
+            } else {
+                prob_sum / text_tokens as f32
+            },
+        });
     }
 
-    Ok(text)
+    transcript.segments = collapse_repeated_segments(transcript.segments);
+    Ok(transcript)
+}
+
+/// Shorter segments ("No." "No.") are left alone; repeating them is often
+/// what the speaker actually said.
+const MIN_REPEAT_WORDS: usize = 3;
+
+/// Merges a segment into the one before it when either repeats the other, a
+/// known whisper failure mode on some audio. The longer text is kept and the
+/// end time is extended over both. Repetition inside a segment ("very very
+/// good") is untouched.
+fn collapse_repeated_segments(segments: Vec<Segment>) -> Vec<Segment> {
+    let mut kept: Vec<Segment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if let Some(previous) = kept.last_mut() {
+            if is_repetition(&previous.text, &segment.text) {
+                previous.end_ms = previous.end_ms.max(segment.end_ms);
+                if segment.text.len() > previous.text.len() {
+                    previous.text = segment.text;
+                }
+                continue;
+            }
+        }
+        kept.push(segment);
+    }
+    kept
+}
+
+fn is_repetition(previous: &str, current: &str) -> bool {
+    let words = |text: &str| -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .flat_map(char::to_lowercase)
+                    .collect::<String>()
+            })
+            .filter(|word| !word.is_empty())
+            .collect()
+    };
+    let (a, b) = (words(previous), words(current));
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if shorter.len() < MIN_REPEAT_WORDS {
+        return false;
+    }
+    // Only the same words again, or a run of them, count: "Add milk to the
+    // list." followed by "Add eggs to the list." is two things said.
+    longer
+        .windows(shorter.len())
+        .any(|window| window == shorter.as_slice())
+}
+
+/// Converts a mono capture at any device rate into the 16kHz buffer whisper
+/// decodes. Every level-based step runs after resampling so a 44.1kHz and a
+/// 48kHz mic produce the same input for the same sound.
+pub fn prepare_audio(input: &[f32], sample_rate: u32) -> Vec<f32> {
+    preprocess_audio(&resample_to_16k(input, sample_rate))
+}
+
+/// Whisper decodes sub-second input unreliably (often a generic error), so
+/// shorter clips are padded up to one second.
+const MIN_DECODE_SAMPLES_16K: usize = 16_000;
+
+/// Appends trailing silence until `samples` holds at least `min_len` samples.
+fn pad_with_silence(samples: &mut Vec<f32>, min_len: usize) {
+    if samples.len() < min_len {
+        samples.resize(min_len, 0.0);
+    }
 }
 
 fn preprocess_audio(samples: &[f32]) -> Vec<f32> {
@@ -183,7 +1113,7 @@ fn preprocess_audio(samples: &[f32]) -> Vec<f32> {
         let gain = (0.05 / rms).clamp(1.0, 12.0);
         if gain > 1.05 {
             for sample in &mut out {
-                *sample = (*sample * gain).clamp(-1.0, 1.0);
+                *sample = soft_limit(*sample * gain);
             }
         }
     }
@@ -191,7 +1121,36 @@ fn preprocess_audio(samples: &[f32]) -> Vec<f32> {
     out
 }
 
-fn resample_to_16k(input: &[f32], source_rate: u32) -> Vec<f32> {
+/// Level below which the limiter leaves samples untouched.
+const LIMITER_KNEE: f32 = 0.8;
+
+/// Passes samples under the knee through unchanged and bends anything louder
+/// smoothly toward ±1 with `tanh`, so boosted peaks round off instead of
+/// flattening into hard-clipped edges.
+fn soft_limit(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= LIMITER_KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - LIMITER_KNEE;
+    let limited = LIMITER_KNEE + headroom * ((magnitude - LIMITER_KNEE) / headroom).tanh();
+    limited.copysign(sample)
+}
+
+/// Half the resampling filter length, counted in samples at the lower of the
+/// two rates.
+const RESAMPLE_HALF_TAPS: usize = 16;
+/// Passband edge as a share of the lower Nyquist frequency; the remainder is
+/// the filter's transition band.
+const RESAMPLE_PASSBAND: f64 = 0.92;
+/// Filter phases precomputed per conversion. Rates whose exact phase count is
+/// larger have their fractional positions rounded to this many steps.
+const RESAMPLE_MAX_PHASES: u64 = 512;
+
+/// Band-limited resampling to 16 kHz with a Blackman-windowed sinc filter.
+/// Content above 8 kHz is removed before decimating instead of folding back
+/// into the speech band as aliasing.
+pub fn resample_to_16k(input: &[f32], source_rate: u32) -> Vec<f32> {
     const TARGET_RATE: u32 = 16_000;
 
     if source_rate == TARGET_RATE {
@@ -202,19 +1161,348 @@ fn resample_to_16k(input: &[f32], source_rate: u32) -> Vec<f32> {
         return Vec::new();
     }
 
-    let ratio = source_rate as f64 / TARGET_RATE as f64;
-    let output_len = ((input.len() as f64) / ratio).floor() as usize;
+    let source = u64::from(source_rate);
+    let target = u64::from(TARGET_RATE);
+    let output_len = (input.len() as u64 * target).div_ceil(source) as usize;
 
+    // Cutoff in cycles per input sample. When downsampling, the kernel is
+    // stretched by the same factor so the transition band stays narrow at
+    // the output rate.
+    let scale = (target as f64 / source as f64).min(1.0);
+    let cutoff = 0.5 * scale * RESAMPLE_PASSBAND;
+    let radius = (RESAMPLE_HALF_TAPS as f64 / scale).ceil() as usize;
+    let phases = (target / gcd(source, target)).min(RESAMPLE_MAX_PHASES);
+    let kernels: Vec<Vec<f32>> = (0..phases)
+        .map(|phase| {
+            let frac = phase as f64 / phases as f64;
+            let taps: Vec<f64> = (0..2 * radius)
+                .map(|tap| {
+                    // Distance from the output position to input sample
+                    // `idx + 1 - radius + tap`.
+                    let x = frac + radius as f64 - 1.0 - tap as f64;
+                    windowed_sinc(x, cutoff, radius as f64)
+                })
+                .collect();
+            let sum: f64 = taps.iter().sum();
+            taps.iter().map(|weight| (weight / sum) as f32).collect()
+        })
+        .collect();
+
+    // Track the read position as an integer index plus a fractional phase in
+    // units of 1/TARGET_RATE. Advancing by `source_rate` per output sample keeps
+    // the position exact over arbitrarily long inputs, unlike `n as f64 * ratio`.
     let mut output = Vec::with_capacity(output_len);
-    for n in 0..output_len {
-        let src_pos = n as f64 * ratio;
-        let idx = src_pos.floor() as usize;
-        let frac = (src_pos - idx as f64) as f32;
+    let mut idx = 0_usize;
+    let mut phase = 0_u64;
+    for _ in 0..output_len {
+        let kernel = &kernels[(phase * phases / target) as usize];
+        let first = (idx + 1).checked_sub(radius);
+        let value = match first {
+            Some(first) if first + kernel.len() <= input.len() => input[first..]
+                .iter()
+                .zip(kernel)
+                .map(|(sample, weight)| sample * weight)
+                .sum(),
+            // Near either end only part of the kernel overlaps the input, so
+            // the overlapping weights are rescaled to keep unity gain.
+            _ => {
+                let (mut acc, mut total) = (0.0_f64, 0.0_f64);
+                for (tap, weight) in kernel.iter().enumerate() {
+                    let Some(sample) = (idx + 1 + tap)
+                        .checked_sub(radius)
+                        .and_then(|k| input.get(k))
+                    else {
+                        continue;
+                    };
+                    acc += f64::from(*sample) * f64::from(*weight);
+                    total += f64::from(*weight);
+                }
+                if total.abs() > f64::EPSILON {
+                    (acc / total) as f32
+                } else {
+                    0.0
+                }
+            }
+        };
+        output.push(value);
 
-        let a = *input.get(idx).unwrap_or(&0.0);
-        let b = *input.get(idx + 1).unwrap_or(&a);
-        output.push(a + (b - a) * frac);
+        phase += source;
+        idx += (phase / target) as usize;
+        phase %= target;
     }
 
     output
 }
+
+/// Low-pass sinc with cutoff `cutoff` (cycles per sample), tapered to zero at
+/// `radius` samples by a Blackman window.
+fn windowed_sinc(x: f64, cutoff: f64, radius: f64) -> f64 {
+    use std::f64::consts::PI;
+
+    if x.abs() >= radius {
+        return 0.0;
+    }
+    let sinc = if x == 0.0 {
+        2.0 * cutoff
+    } else {
+        (2.0 * PI * cutoff * x).sin() / (PI * x)
+    };
+    let position = x / radius;
+    let window = 0.42 + 0.5 * (PI * position).cos() + 0.08 * (2.0 * PI * position).cos();
+    sinc * window
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_prefers_benchmarks_over_heuristic() {
+        let (low, high) = estimate_transcription_ms(10_000, &[0.5, 1.0, f64::NAN], Some(1550));
+        assert_eq!((low, high), (4_500, 11_000));
+
+        let (low, high) = estimate_transcription_ms(10_000, &[], Some(1550));
+        assert!(low < high);
+        assert!(high > 11_000 || GPU_COMPILED);
+    }
+
+    #[test]
+    fn resample_long_input_keeps_length_and_end_alignment() {
+        const SOURCE_RATE: u32 = 44_100;
+        let len = SOURCE_RATE as usize * 60 * 5;
+        let ramp: Vec<f32> = (0..len).map(|i| i as f32 / len as f32).collect();
+
+        let out = resample_to_16k(&ramp, SOURCE_RATE);
+
+        assert_eq!(out.len(), 16_000 * 60 * 5);
+        let last = out.len() - 1;
+        let expected = (last as f64 * SOURCE_RATE as f64 / 16_000.0) / len as f64;
+        assert!((f64::from(out[last]) - expected).abs() < 1e-5);
+    }
+
+    #[test]
+    fn resample_filters_content_above_output_nyquist() {
+        use std::f32::consts::PI;
+        const SOURCE_RATE: u32 = 48_000;
+
+        // The old resampler: straight linear interpolation, no filtering.
+        let linear = |input: &[f32]| -> Vec<f32> {
+            let step = SOURCE_RATE as f32 / 16_000.0;
+            (0..input.len() * 16_000 / SOURCE_RATE as usize)
+                .map(|n| {
+                    let pos = n as f32 * step;
+                    let idx = pos as usize;
+                    let next = input.get(idx + 1).copied().unwrap_or(input[idx]);
+                    input[idx] + (next - input[idx]) * (pos - idx as f32)
+                })
+                .collect()
+        };
+        let rms = |samples: &[f32]| {
+            (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+        };
+
+        // A sweep from 9 to 20 kHz lies entirely above 8 kHz; whatever is
+        // left after resampling is aliasing.
+        let len = SOURCE_RATE as usize;
+        let mut sweep_phase = 0.0_f32;
+        let sweep: Vec<f32> = (0..len)
+            .map(|i| {
+                let freq = 9_000.0 + 11_000.0 * i as f32 / len as f32;
+                sweep_phase += 2.0 * PI * freq / SOURCE_RATE as f32;
+                0.5 * sweep_phase.sin()
+            })
+            .collect();
+        let filtered = rms(&resample_to_16k(&sweep, SOURCE_RATE)[200..15_800]);
+        let aliased = rms(&linear(&sweep)[200..15_800]);
+        assert!(aliased > 0.1, "linear aliasing rms {aliased}");
+        assert!(
+            filtered < aliased / 50.0,
+            "sinc rms {filtered} vs {aliased}"
+        );
+
+        // Speech-band content passes at full level.
+        let tone: Vec<f32> = (0..len)
+            .map(|i| 0.5 * (2.0 * PI * 1_000.0 * i as f32 / SOURCE_RATE as f32).sin())
+            .collect();
+        let passed = rms(&resample_to_16k(&tone, SOURCE_RATE)[200..15_800]);
+        assert!(
+            (passed - 0.5 / 2.0_f32.sqrt()).abs() < 0.01,
+            "tone rms {passed}"
+        );
+    }
+
+    #[test]
+    fn resample_includes_final_partial_frame() {
+        // 46 samples at 48 kHz are 15 full 3:1 frames plus one leftover
+        // sample, which still gets its own output sample.
+        let input = vec![0.5_f32; 46];
+        let out = resample_to_16k(&input, 48_000);
+
+        assert_eq!(out.len(), 16);
+        assert!(out.iter().all(|&s| (s - 0.5).abs() < f32::EPSILON));
+    }
+
+    #[test]
+    fn avg_confidence_weights_segments_by_length() {
+        let segment = |text: &str, avg_prob: f32| Segment {
+            start_ms: 0,
+            end_ms: 1_000,
+            text: text.to_string(),
+            avg_prob,
+        };
+        let transcript = Transcript {
+            segments: vec![segment("abc", 1.0), segment("a", 0.5)],
+            ..Transcript::default()
+        };
+
+        assert_eq!(transcript.avg_confidence(), Some(0.875));
+        assert_eq!(Transcript::default().avg_confidence(), None);
+    }
+
+    #[test]
+    fn collapse_repeated_segments_drops_whisper_loops_only() {
+        let segment = |start_ms: i64, text: &str| Segment {
+            start_ms,
+            end_ms: start_ms + 2_000,
+            text: text.to_string(),
+            avg_prob: 0.9,
+        };
+        let transcript = Transcript {
+            segments: collapse_repeated_segments(vec![
+                segment(0, "The results were very very good."),
+                segment(2_000, "Thank you for watching the video."),
+                segment(4_000, "thank you for watching the video"),
+                segment(6_000, "Thank you for watching!"),
+                segment(8_000, "No."),
+                segment(10_000, "No."),
+                segment(12_000, "Add milk to the list."),
+                segment(14_000, "Add eggs to the list."),
+            ]),
+            ..Transcript::default()
+        };
+
+        assert_eq!(
+            transcript.text(),
+            "The results were very very good. Thank you for watching the video. No. No. \
+             Add milk to the list. Add eggs to the list."
+        );
+        assert_eq!(transcript.segments[1].end_ms, 8_000);
+    }
+
+    #[test]
+    fn deterministic_mode_pins_threads_and_sampling() {
+        let options = TranscribeOptions {
+            deterministic: true,
+            low_priority: true,
+            decode: DecodeParams {
+                best_of: 5,
+                beam_size: Some(5),
+                temperature_fallback: true,
+                temperature: 0.4,
+                ..DecodeParams::default()
+            },
+            ..TranscribeOptions::default()
+        };
+        assert_eq!(decode_threads(&options), DETERMINISTIC_THREADS);
+        let decode = options.clone().reproducible().decode;
+        assert_eq!(decode.best_of, 1);
+        assert_eq!(decode.beam_size, None);
+        assert!(!decode.temperature_fallback);
+        assert_eq!(decode.temperature, 0.0);
+
+        let free = TranscribeOptions {
+            deterministic: false,
+            ..options
+        };
+        assert_eq!(free.clone().reproducible().decode, free.decode);
+    }
+
+    /// Needs a real model, so it only runs on demand:
+    /// `MURMUR_TEST_MODEL=/path/to/ggml-base.en.bin cargo test -- --ignored`.
+    #[test]
+    #[ignore = "needs a ggml model in MURMUR_TEST_MODEL"]
+    fn deterministic_mode_repeats_byte_identical_text() {
+        let model = std::path::PathBuf::from(
+            std::env::var("MURMUR_TEST_MODEL").expect("MURMUR_TEST_MODEL is not set"),
+        );
+        // A few seconds of a wobbling tone gives whisper something to decode.
+        let samples: Vec<f32> = (0..16_000 * 4)
+            .map(|i| {
+                let t = i as f32 / 16_000.0;
+                0.3 * (2.0 * std::f32::consts::PI * (220.0 + 40.0 * (3.0 * t).sin()) * t).sin()
+            })
+            .collect();
+        let options = TranscribeOptions {
+            deterministic: true,
+            low_priority: true,
+            ..TranscribeOptions::default()
+        };
+
+        let first = transcribe(&model, &samples, 16_000, options.clone(), None).unwrap();
+        let second = transcribe(&model, &samples, 16_000, options, None).unwrap();
+        assert_eq!(first.text().as_bytes(), second.text().as_bytes());
+        assert_eq!(first.segments, second.segments);
+    }
+
+    #[test]
+    fn pad_with_silence_extends_short_clips_only() {
+        let mut short = vec![0.5_f32; 1_600];
+        pad_with_silence(&mut short, MIN_DECODE_SAMPLES_16K);
+        assert_eq!(short.len(), MIN_DECODE_SAMPLES_16K);
+        assert_eq!(short[1_599], 0.5);
+        assert_eq!(short[1_600], 0.0);
+
+        let mut long = vec![0.5_f32; 20_000];
+        pad_with_silence(&mut long, MIN_DECODE_SAMPLES_16K);
+        assert_eq!(long.len(), 20_000);
+    }
+
+    #[test]
+    fn soft_limit_bounds_over_unity_input_smoothly() {
+        let inputs: Vec<f32> = (-400..=400).map(|i| i as f32 / 100.0).collect();
+        let outputs: Vec<f32> = inputs.iter().map(|&s| soft_limit(s)).collect();
+
+        assert!(outputs.iter().all(|s| s.abs() <= 1.0));
+        assert!(outputs.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert_eq!(soft_limit(0.5), 0.5);
+        assert_eq!(soft_limit(-3.0), -soft_limit(3.0));
+        // Full scale is bent down rather than hit, and the curve leaves the
+        // knee with the same slope it arrived at.
+        assert!(soft_limit(1.0) < 0.96);
+        let slope = (soft_limit(LIMITER_KNEE + 0.01) - LIMITER_KNEE) / 0.01;
+        assert!(slope > 0.99, "slope past knee {slope}");
+    }
+
+    #[test]
+    fn prepare_audio_matches_across_device_rates() {
+        // A quiet two-tone "voice" so the automatic gain path is exercised too.
+        let signal = |rate: u32| -> Vec<f32> {
+            (0..rate as usize)
+                .map(|i| {
+                    let t = i as f32 / rate as f32;
+                    0.01 * (2.0 * std::f32::consts::PI * 220.0 * t).sin()
+                        + 0.005 * (2.0 * std::f32::consts::PI * 1_000.0 * t).sin()
+                })
+                .collect()
+        };
+
+        let from_44k = prepare_audio(&signal(44_100), 44_100);
+        let from_48k = prepare_audio(&signal(48_000), 48_000);
+
+        assert_eq!(from_44k.len(), 16_000);
+        assert_eq!(from_48k.len(), 16_000);
+        let max_diff = from_44k
+            .iter()
+            .zip(&from_48k)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0_f32, f32::max);
+        assert!(max_diff < 0.01, "max sample difference {max_diff}");
+    }
+}
@@ -1,5 +1,53 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { AppStatus, AudioInputStatus, HistoryEntry, ModelInfo } from './types';
+import type {
+  ActivityThresholds,
+  AnalyticsBucket,
+  AnalyticsPoint,
+  AppStatus,
+  AudioInputStatus,
+  AutoStop,
+  BackupManifest,
+  CaptureBufferSize,
+  ContinuousStatus,
+  CopyFormat,
+  Correction,
+  DbTuning,
+  DecodeParams,
+  DualMic,
+  ExportFormat,
+  FileTranscription,
+  Heartbeat,
+  HistoryEntry,
+  HistorySummary,
+  HotkeyMode,
+  HotkeyPreview,
+  InputDeviceInfo,
+  LatencyPriority,
+  LiveOutput,
+  LogLevel,
+  MicrophonePermission,
+  ModelInfo,
+  NumberFormat,
+  OutputTarget,
+  PartialDownload,
+  PasteMode,
+  PauseTrim,
+  PipelineProfile,
+  PowerModels,
+  PreprocessingPreview,
+  RecordingSummary,
+  RestoreReport,
+  SearchIndexReport,
+  ShortRecording,
+  SpeakerTurns,
+  SubtitleFormat,
+  SyncMode,
+  SystemInfo,
+  TimestampOutput,
+  TranscriptionEstimate,
+  TranscriptionMode,
+  TranscriptSegment
+} from './types';
 
 const bridgeMissingError =
   'Tauri bridge unavailable. Use the Murmur app window from the tray (not a standalone browser tab).';
@@ -16,34 +64,402 @@ const safeInvoke = async <T>(command: string, args?: Record<string, unknown>): P
   }
 };
 
-export const startRecording = (): Promise<void> => safeInvoke('start_recording');
-export const stopRecording = (): Promise<void> => safeInvoke('stop_recording');
-export const toggleRecording = (): Promise<void> => safeInvoke('toggle_recording');
+export const startRecording = (mode?: TranscriptionMode): Promise<void> =>
+  safeInvoke('start_recording', { mode });
+export const stopRecording = (): Promise<RecordingSummary> => safeInvoke('stop_recording');
+export const toggleRecording = (mode?: TranscriptionMode): Promise<void> =>
+  safeInvoke('toggle_recording', { mode });
+export const pauseRecording = (): Promise<boolean> => safeInvoke('pause_recording');
+export const resumeRecording = (): Promise<boolean> => safeInvoke('resume_recording');
 export const cancelTranscription = (): Promise<boolean> => safeInvoke('cancel_transcription');
+export const retryLastTranscription = (): Promise<void> => safeInvoke('retry_last_transcription');
+
+export const forceReset = (): Promise<AppStatus> => safeInvoke('force_reset');
+
+export const ping = (): Promise<Heartbeat> => safeInvoke('ping');
+
+export const recoverState = (): Promise<AppStatus> => safeInvoke('recover_state');
+
+export const startQuickNote = (): Promise<void> => safeInvoke('start_quick_note');
+
+export const startContinuousDictation = (): Promise<void> =>
+  safeInvoke('start_continuous_dictation');
+
+export const stopContinuousDictation = (): Promise<boolean> =>
+  safeInvoke('stop_continuous_dictation');
+
+export const getContinuousDictation = (): Promise<ContinuousStatus | null> =>
+  safeInvoke('get_continuous_dictation');
+
+export const getQuickNoteSeconds = (): Promise<number> => safeInvoke('get_quick_note_seconds');
+
+export const setQuickNoteSeconds = (seconds: number): Promise<number> =>
+  safeInvoke('set_quick_note_seconds', { seconds });
+
+export const getMaxRecordingSeconds = (): Promise<number> =>
+  safeInvoke('get_max_recording_seconds');
+
+export const setMaxRecordingSeconds = (seconds: number): Promise<number> =>
+  safeInvoke('set_max_recording_seconds', { seconds });
+
+export const transcribeFile = (path: string): Promise<FileTranscription> =>
+  safeInvoke('transcribe_file', { path });
+
+export const transcribeClipboardAudio = (): Promise<FileTranscription> =>
+  safeInvoke('transcribe_clipboard_audio');
+
+export const previewPreprocessing = (
+  path: string | null,
+  outputPath: string | null
+): Promise<PreprocessingPreview> => safeInvoke('preview_preprocessing', { path, outputPath });
+
+export const profilePipeline = (wavPath: string): Promise<PipelineProfile> =>
+  safeInvoke('profile_pipeline', { wavPath });
+
+export const transcribeSegments = (wavPath: string): Promise<TranscriptSegment[]> =>
+  safeInvoke('transcribe_segments', { wavPath });
 
 export const getHistory = (limit = 15): Promise<HistoryEntry[]> =>
   safeInvoke('get_history', { limit });
 
+export const listHistorySummaries = (limit = 15, previewChars = 120): Promise<HistorySummary[]> =>
+  safeInvoke('list_history_summaries', { limit, previewChars });
+
+export const getTranscription = (id: number): Promise<HistoryEntry> =>
+  safeInvoke('get_transcription', { id });
+
+export const searchHistory = (query: string, limit?: number): Promise<HistoryEntry[]> =>
+  safeInvoke('search_history', { query, limit });
+
 export const getAppState = (): Promise<AppStatus> => safeInvoke('get_app_state');
 
 export const copyText = (text: string): Promise<void> => safeInvoke('copy_text', { text });
 
+export const copyFormatted = (id: number, format: CopyFormat = 'plain'): Promise<void> =>
+  safeInvoke('copy_formatted', { id, format });
+
+export const updateTranscription = (id: number, text: string): Promise<HistoryEntry> =>
+  safeInvoke('update_transcription', { id, text });
+
+export const retranscribe = (id: number, model: string): Promise<HistoryEntry> =>
+  safeInvoke('retranscribe', { id, model });
+
 export const deleteTranscription = (id: number): Promise<void> =>
   safeInvoke('delete_transcription', { id });
 
+export const exportSearchResults = (
+  query: string,
+  format: ExportFormat,
+  path: string
+): Promise<number> => safeInvoke('export_search_results', { query, format, path });
+
+export const exportHistory = (format: ExportFormat, path: string): Promise<number> =>
+  safeInvoke('export_history', { format, path });
+
+export const exportSubtitles = (
+  id: number,
+  format: SubtitleFormat,
+  path: string
+): Promise<number> => safeInvoke('export_subtitles', { id, format, path });
+
+export const submitCorrection = (id: number, correctedText: string | null): Promise<Correction> =>
+  safeInvoke('submit_correction', { id, correctedText });
+
+export const listCorrections = (limit?: number): Promise<Correction[]> =>
+  safeInvoke('list_corrections', { limit });
+
+export const listEmptyTranscriptions = (limit?: number): Promise<HistoryEntry[]> =>
+  safeInvoke('list_empty_transcriptions', { limit: limit ?? null });
+
+export const deleteEmptyTranscriptions = (): Promise<number> =>
+  safeInvoke('delete_empty_transcriptions');
+
+export const clearHistory = (vacuum?: boolean): Promise<number> =>
+  safeInvoke('clear_history', { vacuum });
+
+export const getMaxHistoryRows = (): Promise<number | null> => safeInvoke('get_max_history_rows');
+
+export const setMaxHistoryRows = (maxRows: number | null): Promise<number> =>
+  safeInvoke('set_max_history_rows', { maxRows });
+
+export const getHistoryRetentionDays = (): Promise<number | null> =>
+  safeInvoke('get_history_retention_days');
+
+export const setHistoryRetentionDays = (days: number | null): Promise<number> =>
+  safeInvoke('set_history_retention_days', { days });
+
+export const pruneHistory = (): Promise<number> => safeInvoke('prune_history');
+
+export const enforceHistoryCap = (): Promise<number> => safeInvoke('enforce_history_cap');
+
+export const getDictationAnalytics = (
+  bucket: AnalyticsBucket,
+  limit?: number
+): Promise<AnalyticsPoint[]> => safeInvoke('dictation_analytics', { bucket, limit });
+
+export const reindexSearch = (): Promise<SearchIndexReport> => safeInvoke('reindex_search');
+
 export const listModels = (): Promise<ModelInfo[]> => safeInvoke('list_models');
 
-export const setActiveModel = (fileName: string): Promise<void> =>
+export const checkModelsDir = (): Promise<void> => safeInvoke('check_models_dir');
+
+export const renameModel = (oldName: string, newName: string): Promise<string> =>
+  safeInvoke('rename_model', { oldName, newName });
+
+export const deleteModel = (fileName: string): Promise<number> =>
+  safeInvoke('delete_model', { fileName });
+
+export const setActiveModel = (fileName: string): Promise<string> =>
   safeInvoke('set_active_model', { fileName });
 
+export const cancelModelDownload = (fileName: string): Promise<boolean> =>
+  safeInvoke('cancel_model_download', { fileName });
+
+export const listResumableDownloads = (): Promise<PartialDownload[]> =>
+  safeInvoke('list_resumable_downloads');
+
+export const resumeModelDownload = (fileName: string): Promise<void> =>
+  safeInvoke('resume_model_download', { fileName });
+
+export const discardModelDownload = (fileName: string): Promise<boolean> =>
+  safeInvoke('discard_model_download', { fileName });
+
+export const getModelMirrorPrefix = (): Promise<string | null> =>
+  safeInvoke('get_model_mirror_prefix');
+
+export const setModelMirrorPrefix = (prefix: string | null): Promise<string | null> =>
+  safeInvoke('set_model_mirror_prefix', { prefix });
+
+export const getTimestampOutput = (): Promise<TimestampOutput> =>
+  safeInvoke('get_timestamp_output');
+
+export const setTimestampOutput = (output: TimestampOutput): Promise<TimestampOutput> =>
+  safeInvoke('set_timestamp_output', { output });
+
+export const getLocale = (): Promise<string | null> => safeInvoke('get_locale');
+
+export const setLocale = (locale: string | null): Promise<string | null> =>
+  safeInvoke('set_locale', { locale });
+
+export const getOutputTarget = (): Promise<OutputTarget> => safeInvoke('get_output_target');
+
+export const setOutputTarget = (target: OutputTarget): Promise<OutputTarget> =>
+  safeInvoke('set_output_target', { target });
+
+export const getPowerModels = (): Promise<PowerModels> => safeInvoke('get_power_models');
+
+export const setPowerModels = (models: PowerModels): Promise<PowerModels> =>
+  safeInvoke('set_power_models', { models });
+
+export const getLiveOutput = (): Promise<LiveOutput> => safeInvoke('get_live_output');
+
+export const setLiveOutput = (output: LiveOutput): Promise<LiveOutput> =>
+  safeInvoke('set_live_output', { output });
+
 export const getHotkey = (): Promise<string> => safeInvoke('get_hotkey');
 
 export const setHotkey = (hotkey: string): Promise<string> => safeInvoke('set_hotkey', { hotkey });
 
+export const getHotkeyMode = (): Promise<HotkeyMode> => safeInvoke('get_hotkey_mode');
+
+export const setHotkeyMode = (mode: HotkeyMode): Promise<HotkeyMode> =>
+  safeInvoke('set_hotkey_mode', { mode });
+
+export const resyncHotkey = (): Promise<string> => safeInvoke('resync_hotkey');
+
+export const canonicalizeHotkeyPreview = (raw: string): Promise<HotkeyPreview> =>
+  safeInvoke('canonicalize_hotkey_preview', { raw });
+
+export const getWindowToggleHotkey = (): Promise<string | null> =>
+  safeInvoke('get_window_toggle_hotkey');
+
+export const setWindowToggleHotkey = (hotkey: string | null): Promise<string | null> =>
+  safeInvoke('set_window_toggle_hotkey', { hotkey });
+
 export const getAutoCopy = (): Promise<boolean> => safeInvoke('get_auto_copy');
 
 export const setAutoCopy = (enabled: boolean): Promise<boolean> =>
   safeInvoke('set_auto_copy', { enabled });
 
+export const getAutoPaste = (): Promise<boolean> => safeInvoke('get_auto_paste');
+
+export const setAutoPaste = (enabled: boolean): Promise<boolean> =>
+  safeInvoke('set_auto_paste', { enabled });
+
+export const getStreamTyping = (): Promise<boolean> => safeInvoke('get_stream_typing');
+
+export const setStreamTyping = (enabled: boolean): Promise<boolean> =>
+  safeInvoke('set_stream_typing', { enabled });
+
+export const getPasteMode = (): Promise<PasteMode> => safeInvoke('get_paste_mode');
+
+export const setPasteMode = (mode: PasteMode): Promise<PasteMode> =>
+  safeInvoke('set_paste_mode', { mode });
+
+export const getKeepAudio = (): Promise<boolean> => safeInvoke('get_keep_audio');
+
+export const setKeepAudio = (enabled: boolean): Promise<boolean> =>
+  safeInvoke('set_keep_audio', { enabled });
+
+export const getLowPriority = (): Promise<boolean> => safeInvoke('get_low_priority');
+
+export const setLowPriority = (enabled: boolean): Promise<boolean> =>
+  safeInvoke('set_low_priority', { enabled });
+
+export const getNumberFormat = (): Promise<NumberFormat> => safeInvoke('get_number_format');
+
+export const setNumberFormat = (format: NumberFormat): Promise<NumberFormat> =>
+  safeInvoke('set_number_format', { format });
+
 export const getAudioInputStatus = (): Promise<AudioInputStatus> =>
   safeInvoke('get_audio_input_status');
+
+export const microphonePermissionStatus = (): Promise<MicrophonePermission> =>
+  safeInvoke('microphone_permission_status');
+
+export const requestMicrophonePermission = (): Promise<MicrophonePermission> =>
+  safeInvoke('request_microphone_permission');
+
+export const accessibilityPermissionStatus = (): Promise<boolean> =>
+  safeInvoke('accessibility_permission_status');
+
+export const requestAccessibilityPermission = (): Promise<boolean> =>
+  safeInvoke('request_accessibility_permission');
+
+export const estimateTranscriptionTime = (
+  durationMs: number,
+  model?: string
+): Promise<TranscriptionEstimate> =>
+  safeInvoke('estimate_transcription_time', { durationMs, model: model ?? null });
+
+export const getSystemInfo = (): Promise<SystemInfo> => safeInvoke('system_info');
+
+export const getTranscriptionMode = (): Promise<TranscriptionMode> =>
+  safeInvoke('get_transcription_mode');
+
+export const setTranscriptionMode = (mode: TranscriptionMode): Promise<TranscriptionMode> =>
+  safeInvoke('set_transcription_mode', { mode });
+
+export const getShortRecording = (): Promise<ShortRecording> => safeInvoke('get_short_recording');
+
+export const setShortRecording = (shortRecording: ShortRecording): Promise<ShortRecording> =>
+  safeInvoke('set_short_recording', { shortRecording });
+
+export const getCaptureWarmupMs = (): Promise<number> => safeInvoke('get_capture_warmup_ms');
+
+export const setCaptureWarmupMs = (warmupMs: number): Promise<number> =>
+  safeInvoke('set_capture_warmup_ms', { warmupMs });
+
+export const getLatencyPriority = (): Promise<LatencyPriority> =>
+  safeInvoke('get_latency_priority');
+
+export const setLatencyPriority = (priority: LatencyPriority): Promise<LatencyPriority> =>
+  safeInvoke('set_latency_priority', { priority });
+
+export const getLanguage = (): Promise<string | null> => safeInvoke('get_language');
+
+export const setLanguage = (language: string | null): Promise<string | null> =>
+  safeInvoke('set_language', { language });
+
+export const getInitialPrompt = (): Promise<string | null> => safeInvoke('get_initial_prompt');
+
+export const setInitialPrompt = (prompt: string | null): Promise<string | null> =>
+  safeInvoke('set_initial_prompt', { prompt });
+
+export const getDecodeOptions = (): Promise<DecodeParams | null> =>
+  safeInvoke('get_decode_options');
+
+export const setDecodeOptions = (options: DecodeParams | null): Promise<DecodeParams | null> =>
+  safeInvoke('set_decode_options', { options });
+
+export const getUseGpu = (): Promise<boolean> => safeInvoke('get_use_gpu');
+
+export const setUseGpu = (enabled: boolean): Promise<boolean> =>
+  safeInvoke('set_use_gpu', { enabled });
+
+export const getGpuBackend = (): Promise<string | null> => safeInvoke('get_gpu_backend');
+
+export const getPreloadModel = (): Promise<boolean> => safeInvoke('get_preload_model');
+
+export const setPreloadModel = (enabled: boolean): Promise<boolean> =>
+  safeInvoke('set_preload_model', { enabled });
+
+export const getDeterministic = (): Promise<boolean> => safeInvoke('get_deterministic');
+
+export const setDeterministic = (enabled: boolean): Promise<boolean> =>
+  safeInvoke('set_deterministic', { enabled });
+
+export const getDebugMode = (): Promise<boolean> => safeInvoke('get_debug_mode');
+
+export const setDebugMode = (enabled: boolean): Promise<boolean> =>
+  safeInvoke('set_debug_mode', { enabled });
+
+export const listInputDevices = (): Promise<string[]> => safeInvoke('list_input_devices');
+
+export const listAudioDevices = (): Promise<InputDeviceInfo[]> => safeInvoke('list_audio_devices');
+
+export const getInputDevice = (): Promise<string | null> => safeInvoke('get_input_device');
+
+export const setInputDevice = (deviceId: string | null): Promise<string | null> =>
+  safeInvoke('set_input_device', { deviceId });
+
+export const getDualMic = (): Promise<DualMic> => safeInvoke('get_dual_mic');
+
+export const setDualMic = (dual: DualMic): Promise<DualMic> => safeInvoke('set_dual_mic', { dual });
+
+export const getSpeakerTurns = (): Promise<SpeakerTurns> => safeInvoke('get_speaker_turns');
+
+export const setSpeakerTurns = (turns: SpeakerTurns): Promise<SpeakerTurns> =>
+  safeInvoke('set_speaker_turns', { turns });
+
+export const getPauseTrim = (): Promise<PauseTrim> => safeInvoke('get_pause_trim');
+
+export const setPauseTrim = (trim: PauseTrim): Promise<PauseTrim> =>
+  safeInvoke('set_pause_trim', { trim });
+
+export const getActivityThresholds = (): Promise<ActivityThresholds> =>
+  safeInvoke('get_activity_thresholds');
+
+export const setActivityThresholds = (
+  thresholds: ActivityThresholds
+): Promise<ActivityThresholds> => safeInvoke('set_activity_thresholds', { thresholds });
+
+export const getAutoStop = (): Promise<AutoStop> => safeInvoke('get_auto_stop');
+
+export const setAutoStop = (autoStop: AutoStop): Promise<AutoStop> =>
+  safeInvoke('set_auto_stop', { autoStop });
+
+export const getCaptureBufferSize = (): Promise<CaptureBufferSize> =>
+  safeInvoke('get_capture_buffer_size');
+
+export const setCaptureBufferSize = (bufferSize: CaptureBufferSize): Promise<CaptureBufferSize> =>
+  safeInvoke('set_capture_buffer_size', { bufferSize });
+
+export const exportSettings = (): Promise<string> => safeInvoke('export_settings');
+
+export const importSettings = (json: string): Promise<void> => safeInvoke('import_settings', { json });
+
+export const exportBackup = (destZip: string, includeModels: boolean): Promise<BackupManifest> =>
+  safeInvoke('export_backup', { destZip, includeModels });
+
+export const importBackup = (srcZip: string): Promise<RestoreReport> =>
+  safeInvoke('import_backup', { srcZip });
+
+export const resetWindowPosition = (): Promise<void> => safeInvoke('reset_window_position');
+
+export const getLogPath = (): Promise<string> => safeInvoke('get_log_path');
+
+export const openLog = (): Promise<void> => safeInvoke('open_log');
+
+export const openTranscriptionAudio = (id: number, reveal?: boolean): Promise<void> =>
+  safeInvoke('open_transcription_audio', { id, reveal });
+
+export const getLogLevel = (): Promise<LogLevel> => safeInvoke('get_log_level');
+
+export const setLogLevel = (level: LogLevel): Promise<LogLevel> =>
+  safeInvoke('set_log_level', { level });
+
+export const getDbTuning = (): Promise<DbTuning> => safeInvoke('get_db_tuning');
+
+export const setDbTuning = (synchronous: SyncMode, busyTimeoutMs: number): Promise<DbTuning> =>
+  safeInvoke('set_db_tuning', { synchronous, busyTimeoutMs });
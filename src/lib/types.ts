@@ -1,11 +1,67 @@
 export type AppStatus = 'idle' | 'recording' | 'processing' | 'cancelling';
 
+export type NumberFormat = 'off' | 'digits' | 'words';
+
+export type OutputTarget = 'clipboard' | { file: string } | { both: string };
+
+export type LatencyPriority = 'fast' | 'balanced' | 'accurate';
+
+export interface DecodeParams {
+  best_of: number;
+  beam_size: number | null;
+  temperature_fallback: boolean;
+  temperature: number;
+  entropy_thold: number;
+  logprob_thold: number;
+}
+
+export type TimestampFormat = 'minutes_seconds' | 'hours_minutes_seconds';
+
+export interface TimestampOutput {
+  enabled: boolean;
+  format: TimestampFormat;
+}
+
+export type TranscriptionSource = 'recording' | 'hotkey' | 'quick_note' | 'file' | 'continuous';
+
+export interface Heartbeat {
+  beat: number;
+  status: AppStatus | null;
+}
+
 export interface HistoryEntry {
   id: number;
   text: string;
   created_at: string;
   duration_ms: number | null;
   model: string;
+  verbatim: boolean;
+  source: TranscriptionSource;
+  language: string | null;
+  avg_confidence: number | null;
+}
+
+export interface Correction {
+  id: number;
+  transcription_id: number | null;
+  original_text: string;
+  corrected_text: string | null;
+  model: string;
+  capture_config: string | null;
+  created_at: string;
+}
+
+export interface HistorySummary {
+  id: number;
+  preview: string;
+  text_chars: number;
+  created_at: string;
+  duration_ms: number | null;
+  model: string;
+  verbatim: boolean;
+  source: TranscriptionSource;
+  language: string | null;
+  avg_confidence: number | null;
 }
 
 export interface ModelInfo {
@@ -17,12 +73,107 @@ export interface ModelInfo {
   download_url: string | null;
 }
 
+export interface PartialDownload {
+  file_name: string;
+  label: string;
+  bytes: number;
+}
+
 export interface TranscriptionCompletePayload {
   id: number;
   text: string;
   duration_ms: number;
   model: string;
   auto_copied: boolean;
+  auto_pasted: boolean;
+  verbatim: boolean;
+  avg_confidence: number | null;
+}
+
+export type CaptureBufferSize = 'default' | { fixed: number };
+
+export interface TranscriptionTiming {
+  id: number;
+  model: string;
+  audio_ms: number;
+  capture_stop_ms: number;
+  prepare_ms: number;
+  model_load_ms: number;
+  model_cached: boolean;
+  decode_ms: number;
+  whisper_ms: number;
+  postprocess_ms: number;
+  insert_ms: number;
+  copy_ms: number;
+  total_ms: number;
+}
+
+export type DualMicMode = 'off' | 'mix' | 'loudest';
+
+export interface DualMic {
+  mode: DualMicMode;
+  primary: string | null;
+  secondary: string | null;
+}
+
+export interface SpeakerTurns {
+  enabled: boolean;
+  labels: [string, string];
+}
+
+export interface PauseTrim {
+  enabled: boolean;
+  max_pause_ms: number;
+}
+
+export interface ActivityThresholds {
+  meter: number;
+  decision: number;
+}
+
+export interface AutoStop {
+  enabled: boolean;
+  silence_timeout_ms: number;
+  min_speech_ms: number;
+}
+
+export interface CaptureSignalStats {
+  rms: number;
+  peak: number;
+  active_ratio: number;
+}
+
+export interface RecordingSummary {
+  duration_ms: number;
+  sample_rate: number;
+  truncated: boolean;
+  signal: CaptureSignalStats;
+}
+
+export interface PreprocessingPreview {
+  duration_ms: number;
+  input: CaptureSignalStats;
+  processed: CaptureSignalStats;
+  written_to: string | null;
+}
+
+export interface PipelineProfile {
+  audio_ms: number;
+  source_rate: number;
+  resample_ms: number;
+  preprocess_ms: number;
+  model_load_ms: number;
+  model_cached: boolean;
+  decode_ms: number;
+  threads: number;
+  text: string;
+}
+
+export interface TranscriptSegment {
+  start_ms: number;
+  end_ms: number;
+  text: string;
+  avg_prob: number;
 }
 
 export interface ErrorPayload {
@@ -40,3 +191,176 @@ export interface AudioInputStatus {
   ok: boolean;
   message: string | null;
 }
+
+export interface InputDeviceInfo {
+  id: string;
+  name: string;
+  is_default: boolean;
+  default_sample_rate: number | null;
+}
+
+export type MicrophonePermission = 'authorized' | 'denied' | 'restricted' | 'not_determined';
+
+export interface TranscriptionEstimate {
+  model: string;
+  low_ms: number;
+  high_ms: number;
+  benchmark_runs: number;
+}
+
+export interface ModelHeader {
+  format: string;
+  model_type: string;
+  multilingual: boolean;
+  approx_params_millions: number | null;
+  n_vocab: number;
+  n_audio_layer: number;
+  n_text_layer: number;
+  n_mels: number;
+  ftype: string;
+}
+
+export interface SystemInfo {
+  app_version: string;
+  whisper_rs_version: string;
+  whisper_backend: string;
+  gpu_compiled: boolean;
+  os: string;
+  arch: string;
+  cpu_cores: number;
+  active_model: string;
+  model_header: ModelHeader | null;
+  model_header_error: string | null;
+  report: string;
+}
+
+export type HotkeyMode = 'toggle' | 'push_to_talk';
+
+export type PasteMode = 'insert' | 'append_with_space' | 'replace_selection';
+
+export interface HotkeyPreview {
+  canonical: string;
+  display: string;
+}
+
+export type CopyFormat = 'plain' | 'html';
+
+export type ExportFormat = 'json' | 'csv' | 'markdown';
+
+export type SubtitleFormat = 'srt' | 'vtt';
+
+export type TranscriptionMode = 'clean' | 'verbatim';
+
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug';
+
+export type SyncMode = 'full' | 'normal';
+
+export interface DbTuning {
+  synchronous: SyncMode;
+  busy_timeout_ms: number;
+}
+
+export interface SearchIndexReport {
+  transcription_rows: number;
+  indexed_rows: number;
+  integrity_ok: boolean;
+  issues: string[];
+}
+
+export type AnalyticsBucket = 'day' | 'week';
+
+export interface AnalyticsPoint {
+  bucket_start: string;
+  transcriptions: number;
+  words: number;
+  duration_ms: number;
+  words_per_minute: number | null;
+}
+
+export interface PowerModels {
+  ac: string | null;
+  battery: string | null;
+}
+
+export interface ModelChangedPayload {
+  file_name: string;
+  power_source: 'ac' | 'battery' | null;
+}
+
+export interface LiveOutput {
+  file: string | null;
+  template: string;
+  separator: string;
+}
+
+export interface BackupManifest {
+  format_version: number;
+  app_version: string;
+  models: string[];
+}
+
+export interface RestoreReport {
+  manifest: BackupManifest;
+  transcriptions: number;
+  models_restored: string[];
+}
+
+export type ShortRecordingPolicy = 'error' | 'ignore' | 'transcribe';
+
+export interface ShortRecording {
+  min_ms: number;
+  policy: ShortRecordingPolicy;
+}
+
+export interface RecordingTooShortPayload {
+  duration_ms: number;
+  min_ms: number;
+  policy: ShortRecordingPolicy;
+}
+
+export interface RecordingTimeWarningPayload {
+  remaining_ms: number;
+  max_ms: number;
+}
+
+export interface FileTranscription {
+  id: number;
+  path: string;
+  text: string;
+  duration_ms: number;
+  model: string;
+}
+
+export interface FileTranscriptionProgress {
+  path: string;
+  chunk_index: number;
+  total_chunks: number;
+  percent: number;
+  elapsed_ms: number;
+  partial_text: string;
+}
+
+export interface FileTranscriptionErrorPayload {
+  path: string;
+  message: string;
+}
+
+export interface ContinuousStatus {
+  utterances: number;
+  elapsed_ms: number;
+  stopping: boolean;
+  text: string;
+}
+
+export interface ContinuousUtterance {
+  id: number;
+  text: string;
+  utterances: number;
+  elapsed_ms: number;
+}
+
+export type ContinuousStopReason = 'requested' | 'session_limit' | 'interrupted' | 'failed';
+
+export interface ContinuousStopped extends ContinuousStatus {
+  reason: ContinuousStopReason;
+}